@@ -0,0 +1,19 @@
+// Looks for the end of the cover image in arbitrary bytes, both by walking the
+// PNG chunks and by scanning for IEND. Neither may point past the data.
+
+#![no_main]
+
+use std::io::Cursor;
+use libfuzzer_sys::fuzz_target;
+use rewind::io::find_iend;
+use rewind::png::image_end;
+
+fuzz_target!(|data: &[u8]| {
+	let len = data.len() as u64;
+	if let Some(end) = find_iend(&mut Cursor::new(data)) {
+		assert!(end <= len);
+	}
+	if let Ok(end) = image_end(&mut Cursor::new(data)) {
+		assert!(end <= len);
+	}
+});
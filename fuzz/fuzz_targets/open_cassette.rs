@@ -0,0 +1,20 @@
+// Opens arbitrary bytes as an in-memory cassette, the way players verify and
+// parse a file. The bytes are sealed with their own CRC32 first, so inputs get
+// past the checksum and into the TOC, strict checks and track lookups.
+
+#![no_main]
+
+use std::io::Cursor;
+use libfuzzer_sys::fuzz_target;
+use rewind::format::Cassette;
+
+fuzz_target!(|data: &[u8]| {
+	let mut sealed = data.to_vec();
+	sealed.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+
+	let Ok(cassette) = Cassette::from_bytes(sealed.clone()) else { return };
+	let _ = cassette.layout.check_strict(&mut Cursor::new(&sealed));
+	for index in 0..=cassette.layout.header.entries.len() {
+		let _ = cassette.track(index);
+	}
+});
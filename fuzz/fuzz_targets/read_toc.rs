@@ -0,0 +1,13 @@
+// Parses arbitrary bytes as a TOC. Whatever parses must encode back to a TOC
+// that parses to the same thing.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rewind::format::CassetteHeader;
+
+fuzz_target!(|data: &[u8]| {
+	let Ok(header) = CassetteHeader::read(&mut &data[..]) else { return };
+	let reread = CassetteHeader::read(&mut &header.to_bytes()[..]).expect("a written TOC must parse");
+	assert_eq!(header, reread);
+});
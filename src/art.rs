@@ -0,0 +1,80 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// ART MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Per-track artwork. `record --art <index>:<image>` shrinks a PNG/JPEG to a
+// small PNG thumbnail that is stored in the track's TOC entry (format v3+).
+// Players show a track's thumbnail next to it and fall back to a thumbnail of
+// the cassette cover for tracks without one.
+
+use std::io::{BufReader, Cursor};
+use std::path::Path;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat, ImageReader};
+use crate::io::open_file;
+use crate::format::MAX_ART_LEN;
+
+/// Thumbnails are scaled down to fit in a square of this many pixels.
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+/// Artwork for one of the recorded tracks.
+#[derive(Clone, Debug)]
+pub struct ArtSpec {
+	/// 1-based track index, matching the order of the audio files.
+	pub track: usize,
+	pub path: String,
+}
+
+/// Parses `<index>:<image>`, e.g. `2:art/track2.jpg`.
+pub fn parse_art(text: &str) -> Result<ArtSpec, String> {
+	let usage = || format!("Invalid art '{}'. Expected <index>:<image>, e.g. 2:track2.jpg", text);
+
+	let (index, path) = text.split_once(':').ok_or_else(usage)?;
+	let track: usize = index.trim().parse().map_err(|_| usage())?;
+	if track == 0 {
+		return Err("Track indices start at 1.".to_string());
+	}
+	if path.trim().is_empty() {
+		return Err(usage());
+	}
+
+	Ok(ArtSpec { track, path: path.to_string() })
+}
+
+/// Loads a PNG/JPEG image and returns it as a PNG thumbnail small enough for the TOC.
+pub fn make_thumbnail(path: &str) -> Result<Vec<u8>, String> {
+	let img = ImageReader::new(BufReader::new(open_file(path)?))
+		.with_guessed_format()
+		.map_err(|e| format!("Cannot read artwork '{}': {}", path, e))?
+		.decode()
+		.map_err(|e| format!("Cannot decode artwork '{}' (PNG or JPEG expected): {}", path, e))?;
+
+	let png = encode_thumbnail(&img).map_err(|e| format!("Cannot shrink artwork '{}': {}", path, e))?;
+	if png.len() > MAX_ART_LEN {
+		return Err(format!("Artwork '{}' is still too large after shrinking ({} bytes, max {}).", path, png.len(), MAX_ART_LEN));
+	}
+	Ok(png)
+}
+
+/// Thumbnail of a cassette's cover image, the fallback art for tracks without their own.
+pub fn cover_thumbnail(cassette_path: &Path) -> Option<Vec<u8>> {
+	// The PNG decoder stops at IEND, so the tape after it is never read
+	let file = open_file(cassette_path).ok()?;
+	let img = ImageReader::with_format(BufReader::new(file), ImageFormat::Png).decode().ok()?;
+	encode_thumbnail(&img).ok()
+}
+
+/// Decodes a PNG thumbnail into `size`×`size` RGB pixels (row-major), for drawing in a terminal.
+pub fn preview_pixels(png: &[u8], size: u32) -> Option<Vec<[u8; 3]>> {
+	let img = image::load_from_memory_with_format(png, ImageFormat::Png).ok()?;
+	let small = img.resize_exact(size, size, FilterType::Triangle).to_rgb8();
+	Some(small.pixels().map(|p| p.0).collect())
+}
+
+/// Scales an image to fit `THUMBNAIL_SIZE` (keeping its aspect ratio) and encodes it as PNG.
+fn encode_thumbnail(img: &DynamicImage) -> Result<Vec<u8>, String> {
+	let thumb = DynamicImage::ImageRgb8(img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8());
+	let mut bytes = Cursor::new(Vec::new());
+	thumb.write_to(&mut bytes, ImageFormat::Png).map_err(|e| e.to_string())?;
+	Ok(bytes.into_inner())
+}
@@ -0,0 +1,119 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// BALANCE MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Left/right balance for stereo tracks, wrapped as a rodio `Source`. Moving the
+// balance towards one side only ever turns the other channel down, so it can't
+// push samples past full scale and stacks safely with the volume and EQ. The
+// position lives in a shared `BalanceSettings` so the players can change it
+// while a track is playing; centered (the default) passes samples untouched.
+// Mono and multichannel tracks are left alone.
+
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// Balance runs from -MAX_BALANCE (left only) to +MAX_BALANCE (right only).
+pub const MAX_BALANCE: i32 = 100;
+
+/// Balance position in percent, shared between the UI and the audio thread.
+#[derive(Default)]
+pub struct BalanceSettings {
+	position: AtomicI32,
+}
+
+impl BalanceSettings {
+	pub fn get(&self) -> i32 {
+		self.position.load(Ordering::Relaxed)
+	}
+
+	/// Sets the position, clamped to ±MAX_BALANCE.
+	pub fn set(&self, position: i32) {
+		self.position.store(position.clamp(-MAX_BALANCE, MAX_BALANCE), Ordering::Relaxed);
+	}
+
+	/// Moves the balance right (or left, with a negative delta).
+	pub fn adjust(&self, delta: i32) {
+		self.set(self.get() + delta);
+	}
+
+	/// Gain of the left and right channel for the current position (each 0.0-1.0).
+	fn gains(&self) -> (f32, f32) {
+		let position = self.get() as f32 / MAX_BALANCE as f32;
+		(1.0 - position.max(0.0), 1.0 + position.min(0.0))
+	}
+}
+
+/// Short description for status lines: "centered", "30% left", ...
+pub fn describe(position: i32) -> String {
+	match position {
+		0 => "centered".to_string(),
+		p if p < 0 => format!("{}% left", -p),
+		p => format!("{}% right", p),
+	}
+}
+
+/// A rodio source that applies the shared balance to stereo input.
+pub struct Balance<S: Source> {
+	input: S,
+	settings: Arc<BalanceSettings>,
+	/// Channel gains for the frame being played, refreshed at frame boundaries.
+	gains: (f32, f32),
+	channel: usize,
+}
+
+impl<S: Source> Balance<S> {
+	pub fn new(input: S, settings: Arc<BalanceSettings>) -> Self {
+		let gains = settings.gains();
+		Balance { input, settings, gains, channel: 0 }
+	}
+}
+
+impl<S: Source> Iterator for Balance<S> {
+	type Item = Sample;
+
+	fn next(&mut self) -> Option<Sample> {
+		let sample = self.input.next()?;
+		if self.input.channels() != 2 {
+			return Some(sample);
+		}
+
+		// Pick up changes at frame boundaries so both channels move together
+		if self.channel == 0 {
+			self.gains = self.settings.gains();
+		}
+		let gain = if self.channel == 0 { self.gains.0 } else { self.gains.1 };
+		self.channel = 1 - self.channel;
+		Some(sample * gain)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.input.size_hint()
+	}
+}
+
+impl<S: Source> Source for Balance<S> {
+	fn current_span_len(&self) -> Option<usize> {
+		self.input.current_span_len()
+	}
+
+	fn channels(&self) -> ChannelCount {
+		self.input.channels()
+	}
+
+	fn sample_rate(&self) -> SampleRate {
+		self.input.sample_rate()
+	}
+
+	fn total_duration(&self) -> Option<Duration> {
+		self.input.total_duration()
+	}
+
+	fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+		self.input.try_seek(pos)?;
+		self.channel = 0;
+		Ok(())
+	}
+}
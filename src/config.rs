@@ -0,0 +1,99 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// CONFIG MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Optional defaults from `config.toml` in the user's config directory
+// (`~/.config/rewind/config.toml` on Linux). Every key becomes the default of
+// the matching command-line flag, so flags (and environment variables) still
+// win (`--no-auto-flip` turns a configured `auto_flip` back off). Unknown keys
+// are ignored, so a config written for a newer build still loads; values that
+// don't make sense are skipped with a warning. The file is read before the
+// command line is parsed, so the warnings are handed back to be printed once
+// `--quiet` and the colors are set up.
+//
+//   output_dir = "/home/me/Music/Cassettes"
+//   auto_flip = true
+//   volume = 60
+//   theme = "neon"
+
+use std::fs;
+use std::path::PathBuf;
+use clap::{Command, ValueEnum};
+use serde::Deserialize;
+use crate::cover::Theme;
+
+/// Highest volume, in percent.
+const MAX_VOLUME: u8 = 100;
+
+/// Defaults read from the config file. Missing keys leave the built-in defaults alone.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Default for `--output-dir`.
+	pub output_dir: Option<String>,
+	/// Default for `--auto-flip` (`play`, `tui`).
+	pub auto_flip: Option<bool>,
+	/// Default for `--volume` in percent (`play`, `tui`).
+	pub volume: Option<u8>,
+	/// Default for `--theme` (`record`, `waveform`).
+	pub theme: Option<String>,
+}
+
+impl Config {
+	/// Where the config file lives, if the platform has a config directory.
+	pub fn path() -> Option<PathBuf> {
+		dirs::config_dir().map(|dir| dir.join("rewind").join("config.toml"))
+	}
+
+	/// Reads the config file, along with warnings for the caller to print. A missing file
+	/// gives the built-in defaults; an unreadable or malformed one does too, with a warning.
+	pub fn load() -> (Self, Vec<String>) {
+		let Some(path) = Self::path() else { return (Config::default(), Vec::new()) };
+		let Ok(text) = fs::read_to_string(&path) else { return (Config::default(), Vec::new()) };
+		match toml::from_str::<Config>(&text) {
+			Ok(config) => config.validated(),
+			Err(e) => (Config::default(), vec![format!("Ignoring {}: {}", path.display(), e)]),
+		}
+	}
+
+	/// Drops values the matching flags would reject, with a warning for each.
+	fn validated(mut self) -> (Self, Vec<String>) {
+		let mut warnings = Vec::new();
+		if let Some(volume) = self.volume.filter(|&v| v > MAX_VOLUME) {
+			warnings.push(format!("Ignoring volume = {} in the config file: it must be 0 to {}.", volume, MAX_VOLUME));
+			self.volume = None;
+		}
+		if let Some(theme) = self.theme.take() {
+			match Theme::from_str(&theme, true) {
+				Ok(_) => self.theme = Some(theme.to_lowercase()),
+				Err(_) => warnings.push(format!("Ignoring theme = \"{}\" in the config file: no such theme.", theme)),
+			}
+		}
+		(self, warnings)
+	}
+
+	/// Makes the configured values the defaults of the matching arguments.
+	pub fn apply(self, mut command: Command) -> Command {
+		if let Some(dir) = self.output_dir {
+			command = command.mut_arg("output_dir", |arg| arg.default_value(dir));
+		}
+		if self.auto_flip == Some(true) {
+			command = set_default(command, &["play", "tui"], "auto_flip", "true");
+		}
+		if let Some(volume) = self.volume {
+			command = set_default(command, &["play", "tui"], "volume", &volume.to_string());
+		}
+		if let Some(theme) = self.theme {
+			command = set_default(command, &["record", "waveform"], "theme", &theme);
+		}
+		command
+	}
+}
+
+/// Sets the default of argument `arg` on each of `subcommands`.
+fn set_default(mut command: Command, subcommands: &[&str], arg: &str, value: &str) -> Command {
+	for &name in subcommands {
+		command = command.mut_subcommand(name, |sub| sub.mut_arg(arg, |a| a.default_value(value.to_string())));
+	}
+	command
+}
@@ -5,6 +5,8 @@
 // Defines application-wide constants used throughout the codebase.
 // - IEND_CHUNK: PNG end-of-file marker (where we append audio data)
 // - BUFFER_SIZE: Optimal buffer size for file I/O operations
+// - FLIP_PAUSE: How long an automatic "flip the tape" between sides lasts
+// - FRAGILE_SIZE_RATIO: Payload-to-cover ratio past which image hosts are a risk
 
 pub const IEND_CHUNK: [u8; 12] = [
 	0x00, 0x00, 0x00, 0x00,
@@ -12,4 +14,9 @@ pub const IEND_CHUNK: [u8; 12] = [
 	0xAE, 0x42, 0x60, 0x82
 ];
 
-pub const BUFFER_SIZE: usize = 16384;
\ No newline at end of file
+pub const BUFFER_SIZE: usize = 16384;
+pub const FLIP_PAUSE: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Audio payloads this many times larger than the cover get a note that the cassette
+/// won't survive image hosts and chat apps that re-encode big images.
+pub const FRAGILE_SIZE_RATIO: u64 = 100;
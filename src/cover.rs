@@ -0,0 +1,251 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// COVER MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Generates cover art for cassettes recorded without an image. Draws a simple
+// procedural cassette shell (body, label, reels, tape window) and encodes it
+// as a PNG so the polyglot structure stays valid and the file stays viewable.
+// Generated covers also print the album, artist and track count on the label
+// using a bundled font.
+
+use std::io::Cursor;
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use clap::ValueEnum;
+use image::{ImageFormat, Rgb, RgbImage};
+
+const COVER_WIDTH: u32 = 480;
+const COVER_HEIGHT: u32 = 300;
+
+/// Font used to print label text on generated covers (DejaVu Sans Mono Bold).
+const LABEL_FONT: &[u8] = include_bytes!("../assets/fonts/DejaVuSansMono-Bold.ttf");
+
+/// Where the cassette's PNG prefix comes from.
+pub enum CoverSource {
+	/// An existing PNG file on disk.
+	File(String),
+	/// A procedurally drawn cassette with a blank label.
+	Default(Theme),
+	/// A procedurally drawn cassette labelled from the tracks' metadata.
+	Generated(Theme),
+}
+
+/// Text printed on a generated cover's label.
+pub struct CoverText {
+	pub album: String,
+	pub artist: String,
+	pub track_count: usize,
+}
+
+impl CoverText {
+	/// Summarizes each track's (album, artist) tags into label text.
+	/// Uses the first album found, and "Various Artists" when the artists disagree.
+	pub fn from_tags(tags: &[(Option<String>, Option<String>)]) -> Self {
+		let album = tags.iter()
+			.find_map(|(album, _)| album.clone())
+			.unwrap_or_else(|| "Mixtape".to_string());
+
+		let mut artists = tags.iter().filter_map(|(_, artist)| artist.as_deref());
+		let artist = match artists.next() {
+			Some(first) if artists.all(|a| a == first) => first.to_string(),
+			Some(_) => "Various Artists".to_string(),
+			None => "Unknown Artist".to_string(),
+		};
+
+		CoverText { album, artist, track_count: tags.len() }
+	}
+}
+
+/// Color scheme for generated covers.
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum Theme {
+	/// Cream label on a dark navy shell
+	#[default]
+	Classic,
+	/// Hot pink label on a deep purple shell
+	Neon,
+	/// Faded browns, like a tape left in the sun
+	Sepia,
+	/// Grayscale
+	Mono,
+}
+
+/// Colors used to draw a generated cover.
+struct Palette {
+	background: Rgb<u8>,
+	shell: Rgb<u8>,
+	label: Rgb<u8>,
+	reel: Rgb<u8>,
+	hub: Rgb<u8>,
+	window: Rgb<u8>,
+	ink: Rgb<u8>,
+}
+
+impl Theme {
+	fn palette(self) -> Palette {
+		match self {
+			Theme::Classic => Palette {
+				background: Rgb([26, 26, 46]),
+				shell: Rgb([43, 43, 64]),
+				label: Rgb([240, 230, 210]),
+				reel: Rgb([22, 22, 30]),
+				hub: Rgb([220, 220, 220]),
+				window: Rgb([15, 15, 22]),
+				ink: Rgb([30, 30, 50]),
+			},
+			Theme::Neon => Palette {
+				background: Rgb([18, 6, 32]),
+				shell: Rgb([48, 16, 80]),
+				label: Rgb([255, 70, 160]),
+				reel: Rgb([20, 8, 36]),
+				hub: Rgb([0, 230, 255]),
+				window: Rgb([10, 4, 20]),
+				ink: Rgb([25, 5, 45]),
+			},
+			Theme::Sepia => Palette {
+				background: Rgb([60, 42, 28]),
+				shell: Rgb([112, 84, 58]),
+				label: Rgb([236, 214, 176]),
+				reel: Rgb([52, 36, 24]),
+				hub: Rgb([200, 180, 150]),
+				window: Rgb([40, 28, 18]),
+				ink: Rgb([80, 50, 30]),
+			},
+			Theme::Mono => Palette {
+				background: Rgb([20, 20, 20]),
+				shell: Rgb([60, 60, 60]),
+				label: Rgb([235, 235, 235]),
+				reel: Rgb([25, 25, 25]),
+				hub: Rgb([200, 200, 200]),
+				window: Rgb([12, 12, 12]),
+				ink: Rgb([20, 20, 20]),
+			},
+		}
+	}
+
+	/// Background and wave colors for `rewind waveform`, taken from the shell and label.
+	pub fn waveform_colors(self) -> (Rgb<u8>, Rgb<u8>) {
+		let palette = self.palette();
+		(palette.background, palette.label)
+	}
+}
+
+/// Renders a cassette with a blank label and returns it as PNG bytes.
+pub fn default_cover(theme: Theme) -> Result<Vec<u8>, String> {
+	encode_png(&draw_cassette(&theme.palette()))
+}
+
+/// Renders a cassette whose label shows the album, artist and track count, as PNG bytes.
+pub fn generate_cover(text: &CoverText, theme: Theme) -> Result<Vec<u8>, String> {
+	let palette = theme.palette();
+	let mut img = draw_cassette(&palette);
+	let font = FontRef::try_from_slice(LABEL_FONT)
+		.map_err(|e| format!("Failed to load the bundled label font: {}", e))?;
+
+	// Album across the top of the label, artist and track count on the bottom strip
+	let tracks = if text.track_count == 1 { "1 track".to_string() } else { format!("{} tracks", text.track_count) };
+	draw_text(&mut img, &font, &text.album, 24.0, 66, 340, palette.ink);
+	draw_text(&mut img, &font, &format!("{} · {}", text.artist, tracks), 16.0, 221, 220, palette.label);
+
+	encode_png(&img)
+}
+
+/// Draws a cassette shell centered on the canvas.
+fn draw_cassette(palette: &Palette) -> RgbImage {
+	let mut img = RgbImage::from_pixel(COVER_WIDTH, COVER_HEIGHT, palette.background);
+
+	// Shell and label
+	fill_rounded_rect(&mut img, 40, 40, 400, 220, 18, palette.shell);
+	fill_rounded_rect(&mut img, 64, 60, 352, 130, 8, palette.label);
+
+	// Tape window between the reels
+	fill_rounded_rect(&mut img, 150, 105, 180, 50, 6, palette.window);
+
+	// Reels with hubs
+	for cx in [150, 330] {
+		fill_circle(&mut img, cx, 130, 34, palette.reel);
+		fill_circle(&mut img, cx, 130, 14, palette.hub);
+		fill_circle(&mut img, cx, 130, 6, palette.reel);
+	}
+
+	// Bottom strip where the tape head sits
+	fill_rounded_rect(&mut img, 120, 210, 240, 40, 6, palette.window);
+
+	img
+}
+
+/// Encodes an image to PNG bytes.
+fn encode_png(img: &RgbImage) -> Result<Vec<u8>, String> {
+	let mut bytes = Cursor::new(Vec::new());
+	img.write_to(&mut bytes, ImageFormat::Png)
+		.map_err(|e| format!("Failed to encode generated cover: {}", e))?;
+	Ok(bytes.into_inner())
+}
+
+/// Fills a rectangle with rounded corners, clipped to the canvas.
+fn fill_rounded_rect(img: &mut RgbImage, x: u32, y: u32, w: u32, h: u32, r: u32, color: Rgb<u8>) {
+	for py in y..(y + h).min(img.height()) {
+		for px in x..(x + w).min(img.width()) {
+			// Distance into the nearest corner square, if any
+			let dx = if px < x + r { x + r - px } else if px >= x + w - r { px + 1 - (x + w - r) } else { 0 };
+			let dy = if py < y + r { y + r - py } else if py >= y + h - r { py + 1 - (y + h - r) } else { 0 };
+			if dx * dx + dy * dy <= r * r {
+				img.put_pixel(px, py, color);
+			}
+		}
+	}
+}
+
+/// Fills a circle, clipped to the canvas.
+fn fill_circle(img: &mut RgbImage, cx: u32, cy: u32, r: u32, color: Rgb<u8>) {
+	for py in cy.saturating_sub(r)..(cy + r + 1).min(img.height()) {
+		for px in cx.saturating_sub(r)..(cx + r + 1).min(img.width()) {
+			let dx = px.abs_diff(cx);
+			let dy = py.abs_diff(cy);
+			if dx * dx + dy * dy <= r * r {
+				img.put_pixel(px, py, color);
+			}
+		}
+	}
+}
+
+/// Draws a single line of text horizontally centered on the canvas, with its top at `top`.
+/// Text wider than `max_width` is truncated with an ellipsis.
+fn draw_text(img: &mut RgbImage, font: &FontRef, text: &str, size: f32, top: u32, max_width: u32, color: Rgb<u8>) {
+	let scaled = font.as_scaled(PxScale::from(size));
+	let line_width = |s: &str| -> f32 { s.chars().map(|c| scaled.h_advance(scaled.glyph_id(c))).sum() };
+
+	let mut line = text.to_string();
+	if line_width(&line) > max_width as f32 {
+		while !line.is_empty() && line_width(&format!("{}…", line)) > max_width as f32 {
+			line.pop();
+		}
+		line = line.trim_end().to_string();
+		line.push('…');
+	}
+
+	let mut x = (img.width() as f32 - line_width(&line)) / 2.0;
+	let baseline = top as f32 + scaled.ascent();
+	for c in line.chars() {
+		let glyph = scaled.scaled_glyph(c);
+		let advance = scaled.h_advance(glyph.id);
+		let glyph = ab_glyph::Glyph { position: ab_glyph::point(x, baseline), ..glyph };
+		if let Some(outlined) = font.outline_glyph(glyph) {
+			let bounds = outlined.px_bounds();
+			outlined.draw(|gx, gy, coverage| {
+				let px = bounds.min.x as i32 + gx as i32;
+				let py = bounds.min.y as i32 + gy as i32;
+				if px >= 0 && py >= 0 && (px as u32) < img.width() && (py as u32) < img.height() {
+					let under = img.get_pixel(px as u32, py as u32);
+					img.put_pixel(px as u32, py as u32, blend(*under, color, coverage));
+				}
+			});
+		}
+		x += advance;
+	}
+}
+
+/// Mixes `over` onto `under` by `alpha` (0.0 - 1.0).
+fn blend(under: Rgb<u8>, over: Rgb<u8>, alpha: f32) -> Rgb<u8> {
+	let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * alpha.clamp(0.0, 1.0)).round() as u8;
+	Rgb([mix(under[0], over[0]), mix(under[1], over[1]), mix(under[2], over[2])])
+}
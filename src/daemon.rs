@@ -0,0 +1,215 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// DAEMON MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Headless player for external frontends (web, Electron). Runs the TUI's `App`
+// as the playback engine without rendering, reads newline-delimited JSON
+// commands from stdin and writes one JSON event per line to stdout.
+//
+// Commands (track numbers are 1-based, like the CLI):
+//   {"cmd":"play"}                  play the selected track, or resume if paused
+//   {"cmd":"play","track":2}        play track 2
+//   {"cmd":"pause"}                 pause playback
+//   {"cmd":"resume"}                resume paused playback
+//   {"cmd":"stop"}                  stop playback
+//   {"cmd":"next"} / {"cmd":"previous"}
+//   {"cmd":"volume","level":70}     set the volume (0-100)
+//   {"cmd":"status"}                re-send the state, track and volume events
+//   {"cmd":"quit"}                  stop and exit (closing stdin does the same)
+//
+// Events:
+//   {"event":"ready","tracks":[{"track":1,"title":"…","artist":"…","duration":201,"side":"A"}, …]}
+//   {"event":"track","track":2,"title":"…","artist":"…","duration":201}   (a new track started)
+//   {"event":"state","state":"playing"|"paused"|"stopped"}
+//   {"event":"progress","elapsed":12,"duration":201}                      (once per second)
+//   {"event":"volume","level":70}
+//   {"event":"error","message":"…"}
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use crate::format::Side;
+use crate::tui::{App, PlayerState, TuiOptions};
+
+/// How often the engine checks for commands and finished tracks.
+const TICK: Duration = Duration::from_millis(100);
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+enum Command {
+	Play { track: Option<usize> },
+	Pause,
+	Resume,
+	Stop,
+	Next,
+	Previous,
+	Volume { level: u8 },
+	Status,
+	Quit,
+}
+
+#[derive(Serialize)]
+struct TrackInfo {
+	track: usize,
+	title: String,
+	artist: String,
+	duration: u64,
+	side: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Event {
+	Ready { tracks: Vec<TrackInfo> },
+	Track { track: usize, title: String, artist: String, duration: u64 },
+	State { state: &'static str },
+	Progress { elapsed: u64, duration: u64 },
+	Volume { level: u8 },
+	Error { message: String },
+}
+
+/// Writes one event as a line of JSON.
+fn emit(event: &Event) {
+	if let Ok(line) = serde_json::to_string(event) {
+		let mut stdout = io::stdout().lock();
+		let _ = writeln!(stdout, "{}", line);
+		let _ = stdout.flush();
+	}
+}
+
+fn state_name(state: PlayerState) -> &'static str {
+	match state {
+		PlayerState::Playing => "playing",
+		PlayerState::Paused => "paused",
+		PlayerState::Stopped => "stopped",
+	}
+}
+
+fn track_event(app: &App, idx: usize) -> Event {
+	let track = &app.tracks[idx];
+	Event::Track { track: idx + 1, title: track.title.clone(), artist: track.artist.clone(), duration: track.duration_secs }
+}
+
+/// Runs the daemon until `quit` or end of input.
+/// A cassette that can't be loaded is reported as an error event before returning.
+pub fn run_daemon(cassette_path: &str, device: Option<String>) -> Result<(), String> {
+	// Sides never wait for a keypress here; there is nobody at the keyboard
+	let options = TuiOptions { auto_flip: true, device, ..TuiOptions::default() };
+	let mut app = App::new(Path::new(cassette_path), &options, &mut |_, _| {}).inspect_err(|e| emit(&Event::Error { message: e.clone() }))?;
+
+	emit(&Event::Ready {
+		tracks: app.tracks.iter().enumerate().map(|(i, t)| TrackInfo {
+			track: i + 1,
+			title: t.title.clone(),
+			artist: t.artist.clone(),
+			duration: t.duration_secs,
+			side: if t.side == Side::B { "B" } else { "A" },
+		}).collect(),
+	});
+
+	// Stdin is read on its own thread so playback keeps advancing between commands
+	let (tx, rx) = mpsc::channel();
+	thread::spawn(move || {
+		for line in io::stdin().lock().lines() {
+			let Ok(line) = line else { break };
+			if tx.send(line).is_err() { break; }
+		}
+	});
+
+	let mut last_track = None;
+	let mut last_state = PlayerState::Stopped;
+	let mut last_elapsed = 0;
+	let mut last_error: Option<String> = None;
+
+	loop {
+		match rx.try_recv() {
+			Ok(line) if line.trim().is_empty() => {}
+			Ok(line) => match serde_json::from_str::<Command>(&line) {
+				Ok(Command::Quit) => break,
+				Ok(command) => apply(&mut app, command, &mut last_track),
+				Err(e) => emit(&Event::Error { message: format!("Invalid command '{}': {}", line.trim(), e) }),
+			},
+			Err(TryRecvError::Disconnected) => break,
+			Err(TryRecvError::Empty) => thread::sleep(TICK),
+		}
+
+		app.poll_track_load();
+		app.check_track_finished();
+
+		// Report whatever changed since the last tick
+		if app.current_track != last_track {
+			if let Some(idx) = app.current_track {
+				emit(&track_event(&app, idx));
+			}
+			last_track = app.current_track;
+		}
+		if app.player_state != last_state {
+			emit(&Event::State { state: state_name(app.player_state) });
+			last_state = app.player_state;
+		}
+		app.update_progress();
+		let elapsed = app.progress.as_secs();
+		if elapsed != last_elapsed {
+			if let Some(idx) = app.current_track {
+				emit(&Event::Progress { elapsed, duration: app.tracks[idx].duration_secs });
+			}
+			last_elapsed = elapsed;
+		}
+		if let Some(e) = app.playback_error.take() {
+			emit(&Event::Error { message: e.to_string() });
+		}
+		if app.cassette_error != last_error {
+			if let Some(ref message) = app.cassette_error {
+				emit(&Event::Error { message: message.clone() });
+			}
+			last_error = app.cassette_error.clone();
+		}
+	}
+
+	app.stop();
+	Ok(())
+}
+
+/// Applies one command to the engine.
+fn apply(app: &mut App, command: Command, last_track: &mut Option<usize>) {
+	match command {
+		Command::Play { track: Some(track) } => {
+			if track == 0 || track > app.tracks.len() {
+				emit(&Event::Error { message: format!("No track {}. This cassette has {} track(s).", track, app.tracks.len()) });
+				return;
+			}
+			app.play_track(track - 1);
+			// Replaying the same track is still a track change
+			*last_track = None;
+		}
+		Command::Play { track: None } => match app.player_state {
+			PlayerState::Playing => {}
+			_ => app.toggle_pause(),
+		},
+		Command::Pause => {
+			if app.player_state == PlayerState::Playing { app.toggle_pause(); }
+		}
+		Command::Resume => {
+			if app.player_state == PlayerState::Paused { app.toggle_pause(); }
+		}
+		Command::Stop => app.stop(),
+		Command::Next => app.next_track(),
+		Command::Previous => app.previous_track(),
+		Command::Volume { level } => {
+			app.set_volume(level);
+			emit(&Event::Volume { level: app.volume.load(Ordering::SeqCst) });
+		}
+		Command::Status => {
+			emit(&Event::State { state: state_name(app.player_state) });
+			if let Some(idx) = app.current_track {
+				emit(&track_event(app, idx));
+			}
+			emit(&Event::Volume { level: app.volume.load(Ordering::SeqCst) });
+		}
+		Command::Quit => {}
+	}
+}
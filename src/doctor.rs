@@ -0,0 +1,102 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// DOCTOR MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// `rewind doctor` checks the playback environment without needing a cassette:
+// the audio host and its output devices, whether the default output stream
+// opens (and in what format), and whether the bundled tape clunk can be probed
+// and decoded. Aimed at "why is it silent?" reports from servers, containers
+// and SSH sessions. `rewind devices` runs just the device listing, for
+// finding the names `--device` accepts.
+
+use std::io::Cursor;
+use lofty::file::AudioFile;
+use lofty::probe::Probe;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{Decoder, OutputStreamBuilder, Source};
+use crate::logger::{log, LogLevel};
+use crate::playback::TAPE_SFX;
+
+/// Runs every check and prints a pass/fail report. Returns whether all checks passed.
+pub fn doctor() -> bool {
+	log(LogLevel::Info, "Checking the playback environment...");
+
+	let checks = [list_devices(), check_output_stream(), check_decoding()];
+	let failed = checks.iter().filter(|passed| !**passed).count();
+
+	if failed == 0 {
+		log(LogLevel::Success, "All checks passed. Cassettes should play here.");
+	} else {
+		log(LogLevel::Error, &format!("{} of {} check(s) failed.", failed, checks.len()));
+		if !checks[0] || !checks[1] {
+			log(LogLevel::Info, "Over SSH, in containers and on headless servers there is often no sound server (PulseAudio, PipeWire) or sound card to play through.");
+		}
+	}
+	failed == 0
+}
+
+/// Lists the audio host's output devices, marking the default one.
+/// Returns false when there are none or they can't be listed.
+pub fn list_devices() -> bool {
+	let host = rodio::cpal::default_host();
+	log(LogLevel::Info, &format!("Audio host: {}", host.id().name()));
+
+	let default_name = host.default_output_device().and_then(|d| d.name().ok());
+	let devices: Vec<String> = match host.output_devices() {
+		Ok(devices) => devices.map(|d| d.name().unwrap_or_else(|_| "(unnamed device)".to_string())).collect(),
+		Err(e) => { log(LogLevel::Error, &format!("Output devices: cannot list them: {}", e)); return false; }
+	};
+
+	if devices.is_empty() {
+		log(LogLevel::Error, "Output devices: none found.");
+		return false;
+	}
+	log(LogLevel::Success, &format!("Output devices: {} found.", devices.len()));
+	for name in &devices {
+		let marker = if default_name.as_ref() == Some(name) { " (default)" } else { "" };
+		log(LogLevel::Info, &format!("  {}{}", name, marker));
+	}
+	if default_name.is_none() {
+		log(LogLevel::Warning, "No default output device is set.");
+	}
+	true
+}
+
+/// Opens the default output stream the players use and reports its format.
+fn check_output_stream() -> bool {
+	match OutputStreamBuilder::open_default_stream() {
+		Ok(mut stream) => {
+			stream.log_on_drop(false);
+			let config = stream.config();
+			log(LogLevel::Success, &format!("Output stream: opened ({} Hz, {} channel(s), {} samples).",
+				config.sample_rate(), config.channel_count(), config.sample_format()));
+			true
+		}
+		Err(e) => { log(LogLevel::Error, &format!("Output stream: cannot open the default device: {}", e)); false }
+	}
+}
+
+/// Probes and decodes the bundled tape clunk, as a stand-in for a cassette track.
+fn check_decoding() -> bool {
+	let probed = Probe::new(Cursor::new(TAPE_SFX)).guess_file_type().map_err(|e| e.to_string())
+		.and_then(|probe| probe.read().map_err(|e| e.to_string()));
+	let duration = match probed {
+		Ok(tagged) => tagged.properties().duration(),
+		Err(e) => { log(LogLevel::Error, &format!("Metadata: cannot probe the bundled sample: {}", e)); return false; }
+	};
+
+	match Decoder::new(Cursor::new(TAPE_SFX)) {
+		Ok(source) => {
+			let (rate, channels) = (source.sample_rate(), source.channels());
+			let samples = source.count();
+			if samples == 0 {
+				log(LogLevel::Error, "Decoding: the bundled sample decoded to no audio.");
+				return false;
+			}
+			log(LogLevel::Success, &format!("Decoding: bundled sample OK ({} ms, {} Hz, {} channel(s)).",
+				duration.as_millis(), rate, channels));
+			true
+		}
+		Err(e) => { log(LogLevel::Error, &format!("Decoding: cannot decode the bundled sample: {}", e)); false }
+	}
+}
@@ -0,0 +1,68 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// DOWNLOAD MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Fetches a shared cassette link into a temporary file, so `rewind inspect <url>`
+// can vet it without saving it first. Inspect reads the download like any local
+// file, and the temporary file is deleted afterwards. Downloads are capped
+// (`--max-download`) and anything but a 200 OK is an error. Needs the
+// `download` feature (reqwest with rustls); other builds explain how to get it.
+
+use std::fs::File;
+use crate::io::TempFile;
+
+/// Largest download `inspect` accepts without `--max-download`.
+pub const DEFAULT_MAX_DOWNLOAD: u64 = 100_000_000;
+
+/// Whether `path` is an http(s) link rather than a file.
+pub fn is_url(path: &str) -> bool {
+	["http://", "https://"].iter().any(|scheme| path.get(..scheme.len()).is_some_and(|start| start.eq_ignore_ascii_case(scheme)))
+}
+
+/// Downloads `url` into a temporary file, returned rewound, refusing responses other than
+/// 200 OK and bodies over `max_bytes` (checked against Content-Length up front, and again
+/// while reading). Keep the `TempFile` until the file has been read.
+#[cfg(feature = "download")]
+pub fn download(url: &str, max_bytes: u64) -> Result<(TempFile, File), String> {
+	use std::io::{self, BufWriter, Read, Seek};
+	use std::time::Duration;
+	use crate::io::{human_size, Progress, ProgressReader};
+
+	// No overall timeout: a big cassette on a slow link is fine, a dead server is not
+	let response = reqwest::blocking::Client::builder()
+		.user_agent(concat!("rewind/", env!("CARGO_PKG_VERSION")))
+		.connect_timeout(Duration::from_secs(30))
+		.timeout(None)
+		.build()
+		.and_then(|client| client.get(url).send())
+		.map_err(|e| format!("Failed to download {}: {}", url, e))?;
+	let status = response.status();
+	if status != reqwest::StatusCode::OK {
+		return Err(format!("The server answered {} instead of 200 OK for {}.", status, url));
+	}
+
+	let expected = response.content_length();
+	if let Some(len) = expected.filter(|&len| len > max_bytes) {
+		return Err(format!("The cassette is {}, over the {} download cap. Raise it with --max-download.", human_size(len), human_size(max_bytes)));
+	}
+
+	// Reading one byte past the cap tells a body that's exactly the cap from one that's bigger
+	let (temp, file) = TempFile::create("download")?;
+	let mut writer = BufWriter::new(file);
+	let mut line = Progress::new("Downloading");
+	let mut on_progress = |done, total| line.update(done, total);
+	let mut body = ProgressReader::new(response.take(max_bytes.saturating_add(1)), expected.unwrap_or(0), &mut on_progress);
+	let len = io::copy(&mut body, &mut writer).map_err(|e| format!("Failed to download {}: {}", url, e))?;
+	if len > max_bytes {
+		return Err(format!("The download went past the {} cap and was stopped. Raise it with --max-download.", human_size(max_bytes)));
+	}
+	let mut file = writer.into_inner().map_err(|e| format!("Failed to download {}: {}", url, e.error()))?;
+	file.rewind().map_err(|e| format!("Failed to download {}: {}", url, e))?;
+	Ok((temp, file))
+}
+
+/// Without the `download` feature there is no HTTP client to fetch `url` with.
+#[cfg(not(feature = "download"))]
+pub fn download(url: &str, _max_bytes: u64) -> Result<(TempFile, File), String> {
+	Err(format!("This build can't download {}. Rebuild with `cargo build --features download`, or save the file and inspect it locally.", url))
+}
@@ -0,0 +1,176 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// EJECT MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Takes things back out of a cassette. `eject_cover` writes the PNG prefix
+// (everything up to and including the real IEND chunk) as a standalone image,
+// leaving the embedded audio behind. `extract_tracks` writes the embedded audio
+// files out, byte for byte or converted with `--to` (tags are carried over),
+// named after their TOC entries or, with `--tag-name`, their title tags.
+
+use std::fs;
+use std::collections::HashSet;
+use std::io::{self, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use lofty::file::TaggedFileExt;
+use lofty::probe::Probe;
+use lofty::tag::Accessor;
+use crate::io::{open_file, create_file, human_size, sanitize_name, track_buffer};
+use crate::format::{CassetteLayout, BLANK_CASSETTE};
+use crate::png::image_end;
+use crate::transcode::{convert, OutputFormat};
+use crate::logger::{log, LogLevel};
+
+/// Options for `extract_tracks`.
+pub struct ExtractOptions {
+	/// 1-based track to extract; every track when `None`.
+	pub track: Option<usize>,
+	/// Directory the tracks are written to.
+	pub output_dir: String,
+	/// Re-encode to this format instead of writing the stored bytes.
+	pub to: Option<OutputFormat>,
+	/// Name files after their title tag (keeping the extension) instead of the TOC name.
+	pub tag_name: bool,
+}
+
+/// Writes the cassette's cover art to `output_path` as a plain PNG.
+pub fn eject_cover(path: &str, output_path: &str) {
+	log(LogLevel::Info, &format!("Ejecting cover art from: {}", path));
+
+	let mut file = match open_file(path) {
+		Ok(f) => f,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+
+	let end = match image_end(&mut file) {
+		Ok(end) => end,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot find the cover image: {}", e)); return; }
+	};
+
+	let output = match create_file(output_path) {
+		Ok(f) => f,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+
+	let mut writer = BufWriter::new(output);
+	let copied = file.seek(SeekFrom::Start(0))
+		.and_then(|_| io::copy(&mut (&mut file).take(end), &mut writer))
+		.and_then(|n| writer.flush().map(|_| n));
+
+	match copied {
+		Ok(n) => log(LogLevel::Success, &format!("Cover ejected to {} ({}).", output_path, human_size(n))),
+		Err(e) => log(LogLevel::Error, &format!("Failed to write cover art: {}", e)),
+	}
+}
+
+/// Writes the cassette's tracks (or just `options.track`) into `options.output_dir`,
+/// named after their TOC entries (or title tags, with `options.tag_name`).
+pub fn extract_tracks(path: &str, options: &ExtractOptions) {
+	log(LogLevel::Info, &format!("Extracting tracks from: {}", path));
+
+	let mut file = match open_file(path) {
+		Ok(f) => f,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+
+	let layout = match CassetteLayout::read(&mut file) {
+		Ok(layout) => layout,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+	let offsets = layout.track_offsets();
+	let entries = layout.header.entries;
+	if entries.is_empty() {
+		log(LogLevel::Warning, BLANK_CASSETTE);
+		return;
+	}
+
+	let selected: Vec<usize> = match options.track {
+		Some(track) if track == 0 || track > entries.len() => {
+			log(LogLevel::Error, &format!("No track {}. This cassette has {} track(s).", track, entries.len()));
+			return;
+		}
+		Some(track) => vec![track - 1],
+		None => (0..entries.len()).collect(),
+	};
+
+	if let Err(e) = fs::create_dir_all(&options.output_dir) {
+		log(LogLevel::Error, &format!("Cannot create '{}': {}", options.output_dir, e));
+		return;
+	}
+
+	if options.to == Some(OutputFormat::Mp3) {
+		log(LogLevel::Warning, "Converting to MP3 is lossy. The stored tracks are left untouched.");
+	}
+
+	let mut written = 0;
+	let mut used_paths = HashSet::new();
+	for idx in selected {
+		let entry = &entries[idx];
+		let mut audio = match track_buffer(entry.size) {
+			Ok(buffer) => buffer,
+			Err(e) => { log(LogLevel::Error, &format!("Cannot extract track {} ({}): {}", idx + 1, entry.name, e)); continue; }
+		};
+		if let Err(e) = file.seek(SeekFrom::Start(offsets[idx])).and_then(|_| file.read_exact(&mut audio)) {
+			log(LogLevel::Error, &format!("Cannot read track {} ({}): {}", idx + 1, entry.name, e));
+			continue;
+		}
+
+		// Two tracks with the same title would overwrite each other; the second keeps its TOC name
+		let name = match options.tag_name.then(|| tag_title(&audio)).flatten() {
+			Some(title) => {
+				let name = match Path::new(&entry.name).extension() {
+					Some(ext) => format!("{}.{}", title, ext.to_string_lossy()),
+					None => title,
+				};
+				let taken = used_paths.contains(&output_path(&options.output_dir, &name, options.to.map(|f| f.extension())));
+				if taken {
+					log(LogLevel::Warning, &format!("Track {} has the same title as an earlier one, so it keeps its name {}.", idx + 1, entry.name));
+				}
+				if taken { entry.name.clone() } else { name }
+			}
+			None => entry.name.clone(),
+		};
+
+		let (bytes, out_path) = match options.to {
+			Some(format) => {
+				log(LogLevel::Info, &format!("Converting {} to {}...", entry.name, format.extension().to_uppercase()));
+				match convert(audio, format) {
+					Ok(bytes) => (bytes, output_path(&options.output_dir, &name, Some(format.extension()))),
+					Err(e) => { log(LogLevel::Error, &format!("{}: {}", entry.name, e)); continue; }
+				}
+			}
+			None => (audio, output_path(&options.output_dir, &name, None)),
+		};
+		used_paths.insert(out_path.clone());
+
+		match fs::write(&out_path, &bytes) {
+			Ok(()) => {
+				log(LogLevel::Success, &format!("Track {} → {} ({})", idx + 1, out_path.display(), human_size(bytes.len() as u64)));
+				written += 1;
+			}
+			Err(e) => log(LogLevel::Error, &format!("Cannot write '{}': {}", out_path.display(), e)),
+		}
+	}
+
+	if written > 0 {
+		log(LogLevel::Success, &format!("Extracted {} track(s) to {}.", written, options.output_dir));
+	}
+}
+
+/// The track's title tag, made safe for a file name, if it has a non-empty one.
+fn tag_title(audio: &[u8]) -> Option<String> {
+	let tagged = Probe::new(Cursor::new(audio)).guess_file_type().ok()?.read().ok()?;
+	let tag = tagged.primary_tag().or_else(|| tagged.first_tag())?;
+	Some(sanitize_name(&tag.title()?)).filter(|title| !title.is_empty())
+}
+
+/// Output path for a track. Only the final path component of the stored name is used,
+/// so a crafted TOC can't write outside the output directory.
+fn output_path(dir: &str, name: &str, extension: Option<&str>) -> PathBuf {
+	let file_name = Path::new(name).file_name().map(|n| n.to_os_string()).unwrap_or_else(|| "track".into());
+	let mut path = Path::new(dir).join(file_name);
+	if let Some(extension) = extension {
+		path.set_extension(extension);
+	}
+	path
+}
@@ -0,0 +1,222 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// EQUALIZER MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// A 3-band (low/mid/high) equalizer applied to decoded audio. Each band is a
+// biquad filter (RBJ cookbook: low shelf, peaking, high shelf) wrapped as a
+// rodio `Source`. Band gains live in a shared `EqSettings` so the players can
+// change them while a track is playing; at 0 dB on every band (the default)
+// samples pass through untouched.
+
+use std::sync::atomic::{AtomicI32, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+pub const BAND_COUNT: usize = 3;
+pub const BAND_NAMES: [&str; BAND_COUNT] = ["Low", "Mid", "High"];
+/// Corner (shelves) or center (peak) frequency of each band, in Hz.
+const BAND_FREQS: [f32; BAND_COUNT] = [200.0, 1000.0, 4000.0];
+/// Gains are clamped to ±this many dB.
+pub const MAX_GAIN_DB: i32 = 12;
+/// Shelf slope / peak width. 0.707 gives a smooth Butterworth-like response.
+const BAND_Q: f32 = 0.707;
+
+/// Band gains in whole dB, shared between the UI and the audio thread.
+#[derive(Default)]
+pub struct EqSettings {
+	gains: [AtomicI32; BAND_COUNT],
+	/// Bumped on every change so running filters know to recompute their coefficients.
+	generation: AtomicU32,
+}
+
+impl EqSettings {
+	pub fn gain(&self, band: usize) -> i32 {
+		self.gains[band].load(Ordering::Relaxed)
+	}
+
+	pub fn gains(&self) -> [i32; BAND_COUNT] {
+		std::array::from_fn(|band| self.gain(band))
+	}
+
+	/// Sets a band's gain, clamped to ±MAX_GAIN_DB.
+	pub fn set_gain(&self, band: usize, db: i32) {
+		self.gains[band].store(db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB), Ordering::Relaxed);
+		self.generation.fetch_add(1, Ordering::Release);
+	}
+
+	/// Raises (or lowers, with a negative delta) a band's gain.
+	pub fn adjust(&self, band: usize, delta: i32) {
+		self.set_gain(band, self.gain(band) + delta);
+	}
+
+	fn is_flat(&self) -> bool {
+		self.gains.iter().all(|g| g.load(Ordering::Relaxed) == 0)
+	}
+}
+
+/// Which biquad shape a band uses.
+#[derive(Clone, Copy)]
+enum FilterKind {
+	LowShelf,
+	Peaking,
+	HighShelf,
+}
+
+const BAND_KINDS: [FilterKind; BAND_COUNT] = [FilterKind::LowShelf, FilterKind::Peaking, FilterKind::HighShelf];
+
+/// Normalized biquad coefficients (a0 = 1).
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+	b0: f32,
+	b1: f32,
+	b2: f32,
+	a1: f32,
+	a2: f32,
+}
+
+impl Biquad {
+	/// Coefficients from the RBJ Audio EQ Cookbook.
+	fn new(kind: FilterKind, sample_rate: f32, freq: f32, gain_db: f32) -> Self {
+		let a = 10f32.powf(gain_db / 40.0);
+		let w0 = 2.0 * std::f32::consts::PI * freq / sample_rate;
+		let (sin, cos) = w0.sin_cos();
+		let alpha = sin / (2.0 * BAND_Q);
+
+		let (b0, b1, b2, a0, a1, a2) = match kind {
+			FilterKind::Peaking => (
+				1.0 + alpha * a, -2.0 * cos, 1.0 - alpha * a,
+				1.0 + alpha / a, -2.0 * cos, 1.0 - alpha / a,
+			),
+			FilterKind::LowShelf => {
+				let k = 2.0 * a.sqrt() * alpha;
+				(
+					a * ((a + 1.0) - (a - 1.0) * cos + k),
+					2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+					a * ((a + 1.0) - (a - 1.0) * cos - k),
+					(a + 1.0) + (a - 1.0) * cos + k,
+					-2.0 * ((a - 1.0) + (a + 1.0) * cos),
+					(a + 1.0) + (a - 1.0) * cos - k,
+				)
+			}
+			FilterKind::HighShelf => {
+				let k = 2.0 * a.sqrt() * alpha;
+				(
+					a * ((a + 1.0) + (a - 1.0) * cos + k),
+					-2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+					a * ((a + 1.0) + (a - 1.0) * cos - k),
+					(a + 1.0) - (a - 1.0) * cos + k,
+					2.0 * ((a - 1.0) - (a + 1.0) * cos),
+					(a + 1.0) - (a - 1.0) * cos - k,
+				)
+			}
+		};
+
+		Biquad { b0: b0 / a0, b1: b1 / a0, b2: b2 / a0, a1: a1 / a0, a2: a2 / a0 }
+	}
+
+	/// Runs one sample through the filter (direct form I), updating `state` = [x1, x2, y1, y2].
+	fn process(&self, x: f32, state: &mut [f32; 4]) -> f32 {
+		let y = self.b0 * x + self.b1 * state[0] + self.b2 * state[1] - self.a1 * state[2] - self.a2 * state[3];
+		*state = [x, state[0], y, state[2]];
+		y
+	}
+}
+
+/// A rodio source that runs its input through the three EQ bands.
+pub struct Equalizer<S: Source> {
+	input: S,
+	settings: Arc<EqSettings>,
+	/// Settings generation the current coefficients were computed for.
+	generation: u32,
+	flat: bool,
+	filters: [Biquad; BAND_COUNT],
+	/// Filter history per channel, per band.
+	history: Vec<[[f32; 4]; BAND_COUNT]>,
+	channel: usize,
+}
+
+impl<S: Source> Equalizer<S> {
+	pub fn new(input: S, settings: Arc<EqSettings>) -> Self {
+		let channels = input.channels().max(1) as usize;
+		let mut eq = Equalizer {
+			input,
+			settings,
+			generation: 0,
+			flat: true,
+			filters: [Biquad::default(); BAND_COUNT],
+			history: vec![[[0.0; 4]; BAND_COUNT]; channels],
+			channel: 0,
+		};
+		eq.update_filters();
+		eq
+	}
+
+	fn update_filters(&mut self) {
+		self.generation = self.settings.generation.load(Ordering::Acquire);
+		self.flat = self.settings.is_flat();
+		let sample_rate = self.input.sample_rate() as f32;
+		for band in 0..BAND_COUNT {
+			let gain = self.settings.gain(band) as f32;
+			self.filters[band] = Biquad::new(BAND_KINDS[band], sample_rate, BAND_FREQS[band], gain);
+		}
+	}
+}
+
+impl<S: Source> Iterator for Equalizer<S> {
+	type Item = Sample;
+
+	fn next(&mut self) -> Option<Sample> {
+		let sample = self.input.next()?;
+
+		// Pick up gain changes at frame boundaries so channels stay in step
+		if self.channel == 0 && self.settings.generation.load(Ordering::Acquire) != self.generation {
+			self.update_filters();
+		}
+
+		let channel = self.channel;
+		self.channel = (self.channel + 1) % self.history.len();
+		if self.flat {
+			return Some(sample);
+		}
+
+		let mut out = sample;
+		for (filter, state) in self.filters.iter().zip(self.history[channel].iter_mut()) {
+			out = filter.process(out, state);
+		}
+		Some(out)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		self.input.size_hint()
+	}
+}
+
+impl<S: Source> Source for Equalizer<S> {
+	fn current_span_len(&self) -> Option<usize> {
+		self.input.current_span_len()
+	}
+
+	fn channels(&self) -> ChannelCount {
+		self.input.channels()
+	}
+
+	fn sample_rate(&self) -> SampleRate {
+		self.input.sample_rate()
+	}
+
+	fn total_duration(&self) -> Option<Duration> {
+		self.input.total_duration()
+	}
+
+	fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+		self.input.try_seek(pos)?;
+		// Old filter history belongs to a different part of the track
+		for state in &mut self.history {
+			*state = [[0.0; 4]; BAND_COUNT];
+		}
+		self.channel = 0;
+		Ok(())
+	}
+}
@@ -0,0 +1,1029 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// FORMAT MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Defines the on-disk layout of a cassette. Everything after the PNG IEND chunk
+// is owned by Rewind and looks like this (all integers little-endian):
+//
+//   [b"RWND" magic] [u8 format version]
+//   [u32 track count]
+//   [u32 name length] [name bytes (UTF-8)] [u64 audio size]   ← once per track
+//     [u8 side (v2+): 0 = Side A, 1 = Side B]
+//     [u32 art length (v3+)] [PNG thumbnail bytes, empty when the track has no art]
+//     [u32 seek point count (v4+)] [u64 millis, u64 byte offset into the track] ...
+//     [u32 CRC32 of the track's audio bytes (v6+)]
+//     [u32 track number (v7+): 0 = its position in the TOC]
+//     [u32 lyrics length (v10+)] [LRC text (UTF-8), empty when the track has no lyrics]
+//     [u8 codec (v12+): 0 = unknown, otherwise one of `CODECS`]
+//   [u32 album length (v8+)] [album bytes] [u32 artist length] [artist bytes] [u16 year: 0 = unknown]
+//   [u16 tape length in minutes (v9+): 0 = no nominal length]
+//   [u32 track alignment in bytes (v11+): 0 = tracks back to back]
+//   [u8 signed (v5+)] [32-byte Ed25519 public key] [64-byte signature]   ← only when signed
+//   [zero padding] [audio bytes of track 1] [zero padding] [audio bytes of track 2] ...
+//   [u32 CRC32 of every byte before it]
+//
+// All modules read and write the TOC through the structs below so the layout
+// is only described in one place. Cassettes recorded before the magic marker
+// existed start directly with the track count; they are read as version 0.
+// Fields added in later versions are only present when the header's version is
+// at least the one that introduced them; older files get their defaults.
+// Padding only exists on aligned tapes: it moves each track's start up to the next
+// multiple of the alignment (counted from the start of the file), so it follows
+// from the TOC alone and readers skip it without it being listed anywhere.
+
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use lofty::file::FileType;
+use crate::io::{find_iend, hash_observed};
+use crate::png::{image_end, PNG_SIGNATURE};
+use crate::logger::trace_event;
+
+/// Marker written right after IEND so readers can tell a cassette from random trailing bytes.
+pub const MAGIC: [u8; 4] = *b"RWND";
+/// Format version written by this build.
+pub const FORMAT_VERSION: u8 = 12;
+/// First version that stores a tape side per track.
+pub const SIDES_VERSION: u8 = 2;
+/// First version that stores artwork per track.
+pub const ART_VERSION: u8 = 3;
+/// First version that stores a seek index per track.
+pub const SEEK_INDEX_VERSION: u8 = 4;
+/// First version that can carry an Ed25519 signature.
+pub const SIGNATURE_VERSION: u8 = 5;
+/// First version that stores a CRC32 per track.
+pub const TRACK_CRC_VERSION: u8 = 6;
+/// First version that stores an explicit track number per track.
+pub const TRACK_NUMBER_VERSION: u8 = 7;
+/// First version that stores album, artist and year for the whole cassette.
+pub const ALBUM_VERSION: u8 = 8;
+/// First version that stores a nominal tape length (C60, C90, ...).
+pub const TAPE_VERSION: u8 = 9;
+/// First version that stores synced lyrics (LRC) per track.
+pub const LYRICS_VERSION: u8 = 10;
+/// First version that can align each track's start to a byte boundary.
+pub const ALIGN_VERSION: u8 = 11;
+/// First version that stores each track's codec.
+pub const CODEC_VERSION: u8 = 12;
+/// Version assigned to cassettes recorded before the magic marker existed.
+pub const LEGACY_VERSION: u8 = 0;
+
+/// What every command says about a cassette whose TOC lists no tracks.
+pub const BLANK_CASSETTE: &str = "This cassette is blank. No tracks found.";
+
+/// Width of the track count field (u32).
+pub const COUNT_LEN: usize = 4;
+/// Width of each entry's name length field (u32).
+pub const NAME_LEN_LEN: usize = 4;
+/// Width of each entry's audio size field (u64).
+pub const SIZE_LEN: usize = 8;
+/// Longest track name accepted, so a corrupt length can't exhaust memory.
+pub const MAX_NAME_LEN: usize = 64 * 1024;
+/// Width of the trailing CRC32 seal (u32).
+pub const CRC_LEN: usize = 4;
+/// Width of each entry's art length field (u32).
+pub const ART_LEN_LEN: usize = 4;
+/// Largest thumbnail accepted per track, so a corrupt length can't exhaust memory.
+pub const MAX_ART_LEN: usize = 256 * 1024;
+/// Width of each entry's seek point count field (u32).
+pub const SEEK_COUNT_LEN: usize = 4;
+/// Width of one seek point (u64 millis + u64 offset).
+pub const SEEK_POINT_LEN: usize = 16;
+/// Most seek points accepted per track (a 10 s spacing covers over a week of audio).
+pub const MAX_SEEK_POINTS: usize = 65_536;
+/// Width of each entry's track number field (u32).
+pub const TRACK_NUMBER_LEN: usize = 4;
+/// Width of the album and artist length fields (u32).
+pub const ALBUM_TEXT_LEN_LEN: usize = 4;
+/// Longest album or artist text accepted, so a corrupt length can't exhaust memory.
+pub const MAX_ALBUM_TEXT_LEN: usize = 4 * 1024;
+/// Width of the album year field (u16).
+pub const YEAR_LEN: usize = 2;
+/// Width of the tape length field (u16 minutes).
+pub const TAPE_LEN_LEN: usize = 2;
+/// Width of each entry's lyrics length field (u32).
+pub const LYRICS_LEN_LEN: usize = 4;
+/// Longest lyrics accepted per track, so a corrupt length can't exhaust memory.
+pub const MAX_LYRICS_LEN: usize = 64 * 1024;
+/// Width of the track alignment field (u32).
+pub const ALIGN_LEN: usize = 4;
+/// Largest track alignment accepted (1 MiB), so a corrupt value can't pad tracks apart by gigabytes.
+pub const MAX_ALIGN: u32 = 1024 * 1024;
+
+/// Byte stored for each codec (v12+). 0 is unknown; bytes missing here (from a newer build)
+/// read as unknown too, so players fall back to sniffing the format.
+pub const CODECS: [(u8, FileType); 12] = [
+	(1, FileType::Flac),
+	(2, FileType::Mpeg),
+	(3, FileType::Mp4),
+	(4, FileType::Aac),
+	(5, FileType::Vorbis),
+	(6, FileType::Wav),
+	(7, FileType::Aiff),
+	(8, FileType::Opus),
+	(9, FileType::Speex),
+	(10, FileType::WavPack),
+	(11, FileType::Ape),
+	(12, FileType::Mpc),
+];
+
+/// Width of an Ed25519 public key.
+pub const PUBLIC_KEY_LEN: usize = 32;
+/// Width of an Ed25519 signature.
+pub const SIGNATURE_LEN: usize = 64;
+
+/// Creator signature over the unsigned TOC and all audio bytes (see the sign module).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CassetteSignature {
+	pub public_key: [u8; PUBLIC_KEY_LEN],
+	pub signature: [u8; SIGNATURE_LEN],
+}
+
+/// Which side of the tape a track is recorded on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Side {
+	#[default]
+	A,
+	B,
+}
+
+impl Side {
+	fn from_byte(byte: u8) -> io::Result<Self> {
+		match byte {
+			0 => Ok(Side::A),
+			1 => Ok(Side::B),
+			other => Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown tape side {}", other))),
+		}
+	}
+
+	fn to_byte(self) -> u8 {
+		match self {
+			Side::A => 0,
+			Side::B => 1,
+		}
+	}
+}
+
+impl std::fmt::Display for Side {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Side::A => write!(f, "Side A"),
+			Side::B => write!(f, "Side B"),
+		}
+	}
+}
+
+/// One entry of a track's seek index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SeekPoint {
+	/// Playback position in milliseconds.
+	pub millis: u64,
+	/// Byte offset, relative to the start of the track, of the first frame at or after `millis`.
+	pub offset: u64,
+}
+
+/// Album metadata stored once per cassette.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AlbumInfo {
+	/// Empty when unknown.
+	pub album: String,
+	/// Empty when unknown.
+	pub artist: String,
+	pub year: Option<u16>,
+}
+
+impl AlbumInfo {
+	/// Reads the album block from the current reader position.
+	pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+		let album = read_album_text(reader, "album")?;
+		let artist = read_album_text(reader, "artist")?;
+		let mut year_buf = [0u8; YEAR_LEN];
+		reader.read_exact(&mut year_buf)?;
+		let year = Some(u16::from_le_bytes(year_buf)).filter(|&year| year != 0);
+		Ok(AlbumInfo { album, artist, year })
+	}
+
+	/// Writes the album block in its on-disk layout.
+	pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		for text in [&self.album, &self.artist] {
+			writer.write_all(&(text.len() as u32).to_le_bytes())?;
+			writer.write_all(text.as_bytes())?;
+		}
+		writer.write_all(&self.year.unwrap_or(0).to_le_bytes())
+	}
+
+	/// Checks that the text fits the format, so a save can't write a block readers reject.
+	pub fn validated(self) -> Result<Self, String> {
+		for (label, text) in [("album", &self.album), ("artist", &self.artist)] {
+			if text.len() > MAX_ALBUM_TEXT_LEN {
+				return Err(format!("The {} name is too long ({} bytes, the limit is {}).", label, text.len(), MAX_ALBUM_TEXT_LEN));
+			}
+		}
+		if self.year == Some(0) {
+			return Err("The year can't be 0.".to_string());
+		}
+		Ok(self)
+	}
+
+	/// True when no field is filled in.
+	pub fn is_empty(&self) -> bool {
+		self.album.is_empty() && self.artist.is_empty() && self.year.is_none()
+	}
+}
+
+impl std::fmt::Display for AlbumInfo {
+	/// "Album — Artist (Year)", leaving out whatever is unknown.
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let names: Vec<&str> = [self.album.as_str(), self.artist.as_str()].into_iter().filter(|s| !s.is_empty()).collect();
+		write!(f, "{}", names.join(" — "))?;
+		match (self.year, names.is_empty()) {
+			(Some(year), true) => write!(f, "{}", year),
+			(Some(year), false) => write!(f, " ({})", year),
+			(None, _) => Ok(()),
+		}
+	}
+}
+
+fn read_album_text<R: Read>(reader: &mut R, label: &str) -> io::Result<String> {
+	let mut len_buf = [0u8; ALBUM_TEXT_LEN_LEN];
+	reader.read_exact(&mut len_buf)?;
+	let len = u32::from_le_bytes(len_buf) as usize;
+	if len > MAX_ALBUM_TEXT_LEN {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{} of {} bytes exceeds the {} byte limit", label, len, MAX_ALBUM_TEXT_LEN)));
+	}
+	let mut text_buf = vec![0u8; len];
+	reader.read_exact(&mut text_buf)?;
+	Ok(String::from_utf8_lossy(&text_buf).to_string())
+}
+
+/// A single track record in the table of contents.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TocEntry {
+	pub name: String,
+	pub size: u64,
+	/// Always Side A for cassettes older than `SIDES_VERSION`.
+	pub side: Side,
+	/// PNG thumbnail for this track; empty when it has none (always, before `ART_VERSION`).
+	pub art: Vec<u8>,
+	/// Seek points in ascending order; empty when the track has none (always, before `SEEK_INDEX_VERSION`).
+	pub seek_index: Vec<SeekPoint>,
+	/// CRC32 of this track's audio bytes. Always `None` before `TRACK_CRC_VERSION`.
+	pub crc: Option<u32>,
+	/// Track number shown instead of the position (e.g. 1, 3, 5 for an album with gaps).
+	/// `None` numbers tracks by position; always `None` before `TRACK_NUMBER_VERSION`.
+	pub number: Option<u32>,
+	/// LRC text for synced lyrics; empty when the track has none (always, before `LYRICS_VERSION`).
+	pub lyrics: String,
+	/// Format of the audio bytes, as Lofty identified it when recording. `None` when unknown
+	/// (always, before `CODEC_VERSION`).
+	pub codec: Option<FileType>,
+}
+
+impl TocEntry {
+	/// Reads one entry in the layout of format `version` from the current reader position.
+	pub fn read<R: Read>(reader: &mut R, version: u8) -> io::Result<Self> {
+		let mut len_buf = [0u8; NAME_LEN_LEN];
+		reader.read_exact(&mut len_buf)?;
+		let name_len = u32::from_le_bytes(len_buf) as usize;
+		if name_len > MAX_NAME_LEN {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, format!("track name of {} bytes exceeds the {} byte limit", name_len, MAX_NAME_LEN)));
+		}
+
+		let mut name_buf = vec![0u8; name_len];
+		reader.read_exact(&mut name_buf)?;
+		let name = String::from_utf8_lossy(&name_buf).to_string();
+
+		let mut size_buf = [0u8; SIZE_LEN];
+		reader.read_exact(&mut size_buf)?;
+		let size = u64::from_le_bytes(size_buf);
+
+		let side = if version >= SIDES_VERSION {
+			let mut side_buf = [0u8; 1];
+			reader.read_exact(&mut side_buf)?;
+			Side::from_byte(side_buf[0])?
+		} else {
+			Side::A
+		};
+
+		let art = if version >= ART_VERSION {
+			let mut art_len_buf = [0u8; ART_LEN_LEN];
+			reader.read_exact(&mut art_len_buf)?;
+			let art_len = u32::from_le_bytes(art_len_buf) as usize;
+			if art_len > MAX_ART_LEN {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("track art of {} bytes exceeds the {} byte limit", art_len, MAX_ART_LEN)));
+			}
+			let mut art = vec![0u8; art_len];
+			reader.read_exact(&mut art)?;
+			art
+		} else {
+			Vec::new()
+		};
+
+		let seek_index = if version >= SEEK_INDEX_VERSION {
+			let mut count_buf = [0u8; SEEK_COUNT_LEN];
+			reader.read_exact(&mut count_buf)?;
+			let count = u32::from_le_bytes(count_buf) as usize;
+			if count > MAX_SEEK_POINTS {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("seek index of {} points exceeds the {} point limit", count, MAX_SEEK_POINTS)));
+			}
+			let mut points = Vec::with_capacity(count);
+			for _ in 0..count {
+				let mut point_buf = [0u8; SEEK_POINT_LEN];
+				reader.read_exact(&mut point_buf)?;
+				let (millis, offset) = point_buf.split_at(SEEK_POINT_LEN / 2);
+				points.push(SeekPoint {
+					millis: u64::from_le_bytes(millis.try_into().unwrap()),
+					offset: u64::from_le_bytes(offset.try_into().unwrap()),
+				});
+			}
+			points
+		} else {
+			Vec::new()
+		};
+
+		let crc = if version >= TRACK_CRC_VERSION {
+			let mut crc_buf = [0u8; CRC_LEN];
+			reader.read_exact(&mut crc_buf)?;
+			Some(u32::from_le_bytes(crc_buf))
+		} else {
+			None
+		};
+
+		let number = if version >= TRACK_NUMBER_VERSION {
+			let mut number_buf = [0u8; TRACK_NUMBER_LEN];
+			reader.read_exact(&mut number_buf)?;
+			Some(u32::from_le_bytes(number_buf)).filter(|&n| n != 0)
+		} else {
+			None
+		};
+
+		let lyrics = if version >= LYRICS_VERSION {
+			let mut lyrics_len_buf = [0u8; LYRICS_LEN_LEN];
+			reader.read_exact(&mut lyrics_len_buf)?;
+			let lyrics_len = u32::from_le_bytes(lyrics_len_buf) as usize;
+			if lyrics_len > MAX_LYRICS_LEN {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("lyrics of {} bytes exceed the {} byte limit", lyrics_len, MAX_LYRICS_LEN)));
+			}
+			let mut lyrics_buf = vec![0u8; lyrics_len];
+			reader.read_exact(&mut lyrics_buf)?;
+			String::from_utf8_lossy(&lyrics_buf).to_string()
+		} else {
+			String::new()
+		};
+
+		let codec = if version >= CODEC_VERSION {
+			let mut codec_buf = [0u8; 1];
+			reader.read_exact(&mut codec_buf)?;
+			CODECS.iter().find(|(byte, _)| *byte == codec_buf[0]).map(|&(_, codec)| codec)
+		} else {
+			None
+		};
+
+		Ok(TocEntry { name, size, side, art, seek_index, crc, number, lyrics, codec })
+	}
+
+	/// Writes this entry in the on-disk layout of format `version`.
+	pub fn write<W: Write>(&self, writer: &mut W, version: u8) -> io::Result<()> {
+		let name_bytes = self.name.as_bytes();
+		writer.write_all(&(name_bytes.len() as u32).to_le_bytes())?;
+		writer.write_all(name_bytes)?;
+		writer.write_all(&self.size.to_le_bytes())?;
+		if version >= SIDES_VERSION {
+			writer.write_all(&[self.side.to_byte()])?;
+		}
+		if version >= ART_VERSION {
+			writer.write_all(&(self.art.len() as u32).to_le_bytes())?;
+			writer.write_all(&self.art)?;
+		}
+		if version >= SEEK_INDEX_VERSION {
+			writer.write_all(&(self.seek_index.len() as u32).to_le_bytes())?;
+			for point in &self.seek_index {
+				writer.write_all(&point.millis.to_le_bytes())?;
+				writer.write_all(&point.offset.to_le_bytes())?;
+			}
+		}
+		if version >= TRACK_CRC_VERSION {
+			// Writers fill this in once the track is copied; a placeholder has the same width
+			writer.write_all(&self.crc.unwrap_or(0).to_le_bytes())?;
+		}
+		if version >= TRACK_NUMBER_VERSION {
+			writer.write_all(&self.number.unwrap_or(0).to_le_bytes())?;
+		}
+		if version >= LYRICS_VERSION {
+			writer.write_all(&(self.lyrics.len() as u32).to_le_bytes())?;
+			writer.write_all(self.lyrics.as_bytes())?;
+		}
+		if version >= CODEC_VERSION {
+			let byte = CODECS.iter().find(|(_, codec)| Some(*codec) == self.codec).map_or(0, |&(byte, _)| byte);
+			writer.write_all(&[byte])?;
+		}
+		Ok(())
+	}
+
+	/// Number to show for this entry at 0-based `index`: its stored track number, or its position.
+	pub fn display_number(&self, index: usize) -> u64 {
+		self.number.map_or(index as u64 + 1, u64::from)
+	}
+}
+
+/// The table of contents written directly after the PNG IEND chunk.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CassetteHeader {
+	pub version: u8,
+	pub entries: Vec<TocEntry>,
+	/// Always `None` before `ALBUM_VERSION`; written as an empty block when `None` in later versions.
+	pub album: Option<AlbumInfo>,
+	/// Nominal tape length in minutes (90 for a C90). `None` when unset; always before `TAPE_VERSION`.
+	pub tape_minutes: Option<u16>,
+	/// Each track starts at a multiple of this many bytes from the start of the file.
+	/// `None` when tracks follow each other directly; always before `ALIGN_VERSION`.
+	pub align: Option<u32>,
+	/// Always `None` before `SIGNATURE_VERSION`.
+	pub signature: Option<CassetteSignature>,
+}
+
+impl CassetteHeader {
+	/// Creates a header in the current format version.
+	pub fn new(entries: Vec<TocEntry>) -> Self {
+		CassetteHeader { version: FORMAT_VERSION, entries, album: None, tape_minutes: None, align: None, signature: None }
+	}
+
+	/// Reads the magic, version, track count and every entry from the current reader position.
+	pub fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+		let mut first_buf = [0u8; COUNT_LEN];
+		reader.read_exact(&mut first_buf)?;
+
+		// Legacy cassettes have no magic: the first four bytes are the track count
+		let (version, count_buf) = if first_buf == MAGIC {
+			let mut version_buf = [0u8; 1];
+			reader.read_exact(&mut version_buf)?;
+			let version = version_buf[0];
+			if version > FORMAT_VERSION {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+					"this cassette uses format v{}, but this build only understands up to v{}",
+					version, FORMAT_VERSION
+				)));
+			}
+			let mut count_buf = [0u8; COUNT_LEN];
+			reader.read_exact(&mut count_buf)?;
+			(version, count_buf)
+		} else {
+			(LEGACY_VERSION, first_buf)
+		};
+		let track_count = u32::from_le_bytes(count_buf);
+
+		let mut entries = Vec::new();
+		for _ in 0..track_count {
+			entries.push(TocEntry::read(reader, version)?);
+		}
+
+		let album = if version >= ALBUM_VERSION {
+			Some(AlbumInfo::read(reader)?)
+		} else {
+			None
+		};
+
+		let tape_minutes = if version >= TAPE_VERSION {
+			let mut tape_buf = [0u8; TAPE_LEN_LEN];
+			reader.read_exact(&mut tape_buf)?;
+			Some(u16::from_le_bytes(tape_buf)).filter(|&minutes| minutes != 0)
+		} else {
+			None
+		};
+
+		let align = if version >= ALIGN_VERSION {
+			let mut align_buf = [0u8; ALIGN_LEN];
+			reader.read_exact(&mut align_buf)?;
+			let align = u32::from_le_bytes(align_buf);
+			if align > MAX_ALIGN {
+				return Err(io::Error::new(io::ErrorKind::InvalidData, format!("track alignment of {} bytes exceeds the {} byte limit", align, MAX_ALIGN)));
+			}
+			Some(align).filter(|&align| align > 1)
+		} else {
+			None
+		};
+
+		let signature = if version >= SIGNATURE_VERSION {
+			let mut flag_buf = [0u8; 1];
+			reader.read_exact(&mut flag_buf)?;
+			match flag_buf[0] {
+				0 => None,
+				1 => {
+					let mut public_key = [0u8; PUBLIC_KEY_LEN];
+					let mut signature = [0u8; SIGNATURE_LEN];
+					reader.read_exact(&mut public_key)?;
+					reader.read_exact(&mut signature)?;
+					Some(CassetteSignature { public_key, signature })
+				}
+				other => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("unknown signature flag {}", other))),
+			}
+		} else {
+			None
+		};
+
+		Ok(CassetteHeader { version, entries, album, tape_minutes, align, signature })
+	}
+
+	/// Writes the magic, version and track count followed by every entry.
+	/// Legacy (v0) headers are written without the magic, exactly as they were read.
+	pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+		if self.version != LEGACY_VERSION {
+			writer.write_all(&MAGIC)?;
+			writer.write_all(&[self.version])?;
+		}
+		writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+		for entry in &self.entries {
+			entry.write(writer, self.version)?;
+		}
+		if self.version >= ALBUM_VERSION {
+			self.album.clone().unwrap_or_default().write(writer)?;
+		}
+		if self.version >= TAPE_VERSION {
+			writer.write_all(&self.tape_minutes.unwrap_or(0).to_le_bytes())?;
+		}
+		if self.version >= ALIGN_VERSION {
+			writer.write_all(&self.align.unwrap_or(0).to_le_bytes())?;
+		}
+		if self.version >= SIGNATURE_VERSION {
+			match self.signature {
+				Some(ref sig) => {
+					writer.write_all(&[1])?;
+					writer.write_all(&sig.public_key)?;
+					writer.write_all(&sig.signature)?;
+				}
+				None => writer.write_all(&[0])?,
+			}
+		}
+		Ok(())
+	}
+
+	/// True when some entry has the same explicit track number as an earlier one.
+	pub fn has_duplicate_numbers(&self) -> bool {
+		let mut numbers: Vec<u32> = self.entries.iter().filter_map(|entry| entry.number).collect();
+		numbers.sort_unstable();
+		numbers.windows(2).any(|pair| pair[0] == pair[1])
+	}
+
+	/// Tape type for display, e.g. "C90", or `None` when no tape length is set.
+	pub fn tape_label(&self) -> Option<String> {
+		self.tape_minutes.map(|minutes| format!("C{}", minutes))
+	}
+
+	/// Absolute offset of each track's audio bytes when the audio starts at `audio_start`,
+	/// in TOC order, followed by where the last track ends (`None` past `u64::MAX`, only in
+	/// corrupt TOCs; the remaining offsets are pinned there).
+	fn track_spans(&self, audio_start: u64) -> (Vec<u64>, Option<u64>) {
+		let mut offsets = Vec::with_capacity(self.entries.len());
+		let mut offset = Some(audio_start);
+		for entry in &self.entries {
+			let start = offset.and_then(|offset| offset.checked_add(padding_before(offset, self.align)));
+			offsets.push(start.unwrap_or(u64::MAX));
+			offset = start.and_then(|start| start.checked_add(entry.size));
+		}
+		(offsets, offset)
+	}
+
+	/// Where the audio of the last track ends when the audio starts at `audio_start`,
+	/// counting alignment padding. `None` when that would pass `u64::MAX`.
+	pub fn audio_end(&self, audio_start: u64) -> Option<u64> {
+		self.track_spans(audio_start).1
+	}
+
+	/// True when tracks are spread over both sides of the tape.
+	pub fn has_sides(&self) -> bool {
+		self.entries.iter().any(|entry| entry.side != Side::A)
+	}
+
+	/// The header bytes a signature covers: this header encoded without its signature.
+	/// Track CRCs are left out as well; they are only known once the audio is written,
+	/// and the signature covers the audio itself.
+	pub fn unsigned_bytes(&self) -> Vec<u8> {
+		let entries = self.entries.iter().map(|entry| TocEntry { crc: None, ..entry.clone() }).collect();
+		CassetteHeader { signature: None, entries, ..self.clone() }.to_bytes()
+	}
+
+	/// Encodes the header into a byte buffer (handy for hashing while writing).
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		self.write(&mut bytes).expect("writing to a Vec cannot fail");
+		bytes
+	}
+}
+
+/// Absolute positions of every region in a cassette file.
+pub struct CassetteLayout {
+	pub header: CassetteHeader,
+	/// Offset of the TOC (immediately after IEND).
+	pub toc_start: u64,
+	/// Offset of the first track's audio bytes.
+	pub audio_start: u64,
+}
+
+impl CassetteLayout {
+	/// Locates the end of the cover image and parses the TOC that follows it.
+	/// Walks the PNG chunks when possible, falling back to scanning for IEND.
+	/// A broken cover and a PNG with nothing after it get their own messages.
+	pub fn read<R: Read + Seek>(reader: &mut R) -> Result<Self, String> {
+		let toc_start = match image_end(reader) {
+			Ok(end) => end,
+			Err(e) => find_iend(reader)
+				.ok_or_else(|| format!("This isn't a cassette: its cover image is broken or not a PNG. {}", e))?,
+		};
+		let file_len = reader.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+		if toc_start >= file_len {
+			return Err("This is a plain PNG image. There is no cassette data after the picture.".to_string());
+		}
+
+		reader.seek(SeekFrom::Start(toc_start)).map_err(|e| e.to_string())?;
+		let header = CassetteHeader::read(reader)
+			.map_err(|e| format!("The table of contents is unreadable: {}", e))?;
+		let audio_start = reader.stream_position().map_err(|e| e.to_string())?;
+		trace_event!(toc_start, audio_start, file_len, version = header.version, tracks = header.entries.len(), "read TOC");
+
+		Ok(CassetteLayout { header, toc_start, audio_start })
+	}
+
+	/// Extra checks for `--strict`: the cover must be a well-formed PNG (no IEND scanning), the
+	/// TOC must start with the magic marker and its track sizes must account for every byte up
+	/// to the CRC seal. Lenient readers accept whatever parses after the first IEND instead.
+	pub fn check_strict<R: Read + Seek>(&self, reader: &mut R) -> Result<(), String> {
+		let image_end = image_end(reader).map_err(|e| format!("Strict mode: the cover isn't a well-formed PNG ({}).", e))?;
+		if image_end != self.toc_start {
+			return Err("Strict mode: the TOC doesn't start right after the cover image.".to_string());
+		}
+		if self.header.version == LEGACY_VERSION {
+			return Err("Strict mode: no RWND marker after the cover image. This may just be a PNG with trailing bytes.".to_string());
+		}
+
+		let file_len = reader.seek(SeekFrom::End(0)).map_err(|e| e.to_string())?;
+		let expected = self.header.audio_end(self.audio_start);
+		let audio_len = expected.map(|end| end - self.audio_start);
+		let sealed = SealedRegion::of_file(file_len).map(|region| region.trailer_start);
+		if sealed.is_none() || expected != sealed {
+			return Err(format!("Strict mode: the TOC describes {} byte(s) of audio, which doesn't match the file size.", audio_len.map(|l| l.to_string()).unwrap_or_else(|| "too many".into())));
+		}
+
+		for (i, entry) in self.header.entries.iter().enumerate() {
+			if entry.name.is_empty() {
+				return Err(format!("Strict mode: track {} has no name.", i + 1));
+			}
+			if !entry.art.is_empty() && !entry.art.starts_with(&PNG_SIGNATURE) {
+				return Err(format!("Strict mode: the art of track {} isn't a PNG.", i + 1));
+			}
+			let ordered = entry.seek_index.windows(2).all(|pair| pair[0].millis < pair[1].millis && pair[0].offset < pair[1].offset);
+			if !ordered || entry.seek_index.iter().any(|p| p.offset >= entry.size) {
+				return Err(format!("Strict mode: the seek index of track {} is inconsistent.", i + 1));
+			}
+		}
+		if self.header.has_duplicate_numbers() {
+			return Err("Strict mode: two tracks share a track number.".to_string());
+		}
+		Ok(())
+	}
+
+	/// Where a second TOC starts, if another `RWND` marker follows this tape's audio and seal.
+	/// That's what a cassette recorded with another cassette as its cover looks like: the first
+	/// TOC (the one readers use) is the old tape's, and the new tape comes after it.
+	pub fn second_toc<R: Read + Seek>(&self, reader: &mut R) -> Option<u64> {
+		let toc_start = self.header.audio_end(self.audio_start)?.checked_add(CRC_LEN as u64)?;
+		let file_len = reader.seek(SeekFrom::End(0)).ok()?;
+		if toc_start.checked_add((MAGIC.len() + CRC_LEN) as u64)? > file_len {
+			return None;
+		}
+		let mut marker = [0u8; MAGIC.len()];
+		reader.seek(SeekFrom::Start(toc_start)).and_then(|_| reader.read_exact(&mut marker)).ok()?;
+		(marker == MAGIC).then_some(toc_start)
+	}
+
+	/// Absolute offset of each track's audio bytes, in TOC order, past any alignment padding.
+	/// Sizes that add up past `u64::MAX` (only in corrupt TOCs) pin the remaining offsets there.
+	pub fn track_offsets(&self) -> Vec<u64> {
+		self.header.track_spans(self.audio_start).0
+	}
+}
+
+/// Zero bytes to write at `offset` so the next track starts on an `align`-byte boundary.
+pub fn padding_before(offset: u64, align: Option<u32>) -> u64 {
+	match align {
+		Some(align) if align > 1 => (align as u64 - offset % align as u64) % align as u64,
+		_ => 0,
+	}
+}
+
+/// A cassette's CRC32 seal: the value stored in its last 4 bytes and the one computed
+/// over everything before them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Seal {
+	pub stored: u32,
+	pub computed: u32,
+}
+
+impl Seal {
+	pub fn is_intact(&self) -> bool {
+		self.stored == self.computed
+	}
+}
+
+/// The bytes a CRC32 seal covers: everything from the start of the file up to the seal,
+/// which is always the last `CRC_LEN` bytes (the trailer). Anything a format version adds
+/// after IEND lands inside it. Writers and verifiers both derive it here, so a header
+/// change can't make them hash different bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SealedRegion {
+	/// Number of bytes hashed, which is also where the trailer starts.
+	pub trailer_start: u64,
+}
+
+impl SealedRegion {
+	/// The region of a cassette file of `file_len` bytes, or `None` if there's no room for a seal.
+	pub fn of_file(file_len: u64) -> Option<Self> {
+		file_len.checked_sub(CRC_LEN as u64).map(|trailer_start| SealedRegion { trailer_start })
+	}
+
+	/// The region a writer hashes: the cover (the `toc_start` bytes before the TOC), the
+	/// TOC as `header` encodes it, and the audio of every entry with its alignment padding.
+	pub fn of_layout(toc_start: u64, header: &CassetteHeader) -> Option<Self> {
+		let audio_start = toc_start.checked_add(header.to_bytes().len() as u64)?;
+		header.audio_end(audio_start).map(|trailer_start| SealedRegion { trailer_start })
+	}
+}
+
+/// Seals a cassette whose `region` was hashed into `crc`. The writer must be exactly at
+/// the end of the region, so the seal lands where verifiers look for it and covers every
+/// byte before it.
+pub fn write_seal<W: Write + Seek>(writer: &mut W, region: SealedRegion, crc: u32) -> io::Result<()> {
+	let position = writer.stream_position()?;
+	if position != region.trailer_start {
+		return Err(io::Error::new(io::ErrorKind::InvalidData,
+			format!("the seal would start at byte {}, but the hashed region ends at byte {}", position, region.trailer_start)));
+	}
+	write_crc(writer, crc)
+}
+
+/// A whole cassette held in memory, for callers that never touch the filesystem
+/// (the web player, tests). Parsed by the same code as cassettes on disk.
+#[allow(dead_code)]
+pub struct Cassette {
+	pub layout: CassetteLayout,
+	bytes: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl Cassette {
+	/// Checks the CRC32 seal of `bytes` and parses the TOC after the cover image.
+	pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, String> {
+		let Some(region) = SealedRegion::of_file(bytes.len() as u64) else {
+			return Err("This file is too small to be a valid cassette.".to_string());
+		};
+		let data_len = region.trailer_start as usize;
+		let stored = read_crc(&mut &bytes[data_len..]).map_err(|e| e.to_string())?;
+		if crc32fast::hash(&bytes[..data_len]) != stored {
+			return Err("This cassette has been damaged! Checksum mismatch detected.".to_string());
+		}
+
+		let layout = CassetteLayout::read(&mut Cursor::new(&bytes))?;
+		Ok(Cassette { layout, bytes })
+	}
+
+	/// Hashes a cassette on disk (or any seekable reader) from the start without loading it,
+	/// reporting `(bytes hashed, total)` to `on_progress`, and reads its seal. Damage is not
+	/// an error here: compare the two values with `Seal::is_intact`.
+	pub fn verify<R: Read + Seek>(reader: &mut R, on_progress: &mut dyn FnMut(u64, u64)) -> Result<Seal, String> {
+		let file_len = reader.seek(SeekFrom::End(0)).and_then(|len| reader.rewind().map(|_| len))
+			.map_err(|e| format!("Failed to read the cassette: {}", e))?;
+		let Some(region) = SealedRegion::of_file(file_len) else {
+			return Err("This file is too small to be a valid cassette.".to_string());
+		};
+
+		let mut hasher = crc32fast::Hasher::new();
+		let stored = hash_observed(reader, &mut hasher, region.trailer_start, on_progress)
+			.and_then(|_| read_crc(reader))
+			.map_err(|e| format!("Failed to read the cassette: {}", e))?;
+		Ok(Seal { stored, computed: hasher.finalize() })
+	}
+
+	/// Audio bytes of the track at `index` (0-based), or `None` if there's no such track
+	/// or the TOC points past the CRC32 seal.
+	pub fn track(&self, index: usize) -> Option<&[u8]> {
+		let size = self.layout.header.entries.get(index)?.size;
+		let start = *self.layout.track_offsets().get(index)?;
+		let end = start.checked_add(size)?;
+		let audio = &self.bytes[..SealedRegion::of_file(self.bytes.len() as u64)?.trailer_start as usize];
+		audio.get(usize::try_from(start).ok()?..usize::try_from(end).ok()?)
+	}
+}
+
+/// Reads the CRC32 seal from the current reader position.
+pub fn read_crc<R: Read>(reader: &mut R) -> io::Result<u32> {
+	let mut crc_buf = [0u8; CRC_LEN];
+	reader.read_exact(&mut crc_buf)?;
+	Ok(u32::from_le_bytes(crc_buf))
+}
+
+/// Writes the CRC32 seal.
+pub fn write_crc<W: Write>(writer: &mut W, crc: u32) -> io::Result<()> {
+	writer.write_all(&crc.to_le_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Cursor;
+
+	/// Stand-in thumbnail; the TOC stores art as opaque bytes.
+	const ART: &[u8] = b"\x89PNG\r\n\x1a\n thumbnail";
+	const SAMPLE_LYRICS: &str = "[ti:Intro]\n[00:00.00]Press play\n[00:00.25]Hear the hiss\n";
+
+	fn sample_album() -> AlbumInfo {
+		AlbumInfo { album: "Test Tape".to_string(), artist: "Rewind".to_string(), year: Some(1984) }
+	}
+
+	/// One entry holding only what format `version` can store, so it decodes back unchanged.
+	fn entries_for(version: u8) -> Vec<TocEntry> {
+		vec![TocEntry {
+			name: "track.wav".to_string(),
+			size: 1234,
+			side: if version >= SIDES_VERSION { Side::B } else { Side::A },
+			art: if version >= ART_VERSION { ART.to_vec() } else { Vec::new() },
+			seek_index: if version >= SEEK_INDEX_VERSION { vec![SeekPoint { millis: 500, offset: 44 }] } else { Vec::new() },
+			crc: (version >= TRACK_CRC_VERSION).then_some(0x1234_5678),
+			number: (version >= TRACK_NUMBER_VERSION).then_some(2),
+			lyrics: if version >= LYRICS_VERSION { SAMPLE_LYRICS.to_string() } else { String::new() },
+			codec: (version >= CODEC_VERSION).then_some(FileType::Vorbis),
+		}]
+	}
+
+	#[test]
+	fn header_round_trips_every_field() {
+		let entry = |name: &str, side| TocEntry {
+			name: name.to_string(),
+			size: 0x0102_0304_0506,
+			side,
+			art: ART.to_vec(),
+			seek_index: vec![SeekPoint { millis: 0, offset: 0 }, SeekPoint { millis: 10_000, offset: 4096 }],
+			crc: Some(0xDEAD_BEEF),
+			number: Some(7),
+			lyrics: SAMPLE_LYRICS.to_string(),
+			codec: Some(FileType::Flac),
+		};
+		let header = CassetteHeader {
+			album: Some(sample_album()),
+			tape_minutes: Some(90),
+			align: Some(4096),
+			signature: Some(CassetteSignature { public_key: [1; PUBLIC_KEY_LEN], signature: [2; SIGNATURE_LEN] }),
+			..CassetteHeader::new(vec![entry("side a.flac", Side::A), entry("ßide b — ☃.ogg", Side::B)])
+		};
+
+		let bytes = header.to_bytes();
+		let mut reader = Cursor::new(&bytes);
+		assert_eq!(CassetteHeader::read(&mut reader).expect("decoding what was encoded"), header);
+		assert_eq!(reader.position(), bytes.len() as u64, "the reader stops right after the TOC");
+	}
+
+	#[test]
+	fn every_version_round_trips_byte_for_byte() {
+		for version in LEGACY_VERSION..=FORMAT_VERSION {
+			let album = (version >= ALBUM_VERSION).then(sample_album);
+			let tape_minutes = (version >= TAPE_VERSION).then_some(90);
+			let align = (version >= ALIGN_VERSION).then_some(4096);
+			let header = CassetteHeader { version, album, tape_minutes, align, ..CassetteHeader::new(entries_for(version)) };
+			let bytes = header.to_bytes();
+			let decoded = CassetteHeader::read(&mut Cursor::new(&bytes)).unwrap_or_else(|e| panic!("v{}: {}", version, e));
+			assert_eq!(decoded, header);
+			assert_eq!(decoded.to_bytes(), bytes, "v{} re-encodes to the same bytes", version);
+		}
+	}
+
+	#[test]
+	fn first_version_layout_uses_the_documented_widths() {
+		// v1: magic, version, u32 count, then per entry a u32 name length, the name and a u64 size
+		let header = CassetteHeader { version: 1, ..CassetteHeader::new(entries_for(1)) };
+		let bytes = header.to_bytes();
+		let name = &header.entries[0].name;
+		assert_eq!(bytes.len(), MAGIC.len() + 1 + COUNT_LEN + NAME_LEN_LEN + name.len() + SIZE_LEN);
+		assert_eq!(&bytes[..MAGIC.len()], &MAGIC);
+		assert_eq!(bytes[MAGIC.len()], 1);
+		assert_eq!(&bytes[5..9], &1u32.to_le_bytes());
+		assert_eq!(&bytes[9..13], &(name.len() as u32).to_le_bytes());
+		assert_eq!(&bytes[bytes.len() - SIZE_LEN..], &1234u64.to_le_bytes());
+
+		// v0 is the same without the magic and version
+		let legacy = CassetteHeader { version: LEGACY_VERSION, ..CassetteHeader::new(entries_for(LEGACY_VERSION)) };
+		assert_eq!(legacy.to_bytes(), &bytes[MAGIC.len() + 1..]);
+	}
+
+	/// The first entry of `entries_for(version)` in its on-disk form, so the field `version` added is last.
+	fn entry_bytes(version: u8) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		entries_for(version)[0].write(&mut bytes, version).unwrap();
+		bytes
+	}
+
+	#[test]
+	fn unknown_side_is_an_error() {
+		let mut bytes = entry_bytes(SIDES_VERSION);
+		*bytes.last_mut().unwrap() = 2;
+		assert!(TocEntry::read(&mut Cursor::new(bytes), SIDES_VERSION).is_err());
+	}
+
+	#[test]
+	fn oversized_art_is_refused() {
+		let mut bytes = entry_bytes(ART_VERSION);
+		let len_at = bytes.len() - ART.len() - ART_LEN_LEN;
+		bytes[len_at..len_at + ART_LEN_LEN].copy_from_slice(&(MAX_ART_LEN as u32 + 1).to_le_bytes());
+		let error = TocEntry::read(&mut Cursor::new(bytes), ART_VERSION).expect_err("a corrupt art length");
+		assert!(error.to_string().contains("exceeds"), "unexpected error: {}", error);
+	}
+
+	#[test]
+	fn oversized_seek_index_is_refused() {
+		let mut bytes = entry_bytes(SEEK_INDEX_VERSION);
+		let count_at = bytes.len() - SEEK_POINT_LEN - SEEK_COUNT_LEN;
+		bytes[count_at..count_at + SEEK_COUNT_LEN].copy_from_slice(&(MAX_SEEK_POINTS as u32 + 1).to_le_bytes());
+		let error = TocEntry::read(&mut Cursor::new(bytes), SEEK_INDEX_VERSION).expect_err("a corrupt seek point count");
+		assert!(error.to_string().contains("exceeds"), "unexpected error: {}", error);
+	}
+
+	#[test]
+	fn signature_and_track_crcs_are_left_out_of_the_signed_bytes() {
+		let unsigned = CassetteHeader::new(entries_for(SIGNATURE_VERSION));
+		let entries = unsigned.entries.iter().map(|entry| TocEntry { crc: Some(0xFEED_FACE), ..entry.clone() }).collect();
+		let signed = CassetteHeader {
+			signature: Some(CassetteSignature { public_key: [3; PUBLIC_KEY_LEN], signature: [4; SIGNATURE_LEN] }),
+			entries,
+			..unsigned.clone()
+		};
+		assert_eq!(signed.unsigned_bytes(), unsigned.to_bytes());
+		assert_eq!(signed.to_bytes().len(), unsigned.to_bytes().len() + PUBLIC_KEY_LEN + SIGNATURE_LEN);
+	}
+
+	#[test]
+	fn oversized_album_text_is_refused() {
+		let mut bytes = Vec::new();
+		sample_album().write(&mut bytes).unwrap();
+		bytes[..ALBUM_TEXT_LEN_LEN].copy_from_slice(&(MAX_ALBUM_TEXT_LEN as u32 + 1).to_le_bytes());
+		let error = AlbumInfo::read(&mut Cursor::new(bytes)).expect_err("a corrupt album length");
+		assert!(error.to_string().contains("exceeds"), "unexpected error: {}", error);
+		assert!(AlbumInfo { album: "A".repeat(MAX_ALBUM_TEXT_LEN + 1), ..sample_album() }.validated().is_err());
+	}
+
+	#[test]
+	fn oversized_lyrics_are_refused() {
+		let mut bytes = entry_bytes(LYRICS_VERSION);
+		let len_at = bytes.len() - SAMPLE_LYRICS.len() - LYRICS_LEN_LEN;
+		bytes[len_at..len_at + LYRICS_LEN_LEN].copy_from_slice(&(MAX_LYRICS_LEN as u32 + 1).to_le_bytes());
+		let error = TocEntry::read(&mut Cursor::new(bytes), LYRICS_VERSION).expect_err("a corrupt lyrics length");
+		assert!(error.to_string().contains("exceed"), "unexpected error: {}", error);
+	}
+
+	#[test]
+	fn alignment_pads_to_the_next_boundary() {
+		assert_eq!(padding_before(4096, Some(4096)), 0);
+		assert_eq!(padding_before(4097, Some(4096)), 4095);
+		assert_eq!(padding_before(4097, Some(1)), 0);
+		assert_eq!(padding_before(4097, None), 0);
+	}
+
+	#[test]
+	fn unknown_codec_reads_as_unknown() {
+		// A codec byte from a newer build isn't an error; players just sniff the format
+		let mut entry = entries_for(CODEC_VERSION).remove(0);
+		let mut bytes = entry_bytes(CODEC_VERSION);
+		*bytes.last_mut().unwrap() = 200;
+		entry.codec = None;
+		assert_eq!(TocEntry::read(&mut Cursor::new(&bytes), CODEC_VERSION).unwrap(), entry);
+	}
+
+	#[test]
+	fn zero_track_number_means_by_position() {
+		let mut entry = entries_for(TRACK_NUMBER_VERSION).remove(0);
+		entry.number = Some(0);
+		let mut bytes = Vec::new();
+		entry.write(&mut bytes, TRACK_NUMBER_VERSION).unwrap();
+		let decoded = TocEntry::read(&mut Cursor::new(bytes), TRACK_NUMBER_VERSION).unwrap();
+		assert_eq!(decoded.number, None);
+		assert_eq!(decoded.display_number(4), 5);
+	}
+
+	#[test]
+	fn newer_version_is_refused() {
+		let mut bytes = CassetteHeader::new(entries_for(FORMAT_VERSION)).to_bytes();
+		bytes[MAGIC.len()] = FORMAT_VERSION + 1;
+		let error = CassetteHeader::read(&mut Cursor::new(bytes)).expect_err("a version from the future");
+		assert!(error.to_string().contains("only understands up to"), "unexpected error: {}", error);
+	}
+
+	#[test]
+	fn truncated_toc_is_an_error() {
+		let bytes = CassetteHeader::new(entries_for(FORMAT_VERSION)).to_bytes();
+		for len in [0, MAGIC.len() + 1, MAGIC.len() + 1 + COUNT_LEN + NAME_LEN_LEN, bytes.len() - 1] {
+			assert!(CassetteHeader::read(&mut Cursor::new(&bytes[..len])).is_err(), "{} bytes", len);
+		}
+	}
+
+	#[test]
+	fn crc_round_trips() {
+		let mut bytes = Vec::new();
+		write_crc(&mut bytes, 0xDEAD_BEEF).unwrap();
+		assert_eq!(bytes, 0xDEAD_BEEFu32.to_le_bytes());
+		assert_eq!(read_crc(&mut Cursor::new(bytes)).unwrap(), 0xDEAD_BEEF);
+	}
+}
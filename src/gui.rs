@@ -4,17 +4,34 @@
 //
 // Desktop GUI for Rewind.png cassettes using Dioxus. Provides a visual
 // track list, playback controls, and progress display. Minimal prototype
+// The album panel edits the cassette-level metadata and saves it straight
+// back into the TOC (format v8+, unsigned cassettes only).
 
 use std::io::{Read, Seek, SeekFrom, Cursor};
-use std::sync::{Mutex, OnceLock};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 
 use dioxus::prelude::*;
-use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use rodio::{OutputStream, Sink};
 use lofty::file::{AudioFile, TaggedFileExt};
 use lofty::probe::Probe;
 use lofty::tag::Accessor;
-
-use crate::io::{open_file, find_iend, format_duration};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+
+use crate::io::{open_file, format_duration, track_buffer};
+use crate::art::cover_thumbnail;
+use crate::format::{AlbumInfo, CassetteLayout, TocEntry, ALBUM_VERSION, BLANK_CASSETTE};
+use crate::lyrics::{current_line, parse_lrc, LyricLine};
+use crate::merge::rewrite_album;
+use crate::notify::notify_now_playing;
+use crate::history::record_play;
+use crate::playback::{decode_with_hint, format_hint, open_output_stream, output_device_names, start_index, PlaybackError};
+use crate::logger::{log, LogLevel};
+use crate::mpris::{MediaCommand, MediaControls, MediaStatus, NowPlaying};
+use crate::equalizer::{BAND_NAMES, MAX_GAIN_DB, EqSettings, Equalizer};
+use crate::balance::{Balance, BalanceSettings, MAX_BALANCE, describe};
 
 // ══════════════════════════════════════════════════════════════════════════════
 // DATA STRUCTURES
@@ -26,9 +43,53 @@ pub struct Track {
 	pub name: String,
 	pub size: u64,
 	pub offset: u64,
+	/// Number shown in the playlist (the stored track number, or the position)
+	pub number: u64,
 	pub artist: String,
 	pub title: String,
 	pub duration_secs: u64,
+	/// Thumbnail as a `data:` URI: the track's own art, or the cassette cover's
+	pub art: Option<String>,
+	/// Synced lyrics, empty when the track has none
+	pub lyrics: Vec<LyricLine>,
+	/// Decoder format hint (see `format_hint`)
+	pub hint: Option<String>,
+}
+
+/// Cassette-level metadata shown in the album panel
+#[derive(Clone, Debug, PartialEq)]
+pub struct AlbumState {
+	pub info: AlbumInfo,
+	/// Why the panel can't save (old format, signed cassette); `None` when it can
+	pub read_only: Option<String>,
+}
+
+/// Album panel fields as typed, before they are checked
+#[derive(Clone, Debug, Default, PartialEq)]
+struct AlbumDraft {
+	album: String,
+	artist: String,
+	year: String,
+}
+
+impl AlbumDraft {
+	fn from_info(info: &AlbumInfo) -> Self {
+		AlbumDraft {
+			album: info.album.clone(),
+			artist: info.artist.clone(),
+			year: info.year.map(|year| year.to_string()).unwrap_or_default(),
+		}
+	}
+
+	/// Trims the fields; an empty year means unknown
+	fn to_info(&self) -> Result<AlbumInfo, String> {
+		let year = match self.year.trim() {
+			"" => None,
+			text => Some(text.parse::<u16>().ok().filter(|&year| year != 0)
+				.ok_or_else(|| format!("'{}' isn't a year. Use a number from 1 to 65535, or leave it empty.", text))?),
+		};
+		Ok(AlbumInfo { album: self.album.trim().to_string(), artist: self.artist.trim().to_string(), year })
+	}
 }
 
 /// Player state
@@ -39,6 +100,9 @@ pub enum PlayerState {
 	Paused,
 }
 
+/// How often the GUI reconciles its state with the audio sink
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
 /// Global app data (set before launch)
 static APP_DATA: OnceLock<AppData> = OnceLock::new();
 
@@ -46,76 +110,222 @@ static APP_DATA: OnceLock<AppData> = OnceLock::new();
 struct AppData {
 	cassette_path: String,
 	tracks: Vec<Track>,
+	album: AlbumState,
+	/// Show a desktop notification on every track change
+	notify: bool,
+	/// Track selected on launch (0-based, already clamped)
+	start_track: usize,
+	/// Start playing `start_track` right away
+	autoplay: bool,
+	/// Output device chosen on the command line (the default device when unset)
+	device: Option<String>,
 }
 
-/// Audio player wrapper - must be kept alive for playback
+/// Audio player wrapper - must be kept alive for playback.
+/// Created once per session; tracks are switched by clearing and refilling the sink
+/// rather than rebuilding the output stream.
 struct AudioPlayer {
 	_stream: OutputStream,
 	sink: Sink,
+	/// Equalizer gains, applied to every track for the rest of the session
+	eq: Arc<EqSettings>,
+	/// Left/right balance, also kept for the whole session
+	balance: Arc<BalanceSettings>,
 }
 
 impl AudioPlayer {
-	fn new() -> Option<Self> {
-		let stream = OutputStreamBuilder::open_default_stream().ok()?;
-		let sink = Sink::connect_new(&stream.mixer());
-		Some(Self {
-			_stream: stream,
-			sink,
-		})
+	/// Opens `device` (or the default device) with a flat EQ and centered balance
+	fn new(device: Option<&str>) -> Result<Self, PlaybackError> {
+		Self::open(device, Arc::new(EqSettings::default()), Arc::new(BalanceSettings::default()))
+	}
+
+	fn open(device: Option<&str>, eq: Arc<EqSettings>, balance: Arc<BalanceSettings>) -> Result<Self, PlaybackError> {
+		let (stream, warning) = open_output_stream(device)?;
+		if let Some(warning) = warning {
+			log(LogLevel::Warning, &warning);
+		}
+		let sink = Sink::connect_new(stream.mixer());
+		Ok(Self { _stream: stream, sink, eq, balance })
+	}
+
+	/// Replaces whatever is queued with the given audio and starts playing it
+	fn load(&self, audio_data: Vec<u8>, hint: Option<&str>) -> Result<(), PlaybackError> {
+		let len = audio_data.len() as u64;
+		let source = decode_with_hint(Cursor::new(audio_data), len, hint)?;
+		self.sink.clear();
+		self.sink.append(Balance::new(Equalizer::new(source, Arc::clone(&self.eq)), Arc::clone(&self.balance)));
+		self.sink.play();
+		Ok(())
+	}
+
+	/// Pauses if playing, resumes if paused
+	fn toggle_pause(&self) {
+		if self.sink.is_paused() {
+			self.sink.play();
+		} else {
+			self.sink.pause();
+		}
+	}
+
+	/// Empties the queue (the stream stays open for the next track)
+	fn stop(&self) {
+		self.sink.clear();
+	}
+
+	/// The state the sink is actually in
+	fn state(&self) -> PlayerState {
+		if self.sink.empty() {
+			PlayerState::Stopped
+		} else if self.sink.is_paused() {
+			PlayerState::Paused
+		} else {
+			PlayerState::Playing
+		}
 	}
 }
 
-/// Global audio player (needs to stay alive)
-static AUDIO_PLAYER: OnceLock<Mutex<Option<AudioPlayer>>> = OnceLock::new();
+/// Global audio player (needs to stay alive), or why no output device could be opened.
+static AUDIO_PLAYER: OnceLock<Mutex<Result<AudioPlayer, PlaybackError>>> = OnceLock::new();
 
-fn get_or_init_player() -> &'static Mutex<Option<AudioPlayer>> {
-	AUDIO_PLAYER.get_or_init(|| Mutex::new(AudioPlayer::new()))
+fn get_or_init_player() -> &'static Mutex<Result<AudioPlayer, PlaybackError>> {
+	AUDIO_PLAYER.get_or_init(|| Mutex::new(AudioPlayer::new(APP_DATA.get().and_then(|data| data.device.as_deref()))))
 }
 
-// ══════════════════════════════════════════════════════════════════════════════
-// CASSETTE LOADING
-// ══════════════════════════════════════════════════════════════════════════════
+/// Moves the global player to another output device, keeping the EQ and balance.
+/// Whatever was playing stops; the old device stays in use if the new one can't be opened.
+fn switch_output(device: Option<&str>) -> Result<(), PlaybackError> {
+	let mut guard = get_or_init_player().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	let (eq, balance) = match guard.as_ref() {
+		Ok(player) => (Arc::clone(&player.eq), Arc::clone(&player.balance)),
+		Err(_) => Default::default(),
+	};
+	let player = AudioPlayer::open(device, eq, balance)?;
+	if let Ok(old) = guard.as_ref() {
+		old.stop();
+	}
+	*guard = Ok(player);
+	Ok(())
+}
 
-/// Load track metadata from a cassette file
-fn load_tracks(path: &str) -> Result<Vec<Track>, String> {
-	let mut file = open_file(path)?;
+/// Runs `f` against the global player. Returns `None` if there is no audio device.
+fn with_player<T>(f: impl FnOnce(&AudioPlayer) -> T) -> Option<T> {
+	let guard = get_or_init_player().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	guard.as_ref().ok().map(f)
+}
 
-	let toc_pos = find_iend(&mut file)
-		.ok_or_else(|| "No PNG structure found - is this a valid cassette?".to_string())?;
+/// Replaces whatever the global player is playing with `audio_data` (decoded with `hint`)
+fn load_into_player(audio_data: Vec<u8>, hint: Option<&str>) -> Result<(), PlaybackError> {
+	let guard = get_or_init_player().lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+	match guard.as_ref() {
+		Ok(player) => player.load(audio_data, hint),
+		Err(e) => Err(e.clone()),
+	}
+}
 
-	file.seek(SeekFrom::Start(toc_pos)).map_err(|e| e.to_string())?;
+/// Current player state, or `Stopped` if there is no audio device
+fn player_state_now() -> PlayerState {
+	with_player(|player| player.state()).unwrap_or(PlayerState::Stopped)
+}
 
-	let mut count_buf = [0u8; 4];
-	file.read_exact(&mut count_buf).map_err(|e| e.to_string())?;
-	let track_count = u32::from_le_bytes(count_buf) as usize;
+/// Signals shared by every control that starts, stops or reloads playback
+#[derive(Clone, Copy)]
+struct PlayerSignals {
+	tracks: Signal<Vec<Track>>,
+	current_track_idx: Signal<Option<usize>>,
+	player_state: Signal<PlayerState>,
+	/// Set when the cassette file can no longer be read; blocks playback until reloaded
+	cassette_error: Signal<Option<String>>,
+	/// Why the last track didn't start (no device, unsupported audio); cleared by the next one that does
+	playback_error: Signal<Option<String>>,
+}
 
-	// Parse TOC entries
-	let mut entries: Vec<(String, u64)> = Vec::new();
-	for _ in 0..track_count {
-		let mut len_buf = [0u8; 4];
-		file.read_exact(&mut len_buf).map_err(|e| e.to_string())?;
-		let name_len = u32::from_le_bytes(len_buf) as usize;
+impl PlayerSignals {
+	/// Loads track `idx` from the cassette and switches the player to it
+	fn play(mut self, idx: usize) {
+		if self.cassette_error.peek().is_some() { return; }
+		let Some(track) = self.tracks.peek().get(idx).cloned() else { return };
+		if track.size == 0 { return; }
+
+		let audio_data = match load_track_data(&cassette_path(), &track) {
+			Ok(data) => data,
+			Err(e) => {
+				// Keep the track list visible, but block playback until the cassette is reloaded
+				self.stop();
+				self.cassette_error.set(Some(PlaybackError::FileRead(e).to_string()));
+				return;
+			}
+		};
+
+		match load_into_player(audio_data, track.hint.as_deref()) {
+			Ok(()) => {
+				self.current_track_idx.set(Some(idx));
+				self.playback_error.set(None);
+				if let Err(e) = record_play(Path::new(&cassette_path()), &track.name) {
+					log(LogLevel::Warning, &e);
+				}
+				if APP_DATA.get().is_some_and(|data| data.notify) {
+					notify_now_playing(&track.artist, &track.title);
+				}
+			}
+			Err(e) => {
+				log(LogLevel::Error, &format!("Cannot play '{}': {}", track.name, e));
+				self.stop();
+				self.report(&e);
+			}
+		}
+		self.player_state.set(player_state_now());
+	}
 
-		let mut name_buf = vec![0u8; name_len];
-		file.read_exact(&mut name_buf).map_err(|e| e.to_string())?;
-		let name = String::from_utf8_lossy(&name_buf).to_string();
+	/// Shows why playback couldn't start, until a track does
+	fn report(mut self, error: &PlaybackError) {
+		self.playback_error.set(Some(error.to_string()));
+	}
 
-		let mut size_buf = [0u8; 8];
-		file.read_exact(&mut size_buf).map_err(|e| e.to_string())?;
-		let size = u64::from_le_bytes(size_buf);
+	/// Stops playback and clears the current track
+	fn stop(mut self) {
+		with_player(|player| player.stop());
+		self.player_state.set(PlayerState::Stopped);
+		self.current_track_idx.set(None);
+	}
 
-		entries.push((name, size));
+	/// Re-reads the cassette from disk, clearing the error if it is readable again
+	fn reload(mut self) {
+		match load_tracks(&cassette_path()) {
+			Ok(tracks) if tracks.is_empty() => self.cassette_error.set(Some(BLANK_CASSETTE.to_string())),
+			Ok(tracks) => {
+				self.stop();
+				self.tracks.set(tracks);
+				self.cassette_error.set(None);
+			}
+			Err(e) => self.cassette_error.set(Some(e)),
+		}
 	}
+}
+
+/// Path of the cassette this GUI was opened with
+fn cassette_path() -> String {
+	APP_DATA.get().map(|data| data.cassette_path.clone()).unwrap_or_default()
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// CASSETTE LOADING
+// ══════════════════════════════════════════════════════════════════════════════
 
-	// Calculate offsets and load metadata
-	let audio_start = file.stream_position().map_err(|e| e.to_string())?;
+/// Load track metadata from a cassette file
+fn load_tracks(path: &str) -> Result<Vec<Track>, String> {
+	let mut file = open_file(path)?;
+	let layout = CassetteLayout::read(&mut file)?;
+	let offsets = layout.track_offsets();
 	let mut tracks = Vec::new();
-	let mut offset = audio_start;
+	let cover_art = cover_thumbnail(Path::new(path)).map(|png| png_data_uri(&png));
 
-	for (name, size) in entries {
+	for (idx, (entry, offset)) in layout.header.entries.into_iter().zip(offsets).enumerate() {
+		let number = entry.display_number(idx);
+		let hint = format_hint(&entry);
+		let TocEntry { name, size, art, lyrics, .. } = entry;
 		// Read audio data to extract metadata
 		file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
-		let mut audio_data = vec![0u8; size as usize];
+		let mut audio_data = track_buffer(size).map_err(|e| format!("'{}': {}", name, e))?;
 		file.read_exact(&mut audio_data).map_err(|e| e.to_string())?;
 
 		let (artist, title, duration_secs) = match Probe::new(Cursor::new(&audio_data)).guess_file_type() {
@@ -136,22 +346,50 @@ fn load_tracks(path: &str) -> Result<Vec<Track>, String> {
 			name,
 			size,
 			offset,
+			number,
 			artist,
 			title,
 			duration_secs,
+			art: if art.is_empty() { cover_art.clone() } else { Some(png_data_uri(&art)) },
+			lyrics: parse_lrc(&lyrics),
+			hint,
 		});
-
-		offset += size;
 	}
 
 	Ok(tracks)
 }
 
+/// Load the cassette-level album metadata, and whether the panel may save it
+fn load_album(path: &str) -> Result<AlbumState, String> {
+	let mut file = open_file(path)?;
+	let header = CassetteLayout::read(&mut file)?.header;
+	let read_only = if header.version < ALBUM_VERSION {
+		Some(format!("This cassette (format v{}) has no album metadata. Re-record it to add some.", header.version))
+	} else if header.signature.is_some() {
+		Some("This cassette is signed; editing it would break the signature.".to_string())
+	} else {
+		None
+	};
+	Ok(AlbumState { info: header.album.unwrap_or_default(), read_only })
+}
+
+/// Writes the edited album metadata into the cassette, then reads it back from disk
+fn save_album(path: &str, draft: &AlbumDraft) -> Result<(AlbumState, Vec<Track>), String> {
+	rewrite_album(path, draft.to_info()?)?;
+	log(LogLevel::Success, &format!("Saved the album metadata of {}.", path));
+	Ok((load_album(path)?, load_tracks(path)?))
+}
+
+/// Embeds PNG bytes in a URI the webview can show without touching the disk
+fn png_data_uri(png: &[u8]) -> String {
+	format!("data:image/png;base64,{}", BASE64.encode(png))
+}
+
 /// Load raw audio data for a specific track
 fn load_track_data(cassette_path: &str, track: &Track) -> Result<Vec<u8>, String> {
 	let mut file = open_file(cassette_path)?;
 	file.seek(SeekFrom::Start(track.offset)).map_err(|e| e.to_string())?;
-	let mut data = vec![0u8; track.size as usize];
+	let mut data = track_buffer(track.size)?;
 	file.read_exact(&mut data).map_err(|e| e.to_string())?;
 	Ok(data)
 }
@@ -161,13 +399,23 @@ fn load_track_data(cassette_path: &str, track: &Track) -> Result<Vec<u8>, String
 // ══════════════════════════════════════════════════════════════════════════════
 
 /// Main entry point for the GUI
-pub fn run_gui(cassette_path: &str) -> Result<(), String> {
+pub fn run_gui(cassette_path: &str, notify: bool, start_track: Option<usize>, autoplay: bool, device: Option<String>) -> Result<(), String> {
 	let tracks = load_tracks(cassette_path)?;
+	if tracks.is_empty() {
+		return Err(BLANK_CASSETTE.to_string());
+	}
+	let album = load_album(cassette_path)?;
+	let start_track = start_index(start_track, tracks.len());
 
 	// Store app data globally before launch
 	APP_DATA.set(AppData {
 		cassette_path: cassette_path.to_string(),
 		tracks,
+		album,
+		notify,
+		start_track,
+		autoplay,
+		device,
 	}).map_err(|_| "Failed to initialize app data")?;
 
 	// Initialize audio player
@@ -198,19 +446,113 @@ pub fn run_gui(cassette_path: &str) -> Result<(), String> {
 fn App() -> Element {
 	// Get app data from global
 	let app_data = APP_DATA.get().expect("App data not initialized");
-	let tracks = app_data.tracks.clone();
-	let cassette_path = app_data.cassette_path.clone();
 
 	// State
-	let mut selected_track = use_signal(|| 0usize);
-	let mut player_state = use_signal(|| PlayerState::Stopped);
-	let mut current_track_idx = use_signal(|| None::<usize>);
+	let mut selected_track = use_signal(|| app_data.start_track);
+	let player = PlayerSignals {
+		tracks: use_signal(|| app_data.tracks.clone()),
+		current_track_idx: use_signal(|| None::<usize>),
+		player_state: use_signal(|| PlayerState::Stopped),
+		cassette_error: use_signal(|| None::<String>),
+		playback_error: use_signal(|| None::<String>),
+	};
+	let mut player_state = player.player_state;
+	let mut eq_gains = use_signal(|| with_player(|p| p.eq.gains()).unwrap_or_default());
+	let mut balance = use_signal(|| with_player(|p| p.balance.get()).unwrap_or_default());
+	let devices = use_signal(output_device_names);
+	let mut output_device = use_signal(|| app_data.device.clone());
+	let mut album = use_signal(|| app_data.album.clone());
+	let mut album_draft = use_signal(|| AlbumDraft::from_info(&app_data.album.info));
+	let mut album_status = use_signal(|| None::<Result<String, String>>);
+	// Index of the lyric line being sung, following the sink's position
+	let mut lyric_line = use_signal(|| None::<usize>);
+
+	// Poll the sink so the UI follows what is actually playing, and auto-advance
+	// when a track ends on its own (the GUI analog of the TUI's check_track_finished)
+	use_future(move || async move {
+		// Media keys and desktop widgets (MPRIS), serviced on the same tick
+		let mut media = MediaControls::start();
+
+		if app_data.autoplay {
+			player.play(app_data.start_track);
+		}
+
+		loop {
+			tokio::time::sleep(POLL_INTERVAL).await;
+
+			while let Some(command) = media.as_ref().and_then(|m| m.try_recv()) {
+				let state = player_state_now();
+				match command {
+					MediaCommand::Play if state == PlayerState::Paused => { with_player(|p| p.toggle_pause()); }
+					MediaCommand::Pause if state == PlayerState::Playing => { with_player(|p| p.toggle_pause()); }
+					MediaCommand::PlayPause if state != PlayerState::Stopped => { with_player(|p| p.toggle_pause()); }
+					MediaCommand::Play | MediaCommand::PlayPause if state == PlayerState::Stopped => player.play(*selected_track.peek()),
+					MediaCommand::Next | MediaCommand::Previous => {
+						let len = player.tracks.peek().len();
+						let current = (*player.current_track_idx.peek()).unwrap_or(*selected_track.peek());
+						if len > 0 {
+							let target = if command == MediaCommand::Next { (current + 1) % len } else { (current + len - 1) % len };
+							player.play(target);
+						}
+					}
+					MediaCommand::Stop => player.stop(),
+					_ => {}
+				}
+			}
+
+			let finished = *player_state.peek() == PlayerState::Playing
+				&& player_state_now() == PlayerState::Stopped;
+			if finished {
+				let next = (*player.current_track_idx.peek()).map(|idx| idx + 1)
+					.filter(|&next| next < player.tracks.peek().len());
+				match next {
+					Some(next) => player.play(next),
+					None => player.stop(),
+				}
+			}
+
+			let actual = player_state_now();
+			if *player_state.peek() != actual {
+				player_state.set(actual);
+			}
+
+			let line = {
+				let tracks = player.tracks.peek();
+				(*player.current_track_idx.peek())
+					.and_then(|idx| tracks.get(idx))
+					.filter(|track| !track.lyrics.is_empty())
+					.and_then(|track| current_line(&track.lyrics, with_player(|p| p.sink.get_pos()).unwrap_or_default()))
+			};
+			if *lyric_line.peek() != line {
+				lyric_line.set(line);
+			}
+
+			if let Some(ref mut media) = media {
+				let status = match actual {
+					PlayerState::Playing => MediaStatus::Playing,
+					PlayerState::Paused => MediaStatus::Paused,
+					PlayerState::Stopped => MediaStatus::Stopped,
+				};
+				let track = (*player.current_track_idx.peek())
+					.and_then(|idx| player.tracks.peek().get(idx).cloned())
+					.map(|track| NowPlaying { title: track.title, artist: track.artist, duration_secs: track.duration_secs });
+				media.publish(status, track);
+			}
+		}
+	});
 
 	// Get current track info for display
-	let current_idx = *current_track_idx.read();
-	let now_playing_track = current_idx.map(|idx| tracks[idx].clone());
+	let tracks = player.tracks.read().clone();
+	let current_idx = *player.current_track_idx.read();
+	let now_playing_track = current_idx.and_then(|idx| tracks.get(idx).cloned());
 	let state = *player_state.read();
 	let selected = *selected_track.read();
+	let cassette_error = player.cassette_error.read().clone();
+	let controls_disabled = cassette_error.is_some();
+	let playback_error = player.playback_error.read().clone();
+	let album_state = album.read().clone();
+	let draft = album_draft.read().clone();
+	let album_summary = if album_state.info.is_empty() { "No album info".to_string() } else { album_state.info.to_string() };
 
 	rsx! {
 		style { {CSS} }
@@ -222,6 +564,77 @@ fn App() -> Element {
 				span { class: "cassette-icon", " [●▪▪●]" }
 			}
 
+			// Shown when the cassette was moved/deleted; the track list stays visible
+			if let Some(ref error) = cassette_error {
+				div { class: "error-banner",
+					div { class: "error-text",
+						div { class: "error-title", "⚠ Cassette no longer accessible" }
+						div { class: "error-detail", "{error}" }
+					}
+					button { onclick: move |_| player.reload(), "⟳ Reload" }
+				}
+			} else if let Some(ref error) = playback_error {
+				div { class: "error-banner",
+					div { class: "error-text",
+						div { class: "error-title", "⚠ Cannot play this track" }
+						div { class: "error-detail", "{error}" }
+					}
+				}
+			}
+
+			// Album metadata: editable on v8+ unsigned cassettes, read-only otherwise
+			div { class: "album",
+				if let Some(ref reason) = album_state.read_only {
+					div { class: "album-summary", "{album_summary}" }
+					div { class: "album-note", "{reason}" }
+				} else {
+					div { class: "album-fields",
+						input {
+							placeholder: "Album",
+							value: "{draft.album}",
+							oninput: move |evt| album_draft.write().album = evt.value(),
+						}
+						input {
+							placeholder: "Artist",
+							value: "{draft.artist}",
+							oninput: move |evt| album_draft.write().artist = evt.value(),
+						}
+						input {
+							class: "album-year",
+							placeholder: "Year",
+							value: "{draft.year}",
+							oninput: move |evt| album_draft.write().year = evt.value(),
+						}
+						button {
+							disabled: controls_disabled,
+							onclick: move |_| {
+								let draft = album_draft.peek().clone();
+								match save_album(&cassette_path(), &draft) {
+									Ok((saved, tracks)) => {
+										// The TOC may change size, moving every track; the playing one is already in memory
+										let mut track_list = player.tracks;
+										track_list.set(tracks);
+										album_draft.set(AlbumDraft::from_info(&saved.info));
+										album.set(saved);
+										album_status.set(Some(Ok("Saved.".to_string())));
+									}
+									Err(e) => {
+										log(LogLevel::Error, &format!("Cannot save the album metadata: {}", e));
+										album_status.set(Some(Err(e)));
+									}
+								}
+							},
+							"Save"
+						}
+					}
+					match album_status.read().clone() {
+						Some(Ok(message)) => rsx! { div { class: "album-note", "{message}" } },
+						Some(Err(error)) => rsx! { div { class: "album-note album-error", "{error}" } },
+						None => rsx! {},
+					}
+				}
+			}
+
 			// Track list
 			div { class: "track-list",
 				for (idx, track) in tracks.iter().enumerate() {
@@ -235,31 +648,17 @@ fn App() -> Element {
 						} else {
 							"track"
 						};
-						let track_for_play = track.clone();
-						let path_for_play = cassette_path.clone();
-
 						rsx! {
 							div {
 								class: "{class_name}",
 								onclick: move |_| selected_track.set(idx),
-								ondoubleclick: move |_| {
-									// Play track on double click
-									if let Ok(audio_data) = load_track_data(&path_for_play, &track_for_play) {
-										if let Ok(mut guard) = get_or_init_player().lock() {
-											// Recreate player to stop previous track
-											*guard = AudioPlayer::new();
-											if let Some(ref player) = *guard {
-												if let Ok(source) = Decoder::new(Cursor::new(audio_data)) {
-													player.sink.append(source);
-													current_track_idx.set(Some(idx));
-													player_state.set(PlayerState::Playing);
-												}
-											}
-										}
-									}
-								},
+								// Play track on double click
+								ondoubleclick: move |_| player.play(idx),
 
-								span { class: "track-number", "{idx + 1}." }
+								span { class: "track-number", "{track.number}." }
+								if let Some(ref art) = track.art {
+									img { class: "track-art", src: "{art}" }
+								}
 								div { class: "track-info",
 									div { class: "track-title", "{track.title}" }
 									div { class: "track-artist", "{track.artist}" }
@@ -285,6 +684,22 @@ fn App() -> Element {
 							PlayerState::Stopped => "⏹ Stopped",
 						}}
 					}
+					if !track.lyrics.is_empty() {
+						// The line being sung between the ones around it
+						div { class: "lyrics",
+							for offset in -1..=1isize {
+								{
+									let current = lyric_line.read().map_or(-1, |idx| idx as isize);
+									let text = usize::try_from(current + offset).ok()
+										.and_then(|idx| track.lyrics.get(idx))
+										.map_or(String::new(), |line| line.text.clone());
+									rsx! {
+										div { class: if offset == 0 { "lyric current" } else { "lyric" }, "{text}" }
+									}
+								}
+							}
+						}
+					}
 				} else {
 					div { class: "np-empty", "No track playing" }
 				}
@@ -293,58 +708,91 @@ fn App() -> Element {
 			// Controls
 			div { class: "controls",
 				button {
-					onclick: {
-						let tracks = tracks.clone();
-						let cassette_path = cassette_path.clone();
-						move |_| {
-							let sel = *selected_track.read();
-							if sel < tracks.len() {
-								let track = &tracks[sel];
-								if let Ok(audio_data) = load_track_data(&cassette_path, track) {
-									if let Ok(mut guard) = get_or_init_player().lock() {
-										*guard = AudioPlayer::new();
-										if let Some(ref player) = *guard {
-											if let Ok(source) = Decoder::new(Cursor::new(audio_data)) {
-												player.sink.append(source);
-												current_track_idx.set(Some(sel));
-												player_state.set(PlayerState::Playing);
-											}
-										}
-									}
-								}
-							}
-						}
-					},
+					disabled: controls_disabled,
+					onclick: move |_| player.play(*selected_track.read()),
 					"▶ Play"
 				}
 				button {
+					disabled: controls_disabled,
 					onclick: move |_| {
-						if let Ok(guard) = get_or_init_player().lock() {
-							if let Some(ref player) = *guard {
-								if player.sink.is_paused() {
-									player.sink.play();
-									player_state.set(PlayerState::Playing);
-								} else {
-									player.sink.pause();
-									player_state.set(PlayerState::Paused);
-								}
+						with_player(|player| {
+							if player.state() != PlayerState::Stopped {
+								player.toggle_pause();
 							}
-						}
+						});
+						player_state.set(player_state_now());
 					},
-					"⏸ Pause"
+					if state == PlayerState::Paused { "▶ Resume" } else { "⏸ Pause" }
 				}
 				button {
-					onclick: move |_| {
-						if let Ok(mut guard) = get_or_init_player().lock() {
-							if let Some(ref player) = *guard {
-								player.sink.stop();
+					disabled: controls_disabled,
+					onclick: move |_| player.stop(),
+					"⏹ Stop"
+				}
+			}
+
+			// Equalizer and balance (flat/centered by default; changes apply to the playing track right away)
+			div { class: "equalizer",
+				for (band, name) in BAND_NAMES.iter().enumerate() {
+					div { class: "eq-band",
+						span { class: "eq-label", "{name}" }
+						input {
+							r#type: "range",
+							min: "{-MAX_GAIN_DB}",
+							max: "{MAX_GAIN_DB}",
+							step: "1",
+							value: "{eq_gains.read()[band]}",
+							oninput: move |evt| {
+								let db = evt.value().parse::<i32>().unwrap_or(0);
+								with_player(|p| p.eq.set_gain(band, db));
+								eq_gains.write()[band] = db;
+							},
+						}
+						span { class: "eq-value", "{eq_gains.read()[band]:+} dB" }
+					}
+				}
+				div { class: "eq-band",
+					span { class: "eq-label", "Balance" }
+					input {
+						r#type: "range",
+						min: "{-MAX_BALANCE}",
+						max: "{MAX_BALANCE}",
+						step: "5",
+						value: "{balance}",
+						oninput: move |evt| {
+							let position = evt.value().parse::<i32>().unwrap_or(0);
+							with_player(|p| p.balance.set(position));
+							balance.set(position);
+						},
+					}
+					span { class: "eq-value", "{describe(balance())}" }
+				}
+			}
+
+			// Output device (switching stops the current track)
+			div { class: "output",
+				span { class: "eq-label", "Output" }
+				select {
+					onchange: move |evt| {
+						let name = evt.value();
+						let device = (!name.is_empty()).then_some(name);
+						player.stop();
+						match switch_output(device.as_deref()) {
+							Ok(()) => output_device.set(device),
+							Err(e) => {
+								log(LogLevel::Error, &format!("Cannot switch the output device: {}", e));
+								player.report(&e);
 							}
-							*guard = None;
 						}
-						player_state.set(PlayerState::Stopped);
-						current_track_idx.set(None);
 					},
-					"⏹ Stop"
+					option { value: "", selected: output_device.read().is_none(), "Default device" }
+					for name in devices.read().iter() {
+						option {
+							value: "{name}",
+							selected: output_device.read().as_deref().is_some_and(|d| d.eq_ignore_ascii_case(name)),
+							"{name}"
+						}
+					}
 				}
 			}
 		}
@@ -424,6 +872,13 @@ body {
 	min-width: 24px;
 }
 
+.track-art {
+	width: 40px;
+	height: 40px;
+	object-fit: cover;
+	border-radius: 4px;
+}
+
 .track-info {
 	flex: 1;
 }
@@ -476,6 +931,20 @@ body {
 	font-style: italic;
 }
 
+.lyrics {
+	margin-top: 12px;
+}
+
+.lyric {
+	color: #666;
+	min-height: 1.4em;
+}
+
+.lyric.current {
+	color: #00d4ff;
+	font-weight: bold;
+}
+
 .controls {
 	display: flex;
 	justify-content: center;
@@ -496,4 +965,143 @@ body {
 .controls button:hover {
 	background: #1565c0;
 }
+
+.controls button:disabled {
+	background: #2a2a4a;
+	color: #666;
+	cursor: not-allowed;
+}
+
+.equalizer {
+	display: flex;
+	gap: 12px;
+	background: #16213e;
+	border-radius: 8px;
+	padding: 12px 16px;
+}
+
+.eq-band {
+	flex: 1;
+	display: flex;
+	flex-direction: column;
+	align-items: center;
+	gap: 4px;
+}
+
+.eq-band input {
+	width: 100%;
+	accent-color: #1976d2;
+}
+
+.eq-label {
+	font-size: 12px;
+	color: #888;
+}
+
+.eq-value {
+	font-size: 12px;
+	color: #64b5f6;
+}
+
+.output {
+	display: flex;
+	align-items: center;
+	gap: 12px;
+	background: #16213e;
+	border-radius: 8px;
+	padding: 12px 16px;
+}
+
+.output select {
+	flex: 1;
+	background: #1a1a2e;
+	color: #eee;
+	border: 1px solid #333;
+	border-radius: 4px;
+	padding: 4px 8px;
+}
+
+.album {
+	background: #16213e;
+	border-radius: 8px;
+	padding: 12px 16px;
+}
+
+.album-summary {
+	font-weight: bold;
+	color: #64b5f6;
+}
+
+.album-fields {
+	display: flex;
+	gap: 8px;
+}
+
+.album-fields input {
+	flex: 1;
+	min-width: 0;
+	background: #1a1a2e;
+	color: #eee;
+	border: 1px solid #333;
+	border-radius: 4px;
+	padding: 4px 8px;
+}
+
+.album-fields input.album-year {
+	flex: 0 0 64px;
+}
+
+.album-fields button {
+	background: #1976d2;
+	color: #fff;
+	border: none;
+	padding: 4px 12px;
+	border-radius: 4px;
+	cursor: pointer;
+}
+
+.album-note {
+	font-size: 12px;
+	color: #888;
+	margin-top: 4px;
+}
+
+.album-error {
+	color: #ff6b6b;
+}
+
+.error-banner {
+	display: flex;
+	align-items: center;
+	gap: 12px;
+	background: #4a1c1c;
+	border: 1px solid #a33;
+	border-radius: 8px;
+	padding: 12px 16px;
+}
+
+.error-text {
+	flex: 1;
+}
+
+.error-title {
+	font-weight: bold;
+	color: #ff6b6b;
+}
+
+.error-detail {
+	font-size: 12px;
+	color: #d99;
+	margin-top: 2px;
+	word-break: break-all;
+}
+
+.error-banner button {
+	background: #a33;
+	color: #fff;
+	border: none;
+	padding: 8px 16px;
+	border-radius: 8px;
+	cursor: pointer;
+}
 "#;
@@ -0,0 +1,184 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// HISTORY MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Play counts and last-played times per cassette and track, kept in
+// `history.json` next to the config file (never in the cassette itself).
+// Cassettes are keyed by their stored CRC32 seal, so renaming or moving one keeps
+// its history; the path is only remembered for display. Every player counts a
+// play when a track starts. Remembering is best-effort: if the config directory
+// can't be written, playback goes on and nothing is recorded. A history file
+// that can't be read is never overwritten, so fixing it by hand loses nothing.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use chrono::{DateTime, Local, Utc};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use crate::format::{read_crc, CRC_LEN};
+use crate::io::{truncate, PendingFile};
+use crate::logger::{log, LogLevel};
+
+/// Rows shown by `stats` (most played) and `stats --history` (recently played).
+const STATS_LIMIT: usize = 10;
+const RECENT_LIMIT: usize = 20;
+/// Column width for cassette paths and track names.
+const PATH_WIDTH: usize = 48;
+
+/// How often something was played, and when last.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayCount {
+	pub plays: u64,
+	/// Unix time in seconds.
+	pub last_played: i64,
+}
+
+/// History of one cassette. Its own counts are the sum of its tracks'.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CassetteHistory {
+	/// Where the cassette was last played from.
+	pub path: String,
+	#[serde(flatten)]
+	pub count: PlayCount,
+	/// Keyed by track name.
+	pub tracks: BTreeMap<String, PlayCount>,
+}
+
+/// Everything that was played, keyed by the cassette's CRC32 seal in hex.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct History {
+	pub cassettes: BTreeMap<String, CassetteHistory>,
+}
+
+impl History {
+	/// Where the history lives, if the platform has a config directory.
+	pub fn path() -> Option<PathBuf> {
+		dirs::config_dir().map(|dir| dir.join("rewind").join("history.json"))
+	}
+
+	/// Reads the history. A missing file (or config directory) gives an empty one; a file
+	/// that can't be read or parsed is an error, so it isn't mistaken for an empty history.
+	pub fn load() -> Result<Self, String> {
+		let Some(path) = Self::path() else { return Ok(History::default()) };
+		let text = match fs::read_to_string(&path) {
+			Ok(text) => text,
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(History::default()),
+			Err(e) => return Err(format!("Cannot read the play history {}: {}", path.display(), e)),
+		};
+		serde_json::from_str(&text).map_err(|e| format!("The play history {} is damaged ({}). Plays aren't counted until it's fixed or removed.", path.display(), e))
+	}
+
+	/// Writes the history, replacing the old file only once the new one is complete.
+	fn save(&self) -> Result<(), String> {
+		let path = Self::path().ok_or("No config directory on this platform.")?;
+		if let Some(dir) = path.parent() {
+			fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+		}
+		let (pending, file) = PendingFile::create(&path)?;
+		let mut writer = BufWriter::new(file);
+		serde_json::to_writer_pretty(&mut writer, self).map_err(|e| e.to_string())?;
+		pending.commit(writer)
+	}
+
+	/// Counts one play of `track` on the cassette with seal `id`, found at `path`.
+	pub fn add_play(&mut self, id: &str, path: &str, track: &str, now: i64) {
+		let cassette = self.cassettes.entry(id.to_string()).or_default();
+		cassette.path = path.to_string();
+		for count in [&mut cassette.count, cassette.tracks.entry(track.to_string()).or_default()] {
+			count.plays += 1;
+			count.last_played = now;
+		}
+	}
+}
+
+/// The cassette's identity: its stored CRC32 seal, which renames don't change.
+fn cassette_id(path: &Path) -> Option<String> {
+	let mut file = File::open(path).ok()?;
+	file.seek(SeekFrom::End(-(CRC_LEN as i64))).ok()?;
+	read_crc(&mut file).ok().map(|crc| format!("{:08X}", crc))
+}
+
+/// Set once the player was told the history can't be read, so it's said only once per run.
+static LOAD_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Counts a play of `track` from the cassette at `cassette`. Write failures are ignored,
+/// so a read-only config directory never gets in the way of playback. A history that
+/// can't be read is left as it is; the first time, the error is returned for the player
+/// to show.
+pub fn record_play(cassette: &Path, track: &str) -> Result<(), String> {
+	let Some(id) = cassette_id(cassette) else { return Ok(()) };
+	let path = fs::canonicalize(cassette).unwrap_or_else(|_| cassette.to_path_buf());
+	let mut history = match History::load() {
+		Ok(history) => history,
+		Err(e) if !LOAD_WARNED.swap(true, Ordering::Relaxed) => return Err(e),
+		Err(_) => return Ok(()),
+	};
+	history.add_play(&id, &path.display().to_string(), track, Utc::now().timestamp());
+	let _ = history.save();
+	Ok(())
+}
+
+/// How often each of the named tracks on the cassette at `cassette` was played (0 when never).
+pub fn track_plays<'a>(cassette: &Path, names: impl Iterator<Item = &'a str>) -> Vec<u64> {
+	let history = History::load().unwrap_or_default();
+	let tracks = cassette_id(cassette).and_then(|id| history.cassettes.get(&id)).map(|cassette| &cassette.tracks);
+	names.map(|name| tracks.and_then(|tracks| tracks.get(name)).map_or(0, |count| count.plays)).collect()
+}
+
+/// Prints the most played cassettes, or with `recent` the recently played ones
+/// with each track's play count.
+pub fn print_stats(recent: bool) {
+	let history = match History::load() {
+		Ok(history) => history,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+	let location = History::path().map(|path| path.display().to_string()).unwrap_or_default();
+	if history.cassettes.is_empty() {
+		log(LogLevel::Warning, &format!("Nothing played yet (history: {}).", location));
+		return;
+	}
+	let total: u64 = history.cassettes.values().map(|cassette| cassette.count.plays).sum();
+	log(LogLevel::Info, &format!("{} play(s) of {} cassette(s) (history: {})", total, history.cassettes.len(), location));
+
+	let mut cassettes: Vec<&CassetteHistory> = history.cassettes.values().collect();
+	println!();
+	println!("  {}", format!("{:>5}  {:<16}  {}", "Plays", "Last played", "Cassette").bold());
+	println!("  {}", "─".repeat(5 + 2 + 16 + 2 + PATH_WIDTH).dimmed());
+	if recent {
+		cassettes.sort_by_key(|cassette| Reverse(cassette.count.last_played));
+		for cassette in cassettes.into_iter().take(RECENT_LIMIT) {
+			print_row(&cassette.count, &cassette.path, "");
+			let mut tracks: Vec<(&String, &PlayCount)> = cassette.tracks.iter().collect();
+			tracks.sort_by(|a, b| b.1.plays.cmp(&a.1.plays).then(b.1.last_played.cmp(&a.1.last_played)));
+			for (name, count) in tracks {
+				print_row(count, name, "  ");
+			}
+		}
+	} else {
+		cassettes.sort_by(|a, b| b.count.plays.cmp(&a.count.plays).then(b.count.last_played.cmp(&a.count.last_played)));
+		for cassette in cassettes.into_iter().take(STATS_LIMIT) {
+			print_row(&cassette.count, &cassette.path, "");
+		}
+	}
+	println!();
+}
+
+/// One row of the stats table; tracks are indented under their cassette.
+fn print_row(count: &PlayCount, name: &str, indent: &str) {
+	let when = DateTime::from_timestamp(count.last_played, 0)
+		.map(|time| time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+		.unwrap_or_default();
+	let name = truncate(name, PATH_WIDTH - indent.len());
+	if indent.is_empty() {
+		println!("  {:>5}  {:<16}  {}", count.plays, when, name);
+	} else {
+		println!("  {}", format!("{:>5}  {:<16}  {}{}", count.plays, when, indent, name).dimmed());
+	}
+}
@@ -6,107 +6,709 @@
 // parses the table of contents (TOC), and extracts audio metadata (artist, title,
 // duration) from embedded tracks using the Lofty library.
 
-use std::io::{Read, Seek, SeekFrom};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::num::NonZeroU64;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+use colored::*;
 use crc32fast::Hasher;
 use lofty::file::{AudioFile, TaggedFileExt};
 use lofty::probe::Probe;
 use lofty::tag::Accessor;
-use crate::io::{open_file, hash_only, find_iend, format_duration};
-use crate::logger::{log, LogLevel};
+use crate::io::{open_file, fit, hash_with_progress, Progress, ProgressReader, Region, find_iend, hexdump, format_duration, human_size, truncate};
+use crate::constants::FRAGILE_SIZE_RATIO;
+use crate::format::{Cassette, CassetteHeader, CassetteLayout, SealedRegion, TocEntry, BLANK_CASSETTE, COUNT_LEN, CRC_LEN};
+use crate::png::{compute_chunk_crc, image_end, read_chunks, read_image_info, ImageInfo, COVER_EXTENSIONS};
+use crate::ogg::read_chain;
+use crate::playback::{decode_with_hint, format_hint, PlaybackError};
+use crate::record::report_tape;
+use crate::tui::{title_label, REEL_FRAMES};
+use crate::sign::{SignatureStatus, fingerprint, load_verifying_key, start_digest, verify};
+use crate::logger::{log, LogLevel, trace_span};
+use crate::download::{download, is_url, DEFAULT_MAX_DOWNLOAD};
+use ed25519_dalek::VerifyingKey;
 
-pub struct TocEntry {
-	pub name: String,
-	pub size: u64,
+/// Number of post-IEND bytes shown by `--debug`.
+const DEBUG_DUMP_LEN: usize = 32;
+
+/// Rough upload limits for `--compat`. They change often and differ per service, so
+/// they're only a guide to where a cassette may get rejected or recompressed.
+const UPLOAD_LIMITS: [(&str, u64); 3] = [
+	("social media image uploads", 5 * 1024 * 1024),
+	("chat apps", 10 * 1024 * 1024),
+	("image hosts", 20 * 1024 * 1024),
+];
+/// Covers wider or taller than this are usually resized (and so re-encoded) on upload.
+const RESIZE_DIMENSION: u32 = 2048;
+
+/// Column widths of the track table. Longer names/tags are cut with an ellipsis.
+const NAME_WIDTH: usize = 28;
+const META_WIDTH: usize = 40;
+const SIZE_WIDTH: usize = 11;
+const TABLE_WIDTH: usize = 3 + 2 + NAME_WIDTH + 2 + SIZE_WIDTH + 2 + 6 + 2 + META_WIDTH;
+/// Names get the whole row when `--no-metadata` drops the time and tag columns.
+const TOC_NAME_WIDTH: usize = TABLE_WIDTH - (3 + 2 + 2 + SIZE_WIDTH);
+
+/// Width of the `--pretty` cassette's label, the same as the TUI's tape window.
+const LABEL_WIDTH: usize = 46;
+/// Room for a track's name on the label, between its number and time.
+const LABEL_NAME_WIDTH: usize = LABEL_WIDTH - (3 + 2 + 2 + 6);
+/// Width inside the `--pretty` cassette's shell.
+const SHELL_WIDTH: usize = LABEL_WIDTH + 6;
+
+/// Optional behaviour for `inspect`.
+#[derive(Default)]
+pub struct InspectOptions {
+	/// Dump the raw bytes following IEND and show exact sizes, to diagnose unreadable cassettes.
+	pub debug: bool,
+	/// Print only the computed CRC32 as hex (for scripts) and skip everything else.
+	pub raw: bool,
+	/// Verify each PNG chunk's own CRC32, reporting cover damage separately from tape damage.
+	pub check_image: bool,
+	/// Refuse files that aren't unambiguously cassettes (see `CassetteLayout::check_strict`).
+	pub strict: bool,
+	/// Skip the full-file CRC32 pass and go straight to the track list.
+	pub skip_crc: bool,
+	/// List only what the TOC says (index, name, size) without probing any track.
+	pub no_metadata: bool,
+	/// Start decoding every listed track, as the players would, and report which ones fail.
+	pub check_playable: bool,
+	/// Report where the cassette is likely to be rejected or stripped when shared.
+	pub compat: bool,
+	/// Verify each track against the CRC32 stored for it in the TOC (format v6+).
+	pub per_track: bool,
+	/// SPKI PEM file with the Ed25519 public key the cassette must be signed with.
+	pub pubkey: Option<String>,
+	/// Number of tracks to skip before the listed page.
+	pub offset: usize,
+	/// Largest number of tracks to list (all remaining when `None`).
+	pub limit: Option<usize>,
+	/// Draw the track list on the label of a cassette instead of printing a table.
+	pub pretty: bool,
+	/// Largest cassette fetched when inspecting a URL (`DEFAULT_MAX_DOWNLOAD` when `None`).
+	pub max_download: Option<NonZeroU64>,
 }
 
-/// Inspects the cassette file, verifying integrity and listing audio tracks.
-pub fn inspect(path: &str) {
-	log(LogLevel::Info, &format!("Inspecting file: {}", path));
+/// Inspects the cassette file (or http(s) URL), verifying integrity and listing audio tracks.
+pub fn inspect(path: &str, options: &InspectOptions) {
+	let _span = trace_span!("inspect", path, strict = options.strict, skip_crc = options.skip_crc);
+	if !options.raw {
+		log(LogLevel::Info, &format!("Inspecting file: {}", path));
+	}
+
+	// A link is downloaded to a temporary file and read from there; nothing is kept
+	if is_url(path) {
+		let cap = options.max_download.map_or(DEFAULT_MAX_DOWNLOAD, NonZeroU64::get);
+		match download(path, cap) {
+			Ok((temp, mut file)) => {
+				let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+				if !options.raw {
+					log(LogLevel::Info, &format!("Downloaded {}.", human_size(len)));
+				}
+				inspect_tape(path, &mut file, len, options);
+				// Closed before the temporary file is deleted
+				drop(file);
+				drop(temp);
+			}
+			Err(e) => log(LogLevel::Error, &e),
+		}
+		return;
+	}
 
 	let mut file = match open_file(path) {
 		Ok(f) => f,
 		Err(e) => { log(LogLevel::Error, &e); return; }
 	};
-
 	let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
-	if file_len < 4 {
+	inspect_tape(path, &mut file, file_len, options);
+}
+
+/// Inspects a cassette of `file_len` bytes read from `file`; `path` is only for display.
+fn inspect_tape(path: &str, file: &mut File, file_len: u64, options: &InspectOptions) {
+	let InspectOptions { debug, raw, check_image, strict, skip_crc, no_metadata, check_playable, compat, per_track, ref pubkey, offset, limit, pretty, .. } = *options;
+	let expected_key = match pubkey.as_deref().map(load_verifying_key).transpose() {
+		Ok(key) => key,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+
+	if SealedRegion::of_file(file_len).is_none() {
 		log(LogLevel::Error, "This file is too small to be a valid cassette.");
 		return;
 	}
 
-	// 1. Verify CRC (single pass)
-	let data_len = file_len - 4;
-	let mut hasher = Hasher::new();
-	hash_only(&mut file, &mut hasher, data_len).unwrap();
-
-	let mut crc_buf = [0u8; 4];
-	file.read_exact(&mut crc_buf).unwrap();
-	let stored_crc = u32::from_le_bytes(crc_buf);
+	if debug && !raw {
+		dump_post_iend(file);
+	}
 
-	if hasher.finalize() != stored_crc {
-		log(LogLevel::Error, "This cassette has been damaged! Checksum mismatch detected.");
-		log(LogLevel::Error, "The file may have been compressed or tampered with.");
+	// 1. Verify CRC (single pass), unless only the contents are wanted
+	if skip_crc {
+		log(LogLevel::Warning, "CRC32: (integrity not checked)");
+		if check_image {
+			check_cover(file);
+		}
+	} else if !verify_tape(file, file_len, raw, check_image) {
+		if !raw {
+			match CassetteLayout::read(file) {
+				// A damaged tape is exactly when knowing which track took the hit helps
+				Ok(layout) if per_track => check_tracks(file, &layout),
+				Ok(_) => {}
+				// Plain PNGs and broken covers fail the checksum too, but that isn't the real problem
+				Err(e) => log(LogLevel::Error, &e),
+			}
+		}
 		return;
 	}
-	log(LogLevel::Success, "Cassette integrity verified. The tape is intact.");
 
-	// 2. Find TOC position
-	let toc_pos = match find_iend(&mut file) {
-		Some(pos) => pos,
-		None => { log(LogLevel::Error, "This doesn't look like a valid cassette. No PNG structure found."); return; }
+	// 2. Find and read TOC
+	let layout = match CassetteLayout::read(file) {
+		Ok(layout) => layout,
+		Err(e) => { log(LogLevel::Error, &e); return; }
 	};
+	if strict {
+		if let Err(e) = layout.check_strict(file) {
+			log(LogLevel::Error, &e);
+			return;
+		}
+		log(LogLevel::Success, "Strict checks passed. The TOC accounts for every byte.");
+	}
+	if let Some(second) = layout.second_toc(file) {
+		log(LogLevel::Warning, &format!("This looks like a nested/doubly-recorded cassette: another TOC starts at byte {}, after the first tape's seal. It was probably recorded with a cassette as its cover, and players only see the older tape inside.", second));
+		log(LogLevel::Info, "To fix it, write out the plain cover with `rewind eject-cover` and record the tracks onto that.");
+	}
+	if !check_signature(file, &layout, file_len, expected_key.as_ref()) {
+		return;
+	}
+	if per_track {
+		check_tracks(file, &layout);
+	}
+	let toc_entries = &layout.header.entries;
 
-	// 3. Read TOC
-	file.seek(SeekFrom::Start(toc_pos)).unwrap();
-
-	let mut count_buf = [0u8; 4];
-	file.read_exact(&mut count_buf).unwrap();
-	let track_count = u32::from_le_bytes(count_buf);
-
-	log(LogLevel::Info, &format!("TOC: {} audio file(s)", track_count));
-
-	let mut toc_entries: Vec<TocEntry> = Vec::new();
-	for _ in 0..track_count {
-		let mut len_buf = [0u8; 4];
-		file.read_exact(&mut len_buf).unwrap();
-		let name_len = u32::from_le_bytes(len_buf) as usize;
-
-		let mut name_buf = vec![0u8; name_len];
-		file.read_exact(&mut name_buf).unwrap();
-		let name = String::from_utf8_lossy(&name_buf).to_string();
+	let image_info = match read_image_info(file) {
+		Ok(info) => {
+			log(LogLevel::Info, &format!("Cover: {}.", info.describe()));
+			Some(info)
+		}
+		Err(e) => { log(LogLevel::Warning, &format!("Cannot read the cover size: {}", e)); None }
+	};
 
-		let mut size_buf = [0u8; 8];
-		file.read_exact(&mut size_buf).unwrap();
-		let size = u64::from_le_bytes(size_buf);
+	if debug {
+		log(LogLevel::Info, &format!("Format version: v{} (TOC at byte {}, audio at byte {})",
+			layout.header.version, layout.toc_start, layout.audio_start));
+		if let Some(align) = layout.header.align {
+			log(LogLevel::Info, &format!("Tracks aligned to {} bytes: {}", align,
+				layout.track_offsets().iter().map(|offset| offset.to_string()).collect::<Vec<_>>().join(", ")));
+		}
+	}
 
-		toc_entries.push(TocEntry { name, size });
+	if let Some(album) = layout.header.album.as_ref().filter(|album| !album.is_empty()) {
+		log(LogLevel::Info, &format!("Album: {}", album));
+	}
+	log(LogLevel::Info, &format!("TOC: {} audio file(s)", toc_entries.len()));
+	if toc_entries.is_empty() {
+		log(LogLevel::Warning, BLANK_CASSETTE);
+		return;
 	}
 
-	// 4. Read metadata for each track
-	let mut track_offset = file.stream_position().unwrap();
+	// Only the tracks in the requested page are probed and listed
+	let page = match track_page(toc_entries.len(), offset, limit) {
+		Ok(page) => page,
+		Err(e) => {
+			log(LogLevel::Warning, &e);
+			if compat {
+				report_compat(file_len, layout.toc_start, image_info.as_ref());
+			}
+			return;
+		}
+	};
+	let first = page.start;
+	if page.len() < toc_entries.len() {
+		log(LogLevel::Info, &format!("Showing tracks {}-{} of {}", first + 1, page.end, toc_entries.len()));
+	}
 
-	for (i, entry) in toc_entries.iter().enumerate() {
-		file.seek(SeekFrom::Start(track_offset)).unwrap();
-		
-		// Read the audio chunk into memory for probing
-		let mut audio_data = vec![0u8; entry.size as usize];
-		file.read_exact(&mut audio_data).unwrap();
-		
-		let meta = match Probe::new(std::io::Cursor::new(&audio_data)).guess_file_type() {
+	// 3. Read metadata for each track
+	let has_sides = layout.header.has_sides();
+	let mut rows = Vec::with_capacity(toc_entries.len());
+	let mut chained = Vec::new();
+	for (i, (entry, track_offset)) in page.clone().zip(toc_entries[page.clone()].iter().zip(layout.track_offsets().into_iter().skip(first))) {
+		if no_metadata {
+			rows.push((None, String::new()));
+			continue;
+		}
+		if entry.size == 0 {
+			rows.push((None, "(empty track)".to_string()));
+			continue;
+		}
+		// Probe the track in place; only the parts Lofty needs are read
+		let track = match Region::new(&mut *file, track_offset, entry.size) {
+			Ok(track) => track,
+			Err(e) => { rows.push((None, format!("(Error reading track: {})", e))); continue; }
+		};
+		let (mut duration, meta) = match Probe::new(BufReader::new(track)).guess_file_type() {
 			Ok(probe) => match probe.read() {
 				Ok(tagged) => {
 					let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
 					let artist = tag.and_then(|t| t.artist()).map(|s| s.to_string()).unwrap_or("-".into());
 					let title = tag.and_then(|t| t.title()).map(|s| s.to_string()).unwrap_or("-".into());
-					let duration = format_duration(tagged.properties().duration().as_secs());
-					format!("🎵 {} - {} [{}]", artist, title, duration)
+					(Some(tagged.properties().duration().as_secs()), format!("{} - {}", artist, title))
 				},
-				Err(e) => format!("(Error reading tags: {})", e)
+				Err(e) => (None, format!("(Error reading tags: {})", e))
 			},
-			Err(e) => format!("(Error probing file: {})", e)
+			Err(e) => (None, format!("(Error probing file: {})", e))
 		};
+		// Tag readers only see the first stream of a chained Ogg; count every link instead
+		if let Some(chain) = Region::new(&mut *file, track_offset, entry.size).ok()
+			.and_then(|track| read_chain(&mut BufReader::new(track)))
+			.filter(|chain| chain.links > 1)
+		{
+			duration = chain.duration.map(|d| d.as_secs()).or(duration);
+			chained.push((i + 1, chain.links));
+		}
+		rows.push((duration, meta));
+	}
+
+	// Tape usage needs every track's length, so a page or --no-metadata leaves it unknown
+	let used_secs = (page.len() == toc_entries.len() && !no_metadata).then(|| {
+		toc_entries.iter().zip(&rows)
+			.map(|(entry, (duration, _))| if entry.size == 0 { Some(0) } else { *duration })
+			.sum::<Option<u64>>()
+	}).flatten();
+
+	// 4. Print them as a table, or on the label of a drawn cassette
+	if pretty {
+		print_cassette(path, &layout.header, page.clone(), &rows);
+	} else {
+		println!();
+		if no_metadata {
+			println!("  {}", format!("{:>3}  {:<TOC_NAME_WIDTH$}  {:>SIZE_WIDTH$}", "#", "Name", "Size").bold());
+		} else {
+			println!("  {}", format!("{:>3}  {:<NAME_WIDTH$}  {:>SIZE_WIDTH$}  {:>6}  {}", "#", "Name", "Size", "Time", "Artist - Title").bold());
+		}
+		println!("  {}", "─".repeat(TABLE_WIDTH).dimmed());
+		for (i, (entry, (duration, meta))) in page.clone().zip(toc_entries[page.clone()].iter().zip(rows)) {
+			if has_sides && (i == first || toc_entries[i - 1].side != entry.side) {
+				println!("  {}", format!("── {} ──", entry.side).cyan());
+			}
+			let duration = duration.map(format_duration).unwrap_or_else(|| "-".into());
+			// Exact byte counts when debugging, readable units otherwise
+			let size = if debug { entry.size.to_string() } else { human_size(entry.size) };
+			if no_metadata {
+				println!("  {:>3}  {:<TOC_NAME_WIDTH$}  {:>SIZE_WIDTH$}", entry.display_number(i).to_string().dimmed(), truncate(&entry.name, TOC_NAME_WIDTH), size);
+				continue;
+			}
+			println!("  {:>3}  {:<NAME_WIDTH$}  {:>SIZE_WIDTH$}  {:>6}  {}",
+				entry.display_number(i).to_string().dimmed(),
+				truncate(&entry.name, NAME_WIDTH),
+				size,
+				duration,
+				truncate(&meta, META_WIDTH).green());
+		}
+	}
+	println!();
+
+	for (track, links) in chained {
+		log(LogLevel::Warning, &format!("Track {} is a chained Ogg file ({} streams joined end to end). Its time covers all of them, but players may stop after the first.", track, links));
+	}
+
+	match (layout.header.tape_minutes, used_secs) {
+		(Some(minutes), Some(used)) => report_tape(minutes, Duration::from_secs(used)),
+		(Some(minutes), None) => log(LogLevel::Info, &format!("Tape: {} ({} long).",
+			layout.header.tape_label().unwrap_or_default(), format_duration(u64::from(minutes) * 60))),
+		(None, _) => {}
+	}
+
+	if check_playable {
+		let offsets = layout.track_offsets();
+		for (i, entry) in page.clone().zip(&toc_entries[page.clone()]) {
+			match try_decode(&*file, offsets[i], entry) {
+				Ok(()) => log(LogLevel::Success, &format!("Track {}: playable", i + 1)),
+				Err(e) => log(LogLevel::Error, &format!("Track {}: {}", i + 1, e)),
+			}
+		}
+	}
+
+	if compat {
+		report_compat(file_len, layout.toc_start, image_info.as_ref());
+	}
+
+	if debug {
+		for (i, entry) in page.clone().zip(&toc_entries[page]).filter(|(_, e)| !e.seek_index.is_empty()) {
+			let last = entry.seek_index.last().map(|p| p.millis / 1000).unwrap_or(0);
+			log(LogLevel::Info, &format!("Debug: track {} has a seek index of {} point(s) up to {}",
+				i + 1, entry.seek_index.len(), format_duration(last)));
+		}
+	}
+}
+
+/// The tracks `--offset` and `--limit` select out of `track_count`, or why there are none.
+fn track_page(track_count: usize, offset: usize, limit: Option<usize>) -> Result<Range<usize>, String> {
+	if offset >= track_count {
+		return Err(format!("--offset {} is past the last track. This cassette has {} track(s).", offset, track_count));
+	}
+	let end = limit.map_or(track_count, |limit| offset.saturating_add(limit).min(track_count));
+	if end == offset {
+		return Err(format!("--limit 0 leaves no tracks to show. This cassette has {} track(s).", track_count));
+	}
+	Ok(offset..end)
+}
+
+/// Draws a cassette with the listed tracks written on its label (`--pretty`), in the
+/// style of the TUI's player. Tracks are named without their extension, as on a real label.
+fn print_cassette(path: &str, header: &CassetteHeader, page: Range<usize>, rows: &[(Option<u64>, String)]) {
+	let label_row = |text: String| println!("  │ │ {} │ │", text);
+	let shell_row = |text: String| println!("  │{:^SHELL_WIDTH$}│", text);
+	let label_edge = "─".repeat(LABEL_WIDTH + 2);
+
+	println!("  ╭{}╮", "─".repeat(SHELL_WIDTH));
+	println!("  │ ╭{}╮ │", label_edge);
+	label_row(title_label(Path::new(path), header.entries.len()).bold().to_string());
+	let album = header.album.as_ref().filter(|album| !album.is_empty()).map(|album| album.to_string()).unwrap_or_default();
+	let tape = header.tape_label().unwrap_or_default();
+	if !album.is_empty() || !tape.is_empty() {
+		let album_width = LABEL_WIDTH - 1 - tape.chars().count();
+		label_row(format!("{} {}", fit(&album, album_width).cyan(), tape.yellow()));
+	}
+	println!("  │ ├{}┤ │", label_edge);
+
+	let has_sides = header.has_sides();
+	for (i, (entry, (duration, _))) in page.clone().zip(header.entries[page.clone()].iter().zip(rows)) {
+		if has_sides && (i == page.start || header.entries[i - 1].side != entry.side) {
+			label_row(format!("{:^LABEL_WIDTH$}", format!("── {} ──", entry.side)).cyan().to_string());
+		}
+		let name = Path::new(&entry.name).file_stem().map_or_else(|| entry.name.clone(), |stem| stem.to_string_lossy().to_string());
+		let time = duration.map(format_duration).unwrap_or_default();
+		label_row(format!("{}  {}  {:>6}", format!("{:>3}", entry.display_number(i)).dimmed(), fit(&name, LABEL_NAME_WIDTH), time));
+	}
+	println!("  │ ╰{}╯ │", label_edge);
+
+	// Reels either side of the tape window, as in the TUI (standing still)
+	let (hub_top, hub_bottom) = REEL_FRAMES[0];
+	let window = "═".repeat(16);
+	shell_row(format!("╭─────╮{}╭─────╮", " ".repeat(20)));
+	shell_row(format!("│ {} │ ╒{}╕ │ {} │", hub_top, window, hub_top));
+	shell_row(format!("│ {} │ ╘{}╛ │ {} │", hub_bottom, window, hub_bottom));
+	shell_row(format!("╰─────╯{}╰─────╯", " ".repeat(20)));
+	shell_row(format!("╱{}╲", " ".repeat(SHELL_WIDTH - 12)));
+	println!("  ╰{}╯", "─".repeat(SHELL_WIDTH));
+}
+
+/// Advisory `--compat` report: where a file of this size and shape is likely to be refused,
+/// and a reminder that any re-encode keeps the cover but drops the tape.
+fn report_compat(file_len: u64, image_size: u64, image_info: Option<&ImageInfo>) {
+	let payload = file_len.saturating_sub(image_size);
+	log(LogLevel::Info, &format!("Compatibility: {} in total, {} of cover image and {} hidden after it.",
+		human_size(file_len), human_size(image_size), human_size(payload)));
+
+	let exceeded: Vec<String> = UPLOAD_LIMITS.iter()
+		.filter(|(_, limit)| file_len > *limit)
+		.map(|(kind, limit)| format!("{} (~{})", kind, human_size(*limit)))
+		.collect();
+	if exceeded.is_empty() {
+		log(LogLevel::Success, "Size: within the usual upload limits.");
+	} else {
+		log(LogLevel::Warning, &format!("Size: larger than typical limits for {}. Expect it to be refused or recompressed there.", exceeded.join(", ")));
+	}
+
+	if payload >= image_size.saturating_mul(FRAGILE_SIZE_RATIO) {
+		log(LogLevel::Warning, &format!("Trailing data: {}× the size of the image. Viewers and upload filters that check for data after the image end are likely to flag it.",
+			payload / image_size.max(1)));
+	} else {
+		log(LogLevel::Success, "Trailing data: small next to the image, so it's less likely to be flagged.");
+	}
+
+	if let Some(info) = image_info.filter(|info| info.width.max(info.height) > RESIZE_DIMENSION) {
+		log(LogLevel::Warning, &format!("Re-encode: a {}×{} cover is likely to be resized on upload, which strips the tape.", info.width, info.height));
+	}
+	log(LogLevel::Info, "Note: Any re-encode (resizing, format conversion, metadata stripping) keeps the cover but drops everything after it. Share cassettes as files or archives to keep the audio.");
+}
+
+/// Opens a decoder on the track the way the players do and decodes its first samples.
+/// Only the start of the track is read.
+fn try_decode(file: &File, offset: u64, entry: &TocEntry) -> Result<(), PlaybackError> {
+	let size = entry.size;
+	if size == 0 {
+		return Err(PlaybackError::UnsupportedCodec("empty track".to_string()));
+	}
+	let track = file.try_clone().and_then(|file| Region::new(file, offset, size)).map_err(|e| PlaybackError::FileRead(e.to_string()))?;
+	let mut decoder = decode_with_hint(BufReader::new(track), size, format_hint(entry).as_deref())?;
+	match decoder.next() {
+		Some(_) => Ok(()),
+		None => Err(PlaybackError::UnsupportedCodec("no audio decoded".to_string())),
+	}
+}
+
+/// Checks the creator signature (if any) and logs the result. With `expected`, the cassette
+/// must be signed by that key. Returns whether inspection should go on to the track list.
+fn check_signature<R: Read + Seek>(file: &mut R, layout: &CassetteLayout, file_len: u64, expected: Option<&VerifyingKey>) -> bool {
+	let Some(ref signature) = layout.header.signature else {
+		if let Some(key) = expected {
+			log(LogLevel::Error, &format!("This cassette is unsigned, but a signature by {} was expected.", fingerprint(key.as_bytes())));
+			return false;
+		}
+		log(LogLevel::Info, "Signature: none (unsigned cassette).");
+		return true;
+	};
+
+	let mut digest = start_digest(&layout.header.unsigned_bytes());
+	let audio_len = SealedRegion::of_file(file_len).map_or(0, |region| region.trailer_start.saturating_sub(layout.audio_start));
+	let mut line = Progress::new("Checking signature");
+	let hashed = file.seek(SeekFrom::Start(layout.audio_start))
+		.and_then(|_| std::io::copy(&mut ProgressReader::new(file.take(audio_len), audio_len, &mut |done, total| line.update(done, total)), &mut digest));
+	if let Err(e) = hashed {
+		log(LogLevel::Error, &format!("Failed to read the cassette: {}", e));
+		return false;
+	}
+
+	match verify(signature, digest) {
+		SignatureStatus::Valid { fingerprint: signer } => {
+			if let Some(key) = expected
+				&& key.as_bytes() != &signature.public_key
+			{
+				log(LogLevel::Error, &format!("Signed by {}, not by the expected key {}.", signer, fingerprint(key.as_bytes())));
+				return false;
+			}
+			log(LogLevel::Success, &format!("Signed by {}. Unmodified since it was signed.", signer));
+			true
+		}
+		SignatureStatus::Invalid => {
+			log(LogLevel::Error, "Signature INVALID! The tracks or the TOC were changed after signing.");
+			false
+		}
+	}
+}
+
+/// Verifies every track against its own CRC32 from the TOC and logs which ones are damaged.
+fn check_tracks<R: Read + Seek>(file: &mut R, layout: &CassetteLayout) {
+	let entries = &layout.header.entries;
+	if entries.iter().any(|entry| entry.crc.is_none()) {
+		log(LogLevel::Warning, &format!("This cassette (format v{}) has no per-track checksums; only the whole-file CRC32 can be checked. Re-record it to add them.", layout.header.version));
+		return;
+	}
+
+	let mut damaged = 0;
+	for (i, (entry, offset)) in entries.iter().zip(layout.track_offsets()).enumerate() {
+		let stored = entry.crc.unwrap_or_default();
+		let mut hasher = Hasher::new();
+		let hashed = Region::new(&mut *file, offset, entry.size)
+			.and_then(|mut track| hash_with_progress(&mut track, &mut hasher, entry.size, &format!("Checking track {}", i + 1)));
+		match hashed {
+			Ok(n) if n == entry.size && hasher.clone().finalize() == stored => {}
+			Ok(n) if n < entry.size => {
+				log(LogLevel::Error, &format!("Track {} ({}) is cut short: {} of {} bytes present.", i + 1, entry.name, n, entry.size));
+				damaged += 1;
+			}
+			Ok(_) => {
+				log(LogLevel::Error, &format!("Track {} ({}) is damaged (stored CRC32 {:08X}, computed {:08X}).", i + 1, entry.name, stored, hasher.finalize()));
+				damaged += 1;
+			}
+			Err(e) => {
+				log(LogLevel::Error, &format!("Track {} ({}) cannot be read: {}", i + 1, entry.name, e));
+				damaged += 1;
+			}
+		}
+	}
+
+	if damaged == 0 {
+		log(LogLevel::Success, &format!("All {} track(s) match their own CRC32.", entries.len()));
+	} else {
+		log(LogLevel::Warning, &format!("{} of {} track(s) damaged. The other tracks are intact.", damaged, entries.len()));
+	}
+}
+
+/// Checks the cassette's CRC32 seal and logs the result (or prints just the CRC with `raw`).
+/// Returns whether inspection should go on to the track list.
+fn verify_tape<R: Read + Seek>(file: &mut R, file_len: u64, raw: bool, check_image: bool) -> bool {
+	let mut line = Progress::new("Verifying");
+	let seal = match Cassette::verify(file, &mut |done, total| line.update(done, total)) {
+		Ok(seal) => seal,
+		Err(e) => { log(LogLevel::Error, &e); return false; }
+	};
+	drop(line);
+
+	if raw {
+		println!("{:08X}", seal.computed);
+		return false;
+	}
+
+	log(LogLevel::Info, &format!("Stored CRC32: {:08X} | Computed CRC32: {:08X}", seal.stored, seal.computed));
+	if check_image {
+		check_cover(file);
+	}
+	if !seal.is_intact() {
+		if has_intact_cover_without_tape(file, file_len) {
+			log(LogLevel::Error, "This cassette was re-compressed and the music was stripped.");
+			log(LogLevel::Error, "The cover image is intact, but nothing Rewind wrote after it survived. Share the original file instead.");
+		} else {
+			log(LogLevel::Error, "This cassette has been damaged! Checksum mismatch detected.");
+			log(LogLevel::Error, "The file may have been compressed or tampered with.");
+		}
+		return false;
+	}
+	log(LogLevel::Success, "Cassette integrity verified. The tape is intact.");
+	true
+}
+
+/// Checks the seal of one cassette, or of every cassette in a directory (and its
+/// subdirectories with `recursive`), then prints how many passed and which failed.
+/// Returns false when any failed or none were found.
+pub fn verify_collection(path: &str, recursive: bool) -> bool {
+	let root = Path::new(path);
+	let cassettes = if root.is_dir() {
+		let mut found = Vec::new();
+		if let Err(e) = find_cassettes(root, recursive, &mut found) {
+			log(LogLevel::Error, &e);
+			return false;
+		}
+		found.sort();
+		found
+	} else {
+		vec![root.to_path_buf()]
+	};
+	if cassettes.is_empty() {
+		log(LogLevel::Error, &format!("No cassettes (*.png, *.apng) found in '{}'.", path));
+		return false;
+	}
+
+	let mut failures = Vec::new();
+	for cassette in &cassettes {
+		let name = cassette.display().to_string();
+		match check_seal(&name) {
+			Ok(()) => log(LogLevel::Info, &format!("{} {}", "intact ".green(), name)),
+			Err(reason) => {
+				log(LogLevel::Info, &format!("{} {}: {}", "damaged".red(), name, reason));
+				failures.push((name, reason));
+			}
+		}
+	}
+
+	let passed = cassettes.len() - failures.len();
+	if failures.is_empty() {
+		log(LogLevel::Success, &format!("{} passed, 0 failed.", passed));
+		return true;
+	}
+	for (name, reason) in &failures {
+		log(LogLevel::Error, &format!("Failed: {} ({})", name, reason));
+	}
+	log(LogLevel::Error, &format!("{} passed, {} failed.", passed, failures.len()));
+	false
+}
+
+/// Collects the `*.png`/`*.apng` files in `dir`, descending into subdirectories with `recursive`.
+fn find_cassettes(dir: &Path, recursive: bool, found: &mut Vec<std::path::PathBuf>) -> Result<(), String> {
+	let read = std::fs::read_dir(dir).map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+	for path in read.filter_map(|entry| entry.ok().map(|e| e.path())) {
+		if path.is_dir() {
+			if recursive {
+				find_cassettes(&path, recursive, found)?;
+			}
+		} else if path.extension().is_some_and(|ext| COVER_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known))) {
+			found.push(path);
+		}
+	}
+	Ok(())
+}
+
+/// Verifies one cassette's seal and TOC without logging, for `verify_collection`.
+fn check_seal(path: &str) -> Result<(), String> {
+	let mut file = open_file(path)?;
+	let file_len = file.metadata().map(|m| m.len()).unwrap_or(0);
+	let mut line = Progress::new("Verifying");
+	let seal = Cassette::verify(&mut file, &mut |done, total| line.update(done, total))?;
+	drop(line);
+	if !seal.is_intact() {
+		if has_intact_cover_without_tape(&mut file, file_len) {
+			return Err("no tape after the cover (re-compressed, or not a cassette)".to_string());
+		}
+		return Err(format!("checksum mismatch (stored {:08X}, computed {:08X})", seal.stored, seal.computed));
+	}
+	CassetteLayout::read(&mut file).map(|_| ())
+}
+
+/// Verifies the CRC32 of every chunk in the cover image and logs the result.
+/// This only concerns the artwork; the tape has its own seal.
+fn check_cover<R: Read + Seek>(file: &mut R) {
+	let chunks = match read_chunks(file) {
+		Ok(chunks) => chunks,
+		Err(e) => { log(LogLevel::Error, &format!("Cover image: cannot walk its chunks: {}", e)); return; }
+	};
+
+	let mut damaged = 0;
+	for chunk in &chunks {
+		let kind = String::from_utf8_lossy(&chunk.kind);
+		match compute_chunk_crc(file, chunk) {
+			Ok(crc) if crc == chunk.crc => {}
+			Ok(crc) => {
+				log(LogLevel::Error, &format!("Cover image: {} chunk at byte {} is corrupt (stored CRC32 {:08X}, computed {:08X}).",
+					kind, chunk.offset, chunk.crc, crc));
+				damaged += 1;
+			}
+			Err(e) => {
+				log(LogLevel::Error, &format!("Cover image: cannot read the {} chunk at byte {}: {}", kind, chunk.offset, e));
+				damaged += 1;
+			}
+		}
+	}
+
+	if damaged == 0 {
+		log(LogLevel::Success, &format!("Cover image intact: all {} PNG chunk CRCs match.", chunks.len()));
+	} else {
+		log(LogLevel::Warning, &format!("The cover art is damaged ({} of {} chunks). Image viewers may show it incorrectly.", damaged, chunks.len()));
+	}
+}
+
+/// Heuristic for a failed checksum: true when the PNG itself is well-formed but no
+/// readable TOC follows it, which is what re-encoding by an image host leaves behind.
+/// A TOC that is still readable points at damage to the tape itself instead.
+fn has_intact_cover_without_tape<R: Read + Seek>(file: &mut R, file_len: u64) -> bool {
+	let Ok(toc_start) = image_end(file) else { return false };
+	if file_len.saturating_sub(toc_start) < (COUNT_LEN + CRC_LEN) as u64 {
+		return true;
+	}
+	file.seek(SeekFrom::Start(toc_start)).is_ok() && CassetteHeader::read(file).is_err()
+}
+
+/// Logs a hexdump of the first bytes after IEND (magic, version and the start of the TOC).
+/// Uses the real end of the image when its chunks can be walked, like the TOC reader does.
+fn dump_post_iend<R: Read + Seek>(file: &mut R) {
+	let Some(toc_pos) = image_end(file).ok().or_else(|| find_iend(file)) else {
+		log(LogLevel::Warning, "Debug: no IEND chunk found, nothing to dump.");
+		return;
+	};
+
+	let mut bytes = Vec::with_capacity(DEBUG_DUMP_LEN);
+	if file.seek(SeekFrom::Start(toc_pos)).is_err()
+		|| file.take(DEBUG_DUMP_LEN as u64).read_to_end(&mut bytes).is_err()
+	{
+		log(LogLevel::Warning, "Debug: could not read the bytes after IEND.");
+		return;
+	}
+
+	log(LogLevel::Info, &format!("Debug: first {} byte(s) after IEND (offset {}):", bytes.len(), toc_pos));
+	for line in hexdump(&bytes, toc_pos) {
+		log(LogLevel::Info, &format!("  {}", line));
+	}
+
+	// Rewind the cursor for the CRC pass
+	let _ = file.rewind();
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn track_page_stays_within_the_tape() {
+		assert_eq!(track_page(10, 0, None), Ok(0..10));
+		assert_eq!(track_page(10, 3, Some(4)), Ok(3..7));
+		assert_eq!(track_page(10, 8, Some(5)), Ok(8..10));
+		assert_eq!(track_page(10, 9, Some(usize::MAX)), Ok(9..10));
 
-		log(LogLevel::Info, &format!("  [{}] {} ({} bytes) | {}", i + 1, entry.name, entry.size, meta));
-		track_offset += entry.size;
+		// Nothing to list: said so instead of "Showing tracks 11-10 of 10" over an empty table
+		for (offset, limit) in [(10, None), (25, Some(3)), (0, Some(0))] {
+			let error = track_page(10, offset, limit).expect_err("an empty page");
+			assert!(error.contains("10 track(s)"), "{}", error);
+		}
+		assert!(track_page(10, 10, None).unwrap_err().contains("past the last track"));
 	}
 }
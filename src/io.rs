@@ -6,15 +6,22 @@
 // Handles file operations, data transfer with hashing, PNG structure parsing,
 // and audio format validation.
 
-use std::fs::File;
-use std::io::{Read, Write, Seek};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::io::{IsTerminal, Read, Write, Seek, SeekFrom};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use crc32fast::Hasher;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+use lofty::file::{FileType, TaggedFile};
 use lofty::probe::Probe;
 use crate::constants::{IEND_CHUNK, BUFFER_SIZE};
+use crate::logger::{is_quiet, clear_progress, set_progress_shown};
 
 /// Opens a file with a descriptive error message on failure.
-pub fn open_file(path: &str) -> Result<File, String> {
-	File::open(path).map_err(|e| format!("Cassette not found in the deck: {} ({})", path, e))
+pub fn open_file<P: AsRef<Path>>(path: P) -> Result<File, String> {
+	let path = path.as_ref();
+	File::open(path).map_err(|e| format!("Cassette not found in the deck: {} ({})", path.display(), e))
 }
 
 /// Creates a file with a descriptive error message on failure.
@@ -22,6 +29,118 @@ pub fn create_file(path: &str) -> Result<File, String> {
 	File::create(path).map_err(|e| format!("Cannot create output file '{}': {}", path, e))
 }
 
+/// An output file written under a temporary name beside it (see `temp_path_beside`) and only
+/// moved into place by `commit`, so a failed write never leaves a truncated file at `path`.
+/// Dropping it without committing deletes the temporary file.
+pub struct PendingFile {
+	path: PathBuf,
+	temp: TempFile,
+}
+
+impl PendingFile {
+	/// Creates the temporary file next to `path`.
+	pub fn create<P: AsRef<Path>>(path: P) -> Result<(Self, File), String> {
+		let path = path.as_ref().to_path_buf();
+		let (temp, file) = TempFile::beside(&path)?;
+		Ok((PendingFile { path, temp }, file))
+	}
+
+	/// Flushes `writer` to disk and moves the finished file to its final path.
+	pub fn commit(self, writer: std::io::BufWriter<File>) -> Result<(), String> {
+		let file = writer.into_inner().map_err(|e| format!("Cannot write '{}': {}", self.path.display(), e.error()))?;
+		file.sync_all().map_err(|e| format!("Cannot write '{}': {}", self.path.display(), e))?;
+		drop(file);
+		self.temp.persist(&self.path).map_err(|e| format!("Cannot move the finished file to '{}': {}", self.path.display(), e))
+	}
+}
+
+/// A fresh path in the system's temporary directory (`TMPDIR`, or `%TEMP%` on Windows)
+/// for intermediate files, named after `base` and unique within this process.
+pub fn temp_path(base: &str) -> PathBuf {
+	static NEXT: AtomicU64 = AtomicU64::new(0);
+	let n = NEXT.fetch_add(1, Ordering::Relaxed);
+	std::env::temp_dir().join(format!("rewind-{}-{}-{}", std::process::id(), n, sanitize_name(base)))
+}
+
+/// The name an output file is written under until it's complete: `<path>.tmp`, in the same
+/// directory, so moving it into place is a rename rather than a copy across filesystems.
+pub fn temp_path_beside(path: &Path) -> PathBuf {
+	let mut name = path.as_os_str().to_owned();
+	name.push(".tmp");
+	PathBuf::from(name)
+}
+
+/// An intermediate file (see `temp_path` and `temp_path_beside`). Dropping it deletes the
+/// file, so an early return on error doesn't leave it behind. Close the file first: Windows
+/// can't delete a file that is still open.
+pub struct TempFile {
+	/// Empty once the file has been moved into place by `persist`.
+	path: PathBuf,
+}
+
+impl TempFile {
+	/// Creates an empty temporary file named after `base`, open for reading and writing.
+	pub fn create(base: &str) -> Result<(Self, File), String> {
+		loop {
+			let path = temp_path(base);
+			match File::options().read(true).write(true).create_new(true).open(&path) {
+				Ok(file) => return Ok((TempFile { path }, file)),
+				// Left over from an earlier run that had the same process ID
+				Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+				Err(e) => return Err(format!("Cannot create a temporary file in '{}': {}", std::env::temp_dir().display(), e)),
+			}
+		}
+	}
+
+	/// Creates a temporary file named after `base` holding `bytes`, rewound for reading.
+	pub fn with_contents(base: &str, bytes: &[u8]) -> Result<(Self, File), String> {
+		let (temp, mut file) = Self::create(base)?;
+		file.write_all(bytes).and_then(|_| file.rewind())
+			.map_err(|e| format!("Cannot write a temporary file in '{}': {}", std::env::temp_dir().display(), e))?;
+		Ok((temp, file))
+	}
+
+	/// Creates `<path>.tmp` next to `path`, replacing one left over from an interrupted run.
+	pub fn beside(path: &Path) -> Result<(Self, File), String> {
+		let temp = temp_path_beside(path);
+		let file = File::options().read(true).write(true).create(true).truncate(true).open(&temp)
+			.map_err(|e| format!("Cannot create output file '{}': {}", temp.display(), e))?;
+		Ok((TempFile { path: temp }, file))
+	}
+
+	#[allow(dead_code)]
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+
+	/// Moves the file to `dest`, after which dropping this no longer deletes anything.
+	pub fn persist(mut self, dest: &Path) -> std::io::Result<()> {
+		fs::rename(&self.path, dest)?;
+		self.path = PathBuf::new();
+		Ok(())
+	}
+}
+
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		if !self.path.as_os_str().is_empty() {
+			let _ = fs::remove_file(&self.path);
+		}
+	}
+}
+
+/// Resolves an output path against `base` (from `--output-dir`/`REWIND_OUTPUT_DIR`).
+/// Absolute paths, or any path when there is no base, are returned unchanged.
+/// The base directory is created if it doesn't exist yet.
+pub fn resolve_output(path: &str, base: Option<&str>) -> Result<String, String> {
+	let Some(base) = base.filter(|b| !b.is_empty()) else { return Ok(path.to_string()) };
+	if Path::new(path).is_absolute() {
+		return Ok(path.to_string());
+	}
+	fs::create_dir_all(base).map_err(|e| format!("Cannot create output directory '{}': {}", base, e))?;
+	Ok(Path::new(base).join(path).to_string_lossy().to_string())
+}
+
 /// Copies all bytes from reader to writer, updating the hasher. Returns bytes written.
 pub fn transfer<R: Read, W: Write>(reader: &mut R, writer: &mut W, hasher: &mut Hasher) -> std::io::Result<u64> {
 	let mut buffer = [0u8; BUFFER_SIZE];
@@ -50,42 +169,321 @@ pub fn hash_only<R: Read>(reader: &mut R, hasher: &mut Hasher, limit: u64) -> st
 	Ok(total)
 }
 
+/// Like `hash_only`, but reports `(bytes hashed, limit)` to `on_progress` as it goes.
+pub fn hash_observed<R: Read>(reader: &mut R, hasher: &mut Hasher, limit: u64, on_progress: &mut dyn FnMut(u64, u64)) -> std::io::Result<u64> {
+	hash_only(&mut ProgressReader::new(reader, limit, on_progress), hasher, limit)
+}
+
+/// Like `hash_only`, but shows a progress line labelled `label` while hashing large inputs.
+pub fn hash_with_progress<R: Read>(reader: &mut R, hasher: &mut Hasher, limit: u64, label: &str) -> std::io::Result<u64> {
+	let mut line = Progress::new(label);
+	hash_observed(reader, hasher, limit, &mut |done, total| line.update(done, total))
+}
+
+/// Reads shorter than this finish quickly enough not to need a progress line.
+const PROGRESS_MIN_BYTES: u64 = 64 * 1024 * 1024;
+/// How often the progress line is redrawn.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A `\r`-redrawn progress line on stderr for long operations: the CLI's observer
+/// for the `(done, total)` byte counts the library reports.
+/// Stays silent for small totals, with `--quiet`, or when stderr isn't a terminal,
+/// and clears itself when dropped.
+pub struct Progress {
+	label: String,
+	interactive: bool,
+	last_draw: Option<Instant>,
+}
+
+impl Progress {
+	pub fn new(label: &str) -> Self {
+		let interactive = !is_quiet() && std::io::stderr().is_terminal();
+		Progress { label: label.to_string(), interactive, last_draw: None }
+	}
+
+	/// Shows `done` of `total` bytes, redrawing the line at most every `PROGRESS_INTERVAL`.
+	pub fn update(&mut self, done: u64, total: u64) {
+		if !self.interactive || total < PROGRESS_MIN_BYTES || self.last_draw.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+			return;
+		}
+		self.last_draw = Some(Instant::now());
+		let done = done.min(total);
+		eprint!("\r{}… {:>3}% ({} of {})\x1b[K", self.label, done * 100 / total, human_size(done), human_size(total));
+		let _ = std::io::stderr().flush();
+		set_progress_shown(true);
+	}
+}
+
+impl Drop for Progress {
+	fn drop(&mut self) {
+		clear_progress();
+	}
+}
+
+/// Reader adapter that reports `(bytes read so far, total)` to an observer after every read.
+pub struct ProgressReader<'a, R> {
+	inner: R,
+	done: u64,
+	total: u64,
+	on_progress: &'a mut dyn FnMut(u64, u64),
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+	pub fn new(inner: R, total: u64, on_progress: &'a mut dyn FnMut(u64, u64)) -> Self {
+		ProgressReader { inner, done: 0, total, on_progress }
+	}
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let n = self.inner.read(buf)?;
+		self.done += n as u64;
+		(self.on_progress)(self.done, self.total);
+		Ok(n)
+	}
+}
+
+/// `size` bytes as an in-memory length, or an error if this platform can't hold that
+/// many in one buffer (over 2 GB on 32-bit targets) rather than a silently wrapped length.
+pub fn track_len(size: u64) -> Result<usize, String> {
+	usize::try_from(size).ok()
+		.filter(|&len| len <= isize::MAX as usize)
+		.ok_or_else(|| format!("Track too large for this platform ({}).", human_size(size)))
+}
+
+/// A zeroed buffer to read a `size`-byte track into (see `track_len`).
+pub fn track_buffer(size: u64) -> Result<Vec<u8>, String> {
+	Ok(vec![0u8; track_len(size)?])
+}
+
+/// A seekable window of `len` bytes of `inner` starting at `start`, so a single track
+/// can be probed or decoded in place instead of being loaded into memory.
+pub struct Region<R> {
+	inner: R,
+	start: u64,
+	len: u64,
+	pos: u64,
+}
+
+impl<R: Read + Seek> Region<R> {
+	pub fn new(mut inner: R, start: u64, len: u64) -> std::io::Result<Self> {
+		inner.seek(SeekFrom::Start(start))?;
+		Ok(Region { inner, start, len, pos: 0 })
+	}
+}
+
+impl<R: Read> Read for Region<R> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		// Saturate rather than truncate: on 32-bit targets the remainder can exceed `usize`
+		let max = buf.len().min(usize::try_from(self.len.saturating_sub(self.pos)).unwrap_or(usize::MAX));
+		let n = self.inner.read(&mut buf[..max])?;
+		self.pos += n as u64;
+		Ok(n)
+	}
+}
+
+impl<R: Seek> Seek for Region<R> {
+	fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+		let target = match pos {
+			SeekFrom::Start(offset) => Some(offset),
+			SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+			SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+		};
+		let (target, absolute) = target.and_then(|target| Some((target, self.start.checked_add(target)?)))
+			.ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek outside the track"))?;
+		self.inner.seek(SeekFrom::Start(absolute))?;
+		self.pos = target;
+		Ok(target)
+	}
+}
+
 /// Scans file for PNG IEND chunk, returns position immediately after it.
-pub fn find_iend(file: &mut File) -> Option<u64> {
-	file.rewind().ok()?;
+pub fn find_iend<R: Read + Seek>(reader: &mut R) -> Option<u64> {
+	reader.rewind().ok()?;
 	let mut buffer = [0u8; BUFFER_SIZE];
+	// Offset of buffer[0] in the file, and how many bytes at its start are left over from the last read
 	let mut file_pos = 0u64;
+	let mut kept = 0;
 
 	loop {
-		let n = file.read(&mut buffer).ok()?;
+		let n = reader.read(&mut buffer[kept..]).ok()?;
 		if n == 0 { break; }
+		let filled = kept + n;
 
-		// Scan for IEND signature (need at least 12 bytes to match)
-		if n >= IEND_CHUNK.len() {
-			for i in 0..=(n - IEND_CHUNK.len()) {
-				if buffer[i..i + IEND_CHUNK.len()] == IEND_CHUNK {
-					return Some(file_pos + i as u64 + IEND_CHUNK.len() as u64);
-				}
-			}
+		if let Some(i) = buffer[..filled].windows(IEND_CHUNK.len()).position(|window| window == IEND_CHUNK) {
+			return Some(file_pos + (i + IEND_CHUNK.len()) as u64);
 		}
-		file_pos += n as u64;
+
+		// Keep the tail so a chunk split across two reads still matches
+		kept = filled.min(IEND_CHUNK.len() - 1);
+		buffer.copy_within(filled - kept..filled, 0);
+		file_pos += (filled - kept) as u64;
 	}
 	None
 }
 
 /// Validates that a file is a supported audio format using Lofty.
-pub fn validate_audio(file: &mut File) -> Result<(), String> {
+/// Returns the probed file so callers can reuse its tags and properties.
+pub fn validate_audio(file: &mut File) -> Result<TaggedFile, String> {
 	file.rewind().map_err(|e| e.to_string())?;
-	Probe::new(&mut *file)
+	let tagged = Probe::new(&mut *file)
 		.guess_file_type()
 		.map_err(|_| "This doesn't sound like music. Unknown format.".to_string())?
 		.read()
 		.map_err(|_| "This audio file is damaged or corrupted.".to_string())?;
 	file.rewind().map_err(|e| e.to_string())?;
-	Ok(())
+	Ok(tagged)
+}
+
+/// True for the formats `record` accepts by default: the ones every player decodes
+/// (FLAC, MP3, MP4/AAC, Ogg Vorbis and WAV). AIFF needs `--allow-format`; the rest
+/// (Opus, WavPack, ...) have no decoder and are refused.
+pub fn is_default_format(file_type: FileType) -> bool {
+	matches!(file_type, FileType::Flac | FileType::Mpeg | FileType::Mp4 | FileType::Aac | FileType::Vorbis | FileType::Wav)
+}
+
+/// True for formats that discard audio data when encoding (MP3, Vorbis, Opus, AAC, ...).
+/// MP4 is left out: it may hold lossless ALAC as well as AAC.
+pub fn is_lossy(file_type: FileType) -> bool {
+	matches!(file_type, FileType::Mpeg | FileType::Vorbis | FileType::Opus | FileType::Aac | FileType::Speex | FileType::Mpc)
+}
+
+/// Parses a timestamp written as seconds ("90", "12.5") or minutes and seconds ("1:30", "1:30.5").
+pub fn parse_timestamp(text: &str) -> Option<std::time::Duration> {
+	let text = text.trim();
+	let secs = match text.split_once(':') {
+		Some((mins, secs)) => {
+			let mins: u64 = mins.parse().ok()?;
+			let secs: f64 = secs.parse().ok()?;
+			if !(0.0..60.0).contains(&secs) { return None; }
+			mins as f64 * 60.0 + secs
+		}
+		None => text.parse().ok()?,
+	};
+	if !secs.is_finite() || secs < 0.0 { return None; }
+	Some(std::time::Duration::from_secs_f64(secs))
+}
+
+/// Parses a position within a track of `total_secs` seconds, written as a percentage ("50%")
+/// or as a timestamp (see `parse_timestamp`). Percentages need a known length.
+pub fn parse_position(text: &str, total_secs: f64) -> Option<std::time::Duration> {
+	let Some(percent) = text.trim().strip_suffix('%') else { return parse_timestamp(text) };
+	let percent: f64 = percent.trim().parse().ok()?;
+	if !(0.0..=100.0).contains(&percent) || !total_secs.is_finite() || total_secs <= 0.0 { return None; }
+	Some(std::time::Duration::from_secs_f64(total_secs * percent / 100.0))
 }
 
 /// Formats duration in seconds to "M:SS" string.
 pub fn format_duration(secs: u64) -> String {
 	format!("{}:{:02}", secs / 60, secs % 60)
 }
+
+/// Formats a byte count with binary units, e.g. "1023 B", "1.0 KiB", "12.3 MiB".
+pub fn human_size(bytes: u64) -> String {
+	const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+	if bytes < 1024 {
+		return format!("{} B", bytes);
+	}
+	let mut value = bytes as f64 / 1024.0;
+	let mut unit = 0;
+	// Move up a unit before rounding would print "1024.0"
+	while value >= 1023.95 && unit < UNITS.len() - 1 {
+		value /= 1024.0;
+		unit += 1;
+	}
+	format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Longest name taken from a tag, in characters.
+const MAX_TAG_NAME_LEN: usize = 120;
+
+/// Makes a tag value safe to use as a track or file name: path separators, reserved and
+/// control characters become `_`, whitespace is collapsed, and leading/trailing dots and
+/// spaces are dropped.
+pub fn sanitize_name(text: &str) -> String {
+	let cleaned: String = text.chars()
+		.map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+		.collect();
+	let collapsed = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+	collapsed.trim_matches(|c: char| c == '.' || c == ' ').chars().take(MAX_TAG_NAME_LEN).collect::<String>().trim_end().to_string()
+}
+
+/// Columns `text` takes up in a terminal: CJK characters and most emoji are two wide.
+pub fn display_width(text: &str) -> usize {
+	UnicodeWidthStr::width(text)
+}
+
+/// Shortens `text` to at most `width` terminal columns, ending in "…" when cut.
+pub fn truncate(text: &str, width: usize) -> String {
+	if display_width(text) <= width {
+		return text.to_string();
+	}
+	let budget = width.saturating_sub(1);
+	let mut used = 0;
+	let mut cut = String::new();
+	for c in text.chars() {
+		used += c.width().unwrap_or(0);
+		if used > budget {
+			break;
+		}
+		cut.push(c);
+	}
+	cut.push('…');
+	cut
+}
+
+/// `text` cut short and padded with spaces to exactly `width` columns, for fixed layouts
+/// (`format!`'s padding counts characters, so double-width ones would overflow).
+pub fn fit(text: &str, width: usize) -> String {
+	let cut = truncate(text, width);
+	let pad = width.saturating_sub(display_width(&cut));
+	cut + &" ".repeat(pad)
+}
+
+/// Like `fit`, but centered.
+pub fn fit_center(text: &str, width: usize) -> String {
+	let cut = truncate(text, width);
+	let pad = width.saturating_sub(display_width(&cut));
+	format!("{}{}{}", " ".repeat(pad / 2), cut, " ".repeat(pad - pad / 2))
+}
+
+/// Formats bytes as classic hexdump lines: offset, hex pairs, and printable ASCII.
+pub fn hexdump(bytes: &[u8], base_offset: u64) -> Vec<String> {
+	bytes.chunks(16).enumerate().map(|(i, chunk)| {
+		let hex: Vec<String> = chunk.iter().map(|b| format!("{:02X}", b)).collect();
+		let ascii: String = chunk.iter()
+			.map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+			.collect();
+		format!("{:08X}  {:<47}  |{}|", base_offset + (i * 16) as u64, hex.join(" "), ascii)
+	}).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sizes_switch_units_at_the_boundaries() {
+		assert_eq!(human_size(0), "0 B");
+		assert_eq!(human_size(1023), "1023 B");
+		assert_eq!(human_size(1024), "1.0 KiB");
+		assert_eq!(human_size(1536), "1.5 KiB");
+		// Just under 1 MiB would round to "1024.0 KiB", so it moves up a unit instead
+		assert_eq!(human_size(1024 * 1024 - 1), "1.0 MiB");
+		assert_eq!(human_size(1024 * 1023 + 972), "1023.9 KiB");
+		assert_eq!(human_size(1024 * 1024), "1.0 MiB");
+		assert_eq!(human_size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+		// TiB is the largest unit, so bigger sizes just grow the number
+		assert_eq!(human_size(2048 * 1024u64.pow(4)), "2048.0 TiB");
+	}
+
+	#[test]
+	fn region_refuses_seeks_outside_the_track() {
+		let mut region = Region::new(std::io::Cursor::new(vec![0u8; 32]), 10, 8).unwrap();
+		assert_eq!(region.seek(SeekFrom::End(-2)).unwrap(), 6);
+		for pos in [SeekFrom::Current(-7), SeekFrom::End(-9), SeekFrom::Start(u64::MAX - 5)] {
+			assert_eq!(region.seek(pos).unwrap_err().kind(), std::io::ErrorKind::InvalidInput, "{:?}", pos);
+		}
+		assert_eq!(region.stream_position().unwrap(), 6, "a refused seek keeps the position");
+	}
+}
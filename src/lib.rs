@@ -0,0 +1,16 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// LIBRARY
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// The cassette parser as a library, so the fuzz targets in `fuzz/` can feed it
+// arbitrary bytes and frontends can open and verify cassettes. Long operations
+// report `(bytes done, total)` to a caller-supplied `FnMut` instead of drawing
+// the CLI's progress line. The `rewind` binary compiles these modules on its
+// own; this is not a stable API.
+
+pub mod constants;
+pub mod format;
+pub mod io;
+pub mod logger;
+pub mod lyrics;
+pub mod png;
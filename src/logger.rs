@@ -4,10 +4,140 @@
 //
 // Provides colored, timestamped console logging with different severity levels.
 // Used throughout the application to provide clear user feedback during operations.
+// `--quiet` hides Info messages; successes, warnings and errors are always shown.
+// Colors are dropped when `NO_COLOR` is set or stdout isn't a terminal.
+// A progress line on stderr is erased before a message is printed; its next
+// update draws it again below.
+//
+// Builds with the `trace` feature can also emit structured diagnostics for bug
+// reports: spans around record, inspect and playback with offsets, sizes and
+// timings as fields. They go to stderr, only with `--trace` or `RUST_LOG`, and
+// never change the messages above. Without the feature `trace_span!` and
+// `trace_event!` compile to nothing.
 
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
 use colored::*;
 use chrono::Local;
 
+/// Set once at startup from `--quiet`.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Hides Info messages from now on.
+pub fn set_quiet(quiet: bool) {
+	QUIET.store(quiet, Ordering::Relaxed);
+}
+
+/// True when Info messages are hidden.
+pub fn is_quiet() -> bool {
+	QUIET.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+thread_local! {
+	/// Messages logged on this thread, so tests can check what a command reported.
+	static LOGGED: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Takes the messages logged on this thread so far.
+#[cfg(test)]
+pub fn take_logged() -> Vec<String> {
+	LOGGED.with(|logged| logged.take())
+}
+
+/// Set while a progress line is drawn on stderr.
+static PROGRESS_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Records whether a progress line is drawn on stderr right now.
+pub fn set_progress_shown(shown: bool) {
+	PROGRESS_SHOWN.store(shown, Ordering::Relaxed);
+}
+
+/// Erases the progress line, if one is drawn.
+pub fn clear_progress() {
+	if PROGRESS_SHOWN.swap(false, Ordering::Relaxed) {
+		eprint!("\r\x1b[K");
+	}
+}
+
+/// Turns styling off when `NO_COLOR` is set (to anything non-empty) or stdout is
+/// piped or redirected. `CLICOLOR_FORCE` keeps colors on regardless. Call once at startup.
+pub fn init_colors() {
+	let force = std::env::var_os("CLICOLOR_FORCE").is_some_and(|v| !v.is_empty() && v != "0");
+	let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+	if force {
+		colored::control::set_override(true);
+	} else if no_color || !std::io::stdout().is_terminal() {
+		colored::control::set_override(false);
+	}
+}
+
+/// Starts printing diagnostics to stderr when `--trace` is given (everything from Rewind) or
+/// `RUST_LOG` is set (e.g. `RUST_LOG=rewind::format=trace,rewind=debug`). Call once at startup.
+#[cfg(feature = "trace")]
+pub fn init_trace(enabled: bool) {
+	use tracing_subscriber::filter::{LevelFilter, Targets};
+	use tracing_subscriber::prelude::*;
+
+	let filter = match std::env::var("RUST_LOG") {
+		Ok(directives) if !directives.is_empty() => match directives.parse::<Targets>() {
+			Ok(filter) => filter,
+			Err(e) => {
+				log(LogLevel::Warning, &format!("Ignoring RUST_LOG ({}).", e));
+				return;
+			}
+		},
+		_ if enabled => Targets::new().with_target("rewind", LevelFilter::TRACE),
+		_ => return,
+	};
+	// Closing spans report how long they took (`time.busy`)
+	let layer = tracing_subscriber::fmt::layer()
+		.with_writer(std::io::stderr)
+		.with_ansi(false)
+		.with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE);
+	let _ = tracing_subscriber::registry().with(layer).with(filter).try_init();
+}
+
+/// Without the `trace` feature there is nothing to turn on; `--trace` says so.
+#[cfg(not(feature = "trace"))]
+pub fn init_trace(enabled: bool) {
+	if enabled {
+		log(LogLevel::Warning, "This build has no diagnostics. Rebuild with `cargo build --features trace` to use --trace.");
+	}
+}
+
+/// Enters a diagnostic span until the returned guard is dropped, e.g.
+/// `let _span = trace_span!("record", output = path, tracks = count);`
+#[cfg(feature = "trace")]
+#[allow(unused_macros)]
+macro_rules! trace_span {
+	($($args:tt)*) => { tracing::debug_span!($($args)*).entered() };
+}
+#[cfg(not(feature = "trace"))]
+#[allow(unused_macros)]
+macro_rules! trace_span {
+	($($args:tt)*) => { $crate::logger::NoSpan };
+}
+#[allow(unused_imports)]
+pub(crate) use trace_span;
+
+/// Emits a diagnostic event inside the current span, e.g. `trace_event!(offset, size, "track")`.
+#[cfg(feature = "trace")]
+macro_rules! trace_event {
+	($($args:tt)*) => { tracing::debug!($($args)*) };
+}
+#[cfg(not(feature = "trace"))]
+macro_rules! trace_event {
+	($($args:tt)*) => {};
+}
+#[allow(unused_imports)]
+pub(crate) use trace_event;
+
+/// What `trace_span!` gives without the `trace` feature.
+#[cfg(not(feature = "trace"))]
+#[allow(dead_code)]
+pub struct NoSpan;
+
 #[allow(dead_code)]
 pub enum LogLevel {
 	Info,
@@ -17,6 +147,12 @@ pub enum LogLevel {
 }
 
 pub fn log(level: LogLevel, message: &str) {
+	if matches!(level, LogLevel::Info) && is_quiet() {
+		return;
+	}
+	#[cfg(test)]
+	LOGGED.with(|logged| logged.borrow_mut().push(message.to_string()));
+	clear_progress();
 	let timestamp = Local::now().format("%H:%M:%S").to_string();
 	let prefix = match level {
 		LogLevel::Info => "𝒊 ".blue().bold(),
@@ -0,0 +1,145 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// LYRICS MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Synced lyrics per track. `record --lyrics <index>:<file.lrc>` stores the LRC
+// text as-is in the track's TOC entry (format v10+). Players parse it into timed
+// lines and highlight the one at the playback position; tracks without lyrics
+// show nothing.
+//
+// LRC lines start with one or more `[mm:ss.xx]` timestamps followed by the text.
+// ID tags such as `[ar:Artist]` are skipped, except `[offset:±ms]`, which shifts
+// every line. Word timestamps (`<mm:ss.xx>`, "enhanced" LRC) are dropped.
+
+use std::io::Read;
+use std::time::Duration;
+use crate::io::open_file;
+use crate::format::MAX_LYRICS_LEN;
+
+/// Lyrics for one of the recorded tracks.
+#[derive(Clone, Debug)]
+pub struct LyricsSpec {
+	/// 1-based track index, matching the order of the audio files.
+	pub track: usize,
+	pub path: String,
+}
+
+/// Parses `<index>:<file.lrc>`, e.g. `2:lyrics/track2.lrc`.
+pub fn parse_lyrics_spec(text: &str) -> Result<LyricsSpec, String> {
+	let usage = || format!("Invalid lyrics '{}'. Expected <index>:<file.lrc>, e.g. 2:track2.lrc", text);
+
+	let (index, path) = text.split_once(':').ok_or_else(usage)?;
+	let track: usize = index.trim().parse().map_err(|_| usage())?;
+	if track == 0 {
+		return Err("Track indices start at 1.".to_string());
+	}
+	if path.trim().is_empty() {
+		return Err(usage());
+	}
+
+	Ok(LyricsSpec { track, path: path.to_string() })
+}
+
+/// Reads an LRC file, checking that it fits in the TOC and has at least one timed line.
+pub fn load_lyrics(path: &str) -> Result<String, String> {
+	let mut bytes = Vec::new();
+	open_file(path)?.take(MAX_LYRICS_LEN as u64 + 1).read_to_end(&mut bytes)
+		.map_err(|e| format!("Cannot read lyrics '{}': {}", path, e))?;
+	if bytes.len() > MAX_LYRICS_LEN {
+		return Err(format!("Lyrics '{}' are too large (the limit is {} bytes).", path, MAX_LYRICS_LEN));
+	}
+	let text = String::from_utf8(bytes).map_err(|_| format!("Lyrics '{}' aren't UTF-8 text.", path))?;
+	let text = text.strip_prefix('\u{feff}').unwrap_or(&text).to_string();
+	if parse_lrc(&text).is_empty() {
+		return Err(format!("Lyrics '{}' have no timestamped lines. Is it an LRC file?", path));
+	}
+	Ok(text)
+}
+
+/// One line of synced lyrics.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LyricLine {
+	/// When the line starts, from the beginning of the track.
+	pub at: Duration,
+	/// Empty for the instrumental breaks some LRC files mark with a bare timestamp.
+	pub text: String,
+}
+
+/// Parses LRC text into lines sorted by time. Lines without a timestamp are skipped.
+pub fn parse_lrc(text: &str) -> Vec<LyricLine> {
+	let mut offset_ms = 0i64;
+	let mut lines = Vec::new();
+	for raw in text.lines() {
+		let mut rest = raw.trim();
+		let mut stamps = Vec::new();
+		while let Some((inside, after)) = rest.strip_prefix('[').and_then(|r| r.split_once(']')) {
+			if let Some(at) = parse_stamp(inside) {
+				stamps.push(at);
+			} else if let Some(value) = inside.strip_prefix("offset:") {
+				offset_ms = value.trim().parse().unwrap_or(0);
+			}
+			rest = after.trim_start();
+		}
+		if stamps.is_empty() {
+			continue;
+		}
+		let text = strip_word_stamps(rest);
+		lines.extend(stamps.into_iter().map(|at| LyricLine { at, text: text.clone() }));
+	}
+
+	// A positive offset makes lyrics appear sooner
+	if offset_ms != 0 {
+		for line in &mut lines {
+			let millis = line.at.as_millis() as i64 - offset_ms;
+			line.at = Duration::from_millis(millis.max(0) as u64);
+		}
+	}
+	lines.sort_by_key(|line| line.at);
+	lines
+}
+
+/// Index of the line being sung at `position`: the last one that has started.
+pub fn current_line(lines: &[LyricLine], position: Duration) -> Option<usize> {
+	lines.partition_point(|line| line.at <= position).checked_sub(1)
+}
+
+/// Parses `mm:ss`, `mm:ss.xx` or `mm:ss.xxx` (also with `:` before the fraction).
+fn parse_stamp(text: &str) -> Option<Duration> {
+	let (minutes, rest) = text.split_once(':')?;
+	let (seconds, fraction) = match rest.split_once(['.', ':']) {
+		Some((seconds, fraction)) => (seconds, fraction),
+		None => (rest, ""),
+	};
+	let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+	if !all_digits(minutes) || !all_digits(seconds) || !(fraction.is_empty() || all_digits(fraction)) || fraction.len() > 3 {
+		return None;
+	}
+	let seconds: u64 = seconds.parse().ok()?;
+	if seconds >= 60 {
+		return None;
+	}
+	// ".5" is half a second, ".05" five hundredths
+	let millis = if fraction.is_empty() { 0 } else { fraction.parse::<u64>().ok()? * 10u64.pow(3 - fraction.len() as u32) };
+	let minutes = minutes.parse::<u64>().ok()?.checked_mul(60_000)?;
+	Some(Duration::from_millis(minutes.checked_add(seconds * 1000 + millis)?))
+}
+
+/// Removes enhanced-LRC word timestamps such as `<01:02.50>` from a line's text.
+fn strip_word_stamps(text: &str) -> String {
+	let mut out = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('<') {
+		match rest[start..].find('>') {
+			Some(len) if parse_stamp(&rest[start + 1..start + len]).is_some() => {
+				out.push_str(&rest[..start]);
+				rest = &rest[start + len + 1..];
+			}
+			_ => {
+				out.push_str(&rest[..=start]);
+				rest = &rest[start + 1..];
+			}
+		}
+	}
+	out.push_str(rest);
+	out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
@@ -51,22 +51,63 @@
 mod logger;
 mod constants;
 mod io;
+mod format;
+mod png;
 mod record;
+mod wizard;
+mod cover;
+mod art;
+mod lyrics;
+mod seek_index;
+mod ogg;
+mod sign;
+mod transcode;
 mod inspect;
+mod eject;
+mod merge;
+mod waveform;
+mod equalizer;
+mod balance;
+mod mono;
+mod notify;
+mod mpris;
 mod playback;
 mod tui;
 mod gui;
+mod daemon;
+mod doctor;
+mod download;
+mod selftest;
+mod config;
+mod history;
 
-use clap::{Parser, Subcommand};
-use record::record;
-use inspect::inspect;
-use playback::{play_random, play_all};
-use tui::run_tui;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use record::{record, parse_align, parse_size, parse_tape, ExtraFormat, RecordOptions};
+use wizard::{pick_cassette, record_wizard};
+use cover::{CoverSource, Theme};
+use transcode::{TrimSpec, OutputFormat, parse_trim};
+use art::{ArtSpec, parse_art};
+use lyrics::{LyricsSpec, parse_lyrics_spec};
+use format::Side;
+use inspect::{inspect, verify_collection, InspectOptions};
+use merge::{merge, split};
+use eject::{eject_cover, extract_tracks, ExtractOptions};
+use waveform::{waveform, WaveformOptions};
+use playback::{play_one, play_all, PlayAllOptions, PlayOneOptions};
+use tui::{run_tui, TuiOptions};
 use gui::run_gui;
+use daemon::run_daemon;
+use doctor::{doctor, list_devices};
+use selftest::selftest;
+use config::Config;
+use history::print_stats;
 use crate::logger::{log, LogLevel};
 use colored::*;
-use rfd::FileDialog;
+use io::{resolve_output, Progress};
 use glob::glob;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Digital cassette tapes disguised as PNG images
 #[derive(Parser)]
@@ -76,28 +117,281 @@ use glob::glob;
 struct Cli {
 	#[command(subcommand)]
 	command: Commands,
+
+	/// Base directory for relative output paths (created if missing; defaults to the current directory)
+	#[arg(long, global = true, env = "REWIND_OUTPUT_DIR", value_name = "DIR")]
+	output_dir: Option<String>,
+
+	/// Only print successes, warnings and errors
+	#[arg(short, long, global = true)]
+	quiet: bool,
+
+	/// Print diagnostics (offsets, sizes, timings) to stderr; needs a build with `--features trace`. RUST_LOG picks targets
+	#[arg(long, global = true)]
+	trace: bool,
 }
 
+// Parsed once per run, so the size of the biggest variant (`record`'s flags) doesn't matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum Commands {
-	/// Inject audio files into a PNG image to create a cassette
+	/// Inject audio files into a PNG image to create a cassette (run without arguments for a guided wizard)
 	Record {
-		/// Path to the PNG image (cover art)
-		image: String,
+		/// Path to the PNG image (cover art). With --no-cover/--generate-cover, this is the first audio file instead
+		image: Option<String>,
 
 		/// Audio files to embed (FLAC/MP3/OGG/WAV)
-		#[arg(required = true)]
 		audio_files: Vec<String>,
 
+		/// Audio files for Side A of the tape (recorded after any positional audio files)
+		#[arg(long = "side-a", value_name = "FILES", num_args = 1..)]
+		side_a: Vec<String>,
+
+		/// Audio files for Side B of the tape (recorded after every Side A track)
+		#[arg(long = "side-b", value_name = "FILES", num_args = 1..)]
+		side_b: Vec<String>,
+
 		/// Output cassette file path
 		#[arg(short, long)]
-		output: String,
+		output: Option<String>,
+
+		/// Generate a default cassette cover instead of using an image
+		#[arg(long, conflicts_with = "generate_cover")]
+		no_cover: bool,
+
+		/// Generate a cassette cover labelled with the album, artist and track count
+		#[arg(long)]
+		generate_cover: bool,
+
+		/// Color theme for generated covers
+		#[arg(long, value_enum, default_value_t = Theme::Classic)]
+		theme: Theme,
+
+		/// Comma-separated track names to store instead of the file names (one per audio file)
+		#[arg(long, value_delimiter = ',')]
+		names: Option<Vec<String>>,
+
+		/// Name tracks after their title tag instead of the file name (untagged tracks keep the file name)
+		#[arg(long, conflicts_with = "names")]
+		name_from_tags: bool,
+
+		/// Comma-separated track numbers to show instead of 1, 2, 3... (one per audio file), e.g. `1,3,5`
+		#[arg(long, value_name = "NUMBERS", value_delimiter = ',', value_parser = clap::value_parser!(u32).range(1..))]
+		track_numbers: Option<Vec<u32>>,
+
+		/// Album title to store for the whole cassette (default: the first album tag)
+		#[arg(long)]
+		album: Option<String>,
+
+		/// Album artist to store for the whole cassette (default: the artist tags, if they agree)
+		#[arg(long)]
+		artist: Option<String>,
+
+		/// Release year to store for the whole cassette (default: the first year tag)
+		#[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+		year: Option<u16>,
+
+		/// Nominal tape the cassette is recorded on, e.g. `C60` or `C90`. Warns when the audio is longer
+		#[arg(long, value_name = "TYPE", value_parser = parse_tape)]
+		tape: Option<u16>,
+
+		/// Refuse to record (before writing anything) when the cassette would exceed this size,
+		/// e.g. `25MB` or `8MiB` to stay under an upload limit
+		#[arg(long, value_name = "SIZE", value_parser = parse_size)]
+		max_size: Option<NonZeroU64>,
+
+		/// Start every track on a multiple of this many bytes, e.g. `4096`, so players can fetch
+		/// tracks with aligned range requests. Pads with zeros between tracks (default: no padding)
+		#[arg(long, value_name = "BYTES", value_parser = parse_align)]
+		align: Option<NonZeroU32>,
+
+		/// Keep only a time range of a track, e.g. `2:1:30-3:00` (repeatable). Re-encodes that track to WAV
+		#[arg(long = "trim", value_name = "INDEX:START-END", value_parser = parse_trim)]
+		trims: Vec<TrimSpec>,
+
+		/// Artwork for a track, e.g. `2:track2.jpg` (repeatable). PNG/JPEG, stored as a small thumbnail
+		#[arg(long = "art", value_name = "INDEX:IMAGE", value_parser = parse_art)]
+		art: Vec<ArtSpec>,
+
+		/// Synced lyrics for a track, e.g. `2:track2.lrc` (repeatable). Shown line by line in the TUI and GUI
+		#[arg(long = "lyrics", value_name = "INDEX:FILE", value_parser = parse_lyrics_spec)]
+		lyrics: Vec<LyricsSpec>,
+
+		/// Store a seek index for FLAC/OGG tracks so players can start mid-track instantly
+		#[arg(long)]
+		seek_index: bool,
+
+		/// Don't point out lossy (MP3/OGG/...) sources
+		#[arg(long)]
+		allow_lossy: bool,
+
+		/// Also accept these formats, e.g. `aiff` (default: FLAC, MP3, AAC/MP4, Ogg Vorbis, WAV).
+		/// Files in them must still decode with this build's players
+		#[arg(long = "allow-format", value_name = "FORMATS", value_delimiter = ',')]
+		allow_formats: Vec<ExtraFormat>,
+
+		/// Sign the cassette with this Ed25519 private key (PKCS#8 PEM)
+		#[arg(long, value_name = "KEYFILE")]
+		sign: Option<String>,
+
+		/// Read the finished cassette back and check its checksum and TOC (exits non-zero if it fails)
+		#[arg(long)]
+		verify_after: bool,
 	},
 
 	/// Inspect a cassette file and verify its integrity
 	Inspect {
+		/// Path to the cassette file, or an http(s) link to one (needs a build with `--features download`)
+		cassette: String,
+
+		/// Print a hexdump of the bytes following IEND (for diagnosing unreadable cassettes)
+		#[arg(long)]
+		debug: bool,
+
+		/// Print only the computed CRC32 as hex, for scripting
+		#[arg(long, conflicts_with = "debug")]
+		raw: bool,
+
+		/// Also verify the cover image's own PNG chunk CRCs
+		#[arg(long, conflicts_with = "raw")]
+		check_image: bool,
+
+		/// Refuse files without the RWND marker or whose TOC doesn't add up exactly
+		#[arg(long, conflicts_with = "raw")]
+		strict: bool,
+
+		/// Skip the checksum pass and only list the contents (much faster on huge cassettes)
+		#[arg(long, conflicts_with = "raw")]
+		skip_crc: bool,
+
+		/// List only the TOC (number, name, size) without reading any track's tags
+		#[arg(long, conflicts_with = "raw")]
+		no_metadata: bool,
+
+		/// Also try to decode each listed track, to find tracks that won't play (slower)
+		#[arg(long, conflicts_with = "raw")]
+		check_playable: bool,
+
+		/// Report whether the file is likely to be refused, flagged or stripped when shared online
+		#[arg(long, conflicts_with = "raw")]
+		compat: bool,
+
+		/// Check every track against its own CRC32 to find out which one is damaged
+		#[arg(long, conflicts_with = "raw")]
+		per_track: bool,
+
+		/// Require a signature by this Ed25519 public key (PEM)
+		#[arg(long, value_name = "KEYFILE", conflicts_with = "raw")]
+		pubkey: Option<String>,
+
+		/// Skip this many tracks before listing (for paging through big compilations)
+		#[arg(long, default_value_t = 0)]
+		offset: usize,
+
+		/// List at most this many tracks
+		#[arg(long)]
+		limit: Option<usize>,
+
+		/// Draw the track list on the label of a little cassette instead of a table
+		#[arg(long, conflicts_with = "raw")]
+		pretty: bool,
+
+		/// Refuse to download a linked cassette larger than this, e.g. `500MB` (default 100MB)
+		#[arg(long, value_name = "SIZE", value_parser = parse_size)]
+		max_download: Option<NonZeroU64>,
+	},
+
+	/// Check the seals of a cassette or a whole collection, exiting non-zero if any are damaged
+	Verify {
+		/// Cassette file, or a directory of cassettes
+		path: String,
+
+		/// Also verify the cassettes in subdirectories
+		#[arg(short, long)]
+		recursive: bool,
+	},
+
+	/// Combine several cassettes into one, in the order given
+	Merge {
+		/// Cassettes to merge (at least two)
+		#[arg(required = true, num_args = 2..)]
+		cassettes: Vec<String>,
+
+		/// PNG to use as the cover (defaults to the first cassette's cover)
+		#[arg(long)]
+		image: Option<String>,
+
+		/// Output cassette file path
+		#[arg(short, long)]
+		output: String,
+	},
+
+	/// Split a cassette into single-track cassettes that share its cover
+	Split {
+		/// Path to the cassette file
+		cassette: String,
+
+		/// Directory to write the cassettes to
+		#[arg(short, long, default_value = ".")]
+		dir: String,
+	},
+
+	/// Write the cassette's cover art out as a standalone PNG
+	EjectCover {
+		/// Path to the cassette file
+		cassette: String,
+
+		/// Output PNG file path
+		#[arg(short, long)]
+		output: String,
+	},
+
+	/// Write the cassette's audio tracks out as standalone files
+	Extract {
+		/// Path to the cassette file
+		cassette: String,
+
+		/// Track number to extract (1-based). Extracts every track if not specified
+		#[arg(short, long)]
+		track: Option<usize>,
+
+		/// Directory to write the tracks to
+		#[arg(short, long, default_value = ".")]
+		output: String,
+
+		/// Decode and re-encode the tracks to this format (MP3 is lossy). Tags are copied over
+		#[arg(long, value_enum, value_name = "FORMAT")]
+		to: Option<OutputFormat>,
+
+		/// Name the files after their title tag instead of the stored name (untagged tracks keep it)
+		#[arg(long)]
+		tag_name: bool,
+	},
+
+	/// Draw a track's waveform as a PNG image
+	Waveform {
 		/// Path to the cassette file
 		cassette: String,
+
+		/// Track number to draw (1-based)
+		#[arg(short, long)]
+		track: usize,
+
+		/// Output PNG file path
+		#[arg(short, long)]
+		output: String,
+
+		/// Image width in pixels
+		#[arg(long, default_value_t = 1200, value_parser = clap::value_parser!(u32).range(16..=8192))]
+		width: u32,
+
+		/// Image height in pixels
+		#[arg(long, default_value_t = 240, value_parser = clap::value_parser!(u32).range(16..=4096))]
+		height: u32,
+
+		/// Color theme (the same ones as generated covers)
+		#[arg(long, value_enum, default_value_t = Theme::Classic)]
+		theme: Theme,
 	},
 
 	/// Play a track from the cassette
@@ -110,31 +404,180 @@ enum Commands {
 		track: Option<usize>,
 
 		/// Play all tracks in sequence
-		#[arg(short, long)]
+		#[arg(short, long, group = "sequence")]
 		all: bool,
+
+		/// With --all, start at this track (1-based) and play to the end
+		#[arg(long, value_name = "N", requires = "all", conflicts_with = "track")]
+		from: Option<usize>,
+
+		/// With --all, start over from track 1 after the last track until stopped (with --shuffle-all, reshuffle)
+		#[arg(long, requires = "sequence")]
+		repeat: bool,
+
+		/// With --track, play that track over and over until stopped
+		#[arg(long, requires = "track", conflicts_with = "all")]
+		repeat_track: bool,
+
+		/// Flip the tape between sides automatically instead of waiting for a keypress
+		#[arg(long, overrides_with = "no_auto_flip")]
+		auto_flip: bool,
+
+		/// Wait for a keypress at the end of a side, even if the config file sets auto_flip
+		#[arg(long, overrides_with = "auto_flip")]
+		no_auto_flip: bool,
+
+		/// Play every track once in random order (a full shuffled pass instead of one random track)
+		#[arg(long, group = "sequence", conflicts_with = "track")]
+		shuffle_all: bool,
+
+		/// Pick the random track favoring the ones played least often (see `rewind stats`)
+		#[arg(long, conflicts_with_all = ["track", "all", "shuffle_all"])]
+		smart: bool,
+
+		/// Silence between tracks with --all/--shuffle-all, in milliseconds
+		#[arg(long, value_name = "MS", default_value_t = 500)]
+		gap: u64,
+
+		/// Play a short tape clunk/hiss between tracks with --all/--shuffle-all
+		#[arg(long)]
+		tape_sfx: bool,
+
+		/// Show a desktop notification on every track change
+		#[arg(long)]
+		notify: bool,
+
+		/// Seed for the random track pick or shuffle, so the same seed always plays the same way
+		#[arg(long, value_name = "N")]
+		seed: Option<u64>,
+
+		/// Start part-way into the track, as M:SS, seconds, or a percentage (e.g. 1:30, 50%)
+		#[arg(long, value_name = "POS", conflicts_with = "sequence")]
+		seek: Option<String>,
+
+		/// Refuse files without the RWND marker or whose TOC doesn't add up exactly
+		#[arg(long)]
+		strict: bool,
+
+		/// Output device to play through (see `rewind devices` for names; defaults to the system default)
+		#[arg(long, value_name = "NAME")]
+		device: Option<String>,
+
+		/// Playback volume in percent
+		#[arg(long, value_name = "PERCENT", default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+		volume: u8,
+
+		/// Mix stereo tracks down to mono (for a single speaker, or a recording with one bad channel)
+		#[arg(long)]
+		mono: bool,
 	},
 
 	/// Open the interactive TUI player
 	Tui {
-		/// Path to the cassette file (opens file picker if not provided)
+		/// Path to the cassette file, or a folder of cassettes to browse (opens file picker if not provided)
 		cassette: Option<String>,
+
+		/// Flip the tape between sides automatically instead of waiting for a keypress
+		#[arg(long, overrides_with = "no_auto_flip")]
+		auto_flip: bool,
+
+		/// Wait for a keypress at the end of a side, even if the config file sets auto_flip
+		#[arg(long, overrides_with = "auto_flip")]
+		no_auto_flip: bool,
+
+		/// Show a desktop notification on every track change
+		#[arg(long)]
+		notify: bool,
+
+		/// Track number to select on launch (1-based)
+		#[arg(short, long)]
+		track: Option<usize>,
+
+		/// Start playing the selected track immediately
+		#[arg(long)]
+		play: bool,
+
+		/// Starting volume in percent
+		#[arg(long, value_name = "PERCENT", default_value_t = 80, value_parser = clap::value_parser!(u8).range(0..=100))]
+		volume: u8,
+
+		/// How often the progress bar is updated, in milliseconds
+		#[arg(long, value_name = "MS", default_value_t = 250, value_parser = clap::value_parser!(u64).range(50..=5000))]
+		progress_interval: u64,
+
+		/// Output device to play through (see `rewind devices` for names; defaults to the system default)
+		#[arg(long, value_name = "NAME")]
+		device: Option<String>,
+
+		/// Mix stereo tracks down to mono (for a single speaker, or a recording with one bad channel)
+		#[arg(long)]
+		mono: bool,
+
+		/// Start with the scrub preview on: seeking with [ and ] plays short blips, like scrubbing
+		/// a tape (toggle with Z; decodes on every step)
+		#[arg(long)]
+		scrub: bool,
 	},
 
 	/// Open the desktop GUI player (v0.4.0)
 	Gui {
 		/// Path to the cassette file (opens file picker if not provided)
 		cassette: Option<String>,
+
+		/// Show a desktop notification on every track change
+		#[arg(long)]
+		notify: bool,
+
+		/// Track number to select on launch (1-based)
+		#[arg(short, long)]
+		track: Option<usize>,
+
+		/// Start playing the selected track immediately
+		#[arg(long)]
+		play: bool,
+
+		/// Output device to play through (see `rewind devices` for names; defaults to the system default)
+		#[arg(long, value_name = "NAME")]
+		device: Option<String>,
+	},
+
+	/// Run a headless player driven by JSON commands on stdin, for external frontends.
+	///
+	/// Reads one JSON command per line and writes one JSON event per line to stdout.
+	/// Commands: {"cmd":"play"}, {"cmd":"play","track":2}, {"cmd":"pause"}, {"cmd":"resume"},
+	/// {"cmd":"stop"}, {"cmd":"next"}, {"cmd":"previous"}, {"cmd":"volume","level":70},
+	/// {"cmd":"status"}, {"cmd":"quit"}.
+	/// Events: ready (track list), track, state, progress, volume and error, each tagged
+	/// by an "event" field, e.g. {"event":"state","state":"playing"}.
+	Daemon {
+		/// Path to the cassette file
+		cassette: String,
+
+		/// Output device to play through (see `rewind devices` for names; defaults to the system default)
+		#[arg(long, value_name = "NAME")]
+		device: Option<String>,
 	},
-}
 
-/// Opens a native file picker to select a PNG cassette file.
-/// Returns None if the user cancels or no file is selected.
-fn pick_cassette() -> Option<String> {
-	FileDialog::new()
-		.add_filter("PNG Cassettes", &["png"])
-		.set_title("Select a Rewind.png Cassette")
-		.pick_file()
-		.map(|p| p.to_string_lossy().to_string())
+	/// Show the most played cassettes, from the play history in the config directory
+	Stats {
+		/// List the recently played cassettes with each track's play count instead
+		#[arg(long)]
+		history: bool,
+	},
+
+	/// Check that audio output and decoding work here, without needing a cassette
+	Doctor,
+
+	/// List the audio output devices (the names `--device` accepts), marking the default
+	Devices,
+
+	/// Read, decode and play a tiny cassette built into the binary, to tell a broken install from a broken cassette
+	#[command(hide = true)]
+	Selftest {
+		/// Stop after decoding instead of playing the beep (for CI and machines without audio)
+		#[arg(long)]
+		silent: bool,
+	},
 }
 
 /// Gets the cassette path from argument or opens file picker.
@@ -144,83 +587,222 @@ fn get_cassette_path(cassette: Option<String>) -> Option<String> {
 		Some(path)
 	} else {
 		log(LogLevel::Info, "No cassette provided. Opening file picker...");
-		let picked = pick_cassette();
-		if picked.is_none() {
+		let Some(picked) = pick_cassette() else {
 			log(LogLevel::Warning, "No cassette selected. Exiting.");
+			return None;
+		};
+		match picked.into_os_string().into_string() {
+			Ok(path) => Some(path),
+			Err(path) => {
+				log(LogLevel::Error, &format!("'{}' isn't valid Unicode, which cassette paths need to be. Rename it first.", Path::new(&path).display()));
+				None
+			}
+		}
+	}
+}
+
+/// Places a relative output path under the `--output-dir` base, logging any error.
+fn output_path(path: &str, base: Option<&str>) -> Option<String> {
+	resolve_output(path, base).inspect_err(|e| log(LogLevel::Error, e)).ok()
+}
+
+/// Expands wildcards in audio file patterns (cross-platform).
+/// Patterns that match nothing, or aren't valid globs, are kept as literal file names.
+/// Matches are kept as paths, so names that aren't valid Unicode still open.
+fn expand_patterns(patterns: &[String]) -> Vec<PathBuf> {
+	let mut expanded_files = Vec::new();
+	for pattern in patterns {
+		match glob(pattern) {
+			Ok(paths) => {
+				let mut found_any = false;
+				for entry in paths {
+					match entry {
+						Ok(path) => {
+							expanded_files.push(path);
+							found_any = true;
+						}
+						Err(e) => log(LogLevel::Warning, &format!("Skipping '{}' while expanding '{}': {}", e.path().display(), pattern, e.error())),
+					}
+				}
+				if !found_any {
+					expanded_files.push(PathBuf::from(pattern));
+				}
+			}
+			Err(_) => expanded_files.push(PathBuf::from(pattern)),
 		}
-		picked
 	}
+	expanded_files
 }
 
 fn main() {
-	log(LogLevel::Info, &format!("Welcome to {}! {}", "Rewind.png".cyan(), "[●▪▪●]".bold()));
+	// Defaults from the config file go in before parsing, so flags still override them
+	let (config, config_warnings) = Config::load();
+	let matches = config.apply(Cli::command()).get_matches();
+	let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+	logger::set_quiet(cli.quiet);
+	logger::init_colors();
+	logger::init_trace(cli.trace);
+	for warning in &config_warnings {
+		log(LogLevel::Warning, warning);
+	}
 
-	let cli = Cli::parse();
+	// Machine-readable output must be the only thing on stdout
+	let machine_output = matches!(cli.command, Commands::Daemon { .. } | Commands::Inspect { raw: true, .. });
+	if !machine_output {
+		log(LogLevel::Info, &format!("Welcome to {}! {}", "Rewind.png".cyan(), "[●▪▪●]".bold()));
+	}
 
+	let base = cli.output_dir.as_deref();
 	match cli.command {
-		Commands::Record { image, audio_files, output } => {
-			// Expand wildcards in audio file patterns (cross-platform)
-			let mut expanded_files = Vec::new();
-			for pattern in &audio_files {
-				// Try to expand as glob pattern
-				match glob(pattern) {
-					Ok(paths) => {
-						let mut found_any = false;
-						for entry in paths.flatten() {
-							if let Some(path_str) = entry.to_str() {
-								expanded_files.push(path_str.to_string());
-								found_any = true;
-							}
-						}
-						// If no matches found, treat as literal filename
-						if !found_any {
-							expanded_files.push(pattern.clone());
+		Commands::Record { image, mut audio_files, side_a, side_b, output, no_cover, generate_cover, theme, names, name_from_tags, track_numbers, album, artist, year, tape, max_size, align, trims, art, lyrics, seek_index, allow_lossy, allow_formats, sign, verify_after } => {
+			let options = RecordOptions { names, name_from_tags, track_numbers, album, artist, year, tape_minutes: tape, max_size, align, trims, art, lyrics, seek_index, allow_lossy, allow_formats, sign_key: sign, verify_after, ..RecordOptions::default() };
+			let mut line = Progress::new("Recording");
+
+			// Nothing to record given: ask for it instead
+			if image.is_none() && side_a.is_empty() && side_b.is_empty() {
+				match record_wizard() {
+					Ok(Some(answers)) => {
+						let Some(output) = output_path(&answers.output, base) else { std::process::exit(1) };
+						if !record(&answers.cover, &answers.audio_files, &output, &options, &mut |done, total| line.update(done, total)) {
+							std::process::exit(1);
 						}
 					}
-					Err(_) => {
-						// Invalid pattern, treat as literal filename
-						expanded_files.push(pattern.clone());
+					Ok(None) => log(LogLevel::Warning, "Recording cancelled."),
+					Err(e) => {
+						log(LogLevel::Error, &e);
+						std::process::exit(1);
 					}
 				}
+				return;
 			}
-			
+
+			// Every way recording can fail exits non-zero, so scripts can tell
+			let Some(output) = output else {
+				log(LogLevel::Error, "Missing --output. Where should the cassette be saved?");
+				std::process::exit(1);
+			};
+			let Some(output) = output_path(&output, base) else { std::process::exit(1) };
+			let cover = if no_cover || generate_cover {
+				// Without a cover, the first positional argument is just another audio file
+				if let Some(image) = image {
+					audio_files.insert(0, image);
+				}
+				if generate_cover { CoverSource::Generated(theme) } else { CoverSource::Default(theme) }
+			} else if let Some(image) = image {
+				CoverSource::File(image)
+			} else {
+				log(LogLevel::Error, "Missing cover image. Pass a PNG first, or use --no-cover/--generate-cover.");
+				std::process::exit(1);
+			};
+
+			// Positional files and --side-a make up Side A, followed by Side B
+			audio_files.extend(side_a);
+			let mut expanded_files = expand_patterns(&audio_files);
+			let side_a_count = expanded_files.len();
+			expanded_files.extend(expand_patterns(&side_b));
+			let sides: Vec<Side> = (0..expanded_files.len())
+				.map(|i| if i < side_a_count { Side::A } else { Side::B })
+				.collect();
+
 			if expanded_files.is_empty() {
 				log(LogLevel::Error, "No audio files found matching the provided patterns.");
-				return;
+				std::process::exit(1);
+			}
+
+			if !record(&cover, &expanded_files, &output, &RecordOptions { sides, ..options }, &mut |done, total| line.update(done, total)) {
+				std::process::exit(1);
+			}
+		}
+
+		Commands::Inspect { cassette, debug, raw, check_image, strict, skip_crc, no_metadata, check_playable, compat, per_track, pubkey, offset, limit, pretty, max_download } => {
+			inspect(&cassette, &InspectOptions { debug, raw, check_image, strict, skip_crc, no_metadata, check_playable, compat, per_track, pubkey, offset, limit, pretty, max_download });
+		}
+
+		Commands::Verify { path, recursive } => {
+			if !verify_collection(&path, recursive) {
+				std::process::exit(1);
 			}
-			
-			let audio_refs: Vec<&str> = expanded_files.iter().map(|s| s.as_str()).collect();
-			record(&image, &audio_refs, &output);
 		}
 
-		Commands::Inspect { cassette } => {
-			inspect(&cassette);
+		Commands::Merge { cassettes, image, output } => {
+			let Some(output) = output_path(&output, base) else { return };
+			merge(&cassettes, image.as_deref(), &output);
+		}
+
+		Commands::Split { cassette, dir } => {
+			let Some(dir) = output_path(&dir, base) else { return };
+			split(&cassette, &dir);
+		}
+
+		Commands::EjectCover { cassette, output } => {
+			let Some(output) = output_path(&output, base) else { return };
+			eject_cover(&cassette, &output);
+		}
+
+		Commands::Extract { cassette, track, output, to, tag_name } => {
+			let Some(output_dir) = output_path(&output, base) else { return };
+			extract_tracks(&cassette, &ExtractOptions { track, output_dir, to, tag_name });
+		}
+
+		Commands::Waveform { cassette, track, output, width, height, theme } => {
+			let Some(output) = output_path(&output, base) else { return };
+			waveform(&cassette, &output, &WaveformOptions { track, width, height, theme });
 		}
 
-		Commands::Play { cassette, track, all } => {
+		Commands::Play { cassette, track, all, from, repeat, repeat_track, auto_flip, no_auto_flip, shuffle_all, smart, gap, tape_sfx, notify, seed, seek, strict, device, volume, mono } => {
 			let Some(path) = get_cassette_path(cassette) else { return };
-			if all {
-				play_all(&path);
-			} else if let Some(_track_num) = track {
-				log(LogLevel::Warning, "Track selection not yet implemented. Playing random track.");
-				play_random(&path);
+			// A configured auto_flip is a default clap can't unset, so --no-auto-flip wins here
+			let auto_flip = auto_flip && !no_auto_flip;
+			if all || shuffle_all {
+				play_all(&path, &PlayAllOptions { gap: Duration::from_millis(gap), tape_sfx, auto_flip, notify, strict, device, from, repeat, shuffle: shuffle_all, seed, volume, mono });
 			} else {
-				play_random(&path);
+				play_one(&path, &PlayOneOptions { track, seed, smart, seek, repeat_track, notify, strict, device, volume, mono });
 			}
 		}
 
-		Commands::Tui { cassette } => {
+		Commands::Tui { cassette, auto_flip, no_auto_flip, notify, track, play, device, volume, progress_interval, mono, scrub } => {
 			let Some(path) = get_cassette_path(cassette) else { return };
-			if let Err(e) = run_tui(&path) {
+			let auto_flip = auto_flip && !no_auto_flip;
+			let options = TuiOptions { auto_flip, notify, start_track: track, autoplay: play, device, volume: Some(volume), progress_interval: Some(Duration::from_millis(progress_interval)), mono, scrub_preview: scrub };
+			if let Err(e) = run_tui(&path, &options) {
 				log(LogLevel::Error, &e);
 			}
 		}
 
-		Commands::Gui { cassette } => {
+		Commands::Daemon { cassette, device } => {
+			// Errors were already sent to stdout as events
+			if run_daemon(&cassette, device).is_err() {
+				std::process::exit(1);
+			}
+		}
+
+		Commands::Gui { cassette, notify, track, play, device } => {
 			let Some(path) = get_cassette_path(cassette) else { return };
-			if let Err(e) = run_gui(&path) {
+			if let Err(e) = run_gui(&path, notify, track, play, device) {
 				log(LogLevel::Error, &e);
 			}
 		}
+
+		Commands::Stats { history } => {
+			print_stats(history);
+		}
+
+		Commands::Doctor => {
+			if !doctor() {
+				std::process::exit(1);
+			}
+		}
+
+		Commands::Selftest { silent } => {
+			if !selftest(silent) {
+				std::process::exit(1);
+			}
+		}
+
+		Commands::Devices => {
+			if !list_devices() {
+				std::process::exit(1);
+			}
+		}
 	}
 }
\ No newline at end of file
@@ -0,0 +1,251 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// MERGE MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Joins several cassettes into one compilation, splits one back into
+// single-track cassettes, and rewrites a cassette's album metadata in place.
+// Every source is checked against its CRC32 seal first.
+// Merging concatenates the TOC entries (keeping names, sides and art as-is,
+// duplicates included, but renumbering by position if track numbers clash);
+// splitting gives each track its own cassette with the original cover and
+// album metadata; a merge keeps the album metadata only if every source agrees. Audio
+// is always copied straight across, never re-encoded, and every output is
+// sealed with a fresh CRC32.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crc32fast::Hasher;
+use crate::io::{open_file, transfer, PendingFile, Progress};
+use crate::format::{padding_before, AlbumInfo, Cassette, CassetteHeader, CassetteLayout, SealedRegion, ALBUM_VERSION, BLANK_CASSETTE, write_seal};
+use crate::png::image_end;
+use crate::logger::{log, LogLevel};
+
+/// A source cassette that passed validation.
+struct Source {
+	path: String,
+	file: File,
+	layout: CassetteLayout,
+}
+
+/// Merges `inputs` (in order) into `output_path`. The cover comes from `image`,
+/// or from the first cassette when none is given.
+pub fn merge(inputs: &[String], image: Option<&str>, output_path: &str) {
+	log(LogLevel::Info, &format!("Merging {} cassette(s) into {}", inputs.len(), output_path));
+
+	// 1. Validate every source before writing anything
+	let mut sources = Vec::with_capacity(inputs.len());
+	for path in inputs {
+		match open_source(path) {
+			Ok(source) => {
+				log(LogLevel::Info, &format!("Validated: {} ({} track(s))", path, source.layout.header.entries.len()));
+				sources.push(source);
+			}
+			Err(e) => { log(LogLevel::Error, &format!("'{}' rejected: {}", path, e)); return; }
+		}
+	}
+
+	let entries: Vec<_> = sources.iter().flat_map(|s| s.layout.header.entries.iter().cloned()).collect();
+	let mut names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+	names.sort_unstable();
+	if names.windows(2).any(|pair| pair[0] == pair[1]) {
+		log(LogLevel::Warning, "Some track names appear more than once. All copies are kept.");
+	}
+	let mut header = CassetteHeader::new(entries);
+	if header.has_duplicate_numbers() {
+		log(LogLevel::Warning, "Some track numbers appear more than once. The merged tracks are numbered by position instead.");
+		header.entries.iter_mut().for_each(|entry| entry.number = None);
+	}
+	let first_album = &sources[0].layout.header.album;
+	if sources.iter().all(|s| &s.layout.header.album == first_album) {
+		header.album = first_album.clone();
+	} else {
+		log(LogLevel::Warning, "The sources have different album metadata. The merged cassette has none.");
+	}
+	// Like the album metadata, the tape length and track alignment are only kept when every source agrees
+	let first_tape = sources[0].layout.header.tape_minutes;
+	if sources.iter().all(|s| s.layout.header.tape_minutes == first_tape) {
+		header.tape_minutes = first_tape;
+	}
+	let first_align = sources[0].layout.header.align;
+	if sources.iter().all(|s| s.layout.header.align == first_align) {
+		header.align = first_align;
+	}
+
+	// 2. Find the cover
+	let (mut cover, cover_name) = match image {
+		Some(path) => match open_file(path) {
+			Ok(f) => (f, path.to_string()),
+			Err(e) => { log(LogLevel::Error, &e); return; }
+		},
+		None => match sources[0].file.try_clone() {
+			Ok(f) => (f, format!("the cover of {}", sources[0].path)),
+			Err(e) => { log(LogLevel::Error, &format!("Cannot reopen '{}': {}", sources[0].path, e)); return; }
+		},
+	};
+	// Only the image itself is copied, so a cassette can donate its cover without its tape
+	let cover_len = match image_end(&mut cover) {
+		Ok(end) => end,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot use {} as the cover: {}", cover_name, e)); return; }
+	};
+
+	// 3. Write the cover, the combined TOC and every source's audio
+	let track_count = header.entries.len();
+	let mut audio: Vec<(&mut File, Vec<u64>)> = sources.iter_mut()
+		.map(|s| {
+			let offsets = s.layout.track_offsets();
+			(&mut s.file, offsets)
+		})
+		.collect();
+	match write_cassette(output_path, &mut cover, cover_len, header, &mut audio) {
+		Ok(crc) => log(LogLevel::Success, &format!("🎵 Merged {} track(s)! Sealed with CRC32: {:08X}", track_count, crc)),
+		Err(e) => log(LogLevel::Error, &e),
+	}
+}
+
+/// Splits a cassette into one single-track cassette per track in `output_dir`,
+/// each with the original cover, named `<NN> - <track>.png`.
+pub fn split(path: &str, output_dir: &str) {
+	log(LogLevel::Info, &format!("Splitting {} into {}", path, output_dir));
+
+	let mut source = match open_source(path) {
+		Ok(source) => source,
+		Err(e) => { log(LogLevel::Error, &format!("'{}' rejected: {}", path, e)); return; }
+	};
+	let cover_len = match image_end(&mut source.file) {
+		Ok(end) => end,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot find the cover image: {}", e)); return; }
+	};
+	let mut cover = match source.file.try_clone() {
+		Ok(f) => f,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot reopen '{}': {}", path, e)); return; }
+	};
+	if let Err(e) = fs::create_dir_all(output_dir) {
+		log(LogLevel::Error, &format!("Cannot create '{}': {}", output_dir, e));
+		return;
+	}
+
+	let entries = source.layout.header.entries.clone();
+	let album = source.layout.header.album.clone();
+	let tape_minutes = source.layout.header.tape_minutes;
+	let align = source.layout.header.align;
+	let offsets = source.layout.track_offsets();
+	let mut written = 0;
+	for (i, (entry, offset)) in entries.into_iter().zip(offsets).enumerate() {
+		let stem = Path::new(&entry.name).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "track".into());
+		let number = entry.display_number(i);
+		let out_path = Path::new(output_dir).join(format!("{:02} - {}.png", number, stem));
+		let out_str = out_path.to_string_lossy().to_string();
+
+		let header = CassetteHeader { album: album.clone(), tape_minutes, align, ..CassetteHeader::new(vec![entry]) };
+		match write_cassette(&out_str, &mut cover, cover_len, header, &mut [(&mut source.file, vec![offset])]) {
+			Ok(crc) => {
+				log(LogLevel::Success, &format!("Track {} → {} (CRC32: {:08X})", number, out_str, crc));
+				written += 1;
+			}
+			Err(e) => log(LogLevel::Error, &e),
+		}
+	}
+
+	if written > 0 {
+		log(LogLevel::Success, &format!("🎵 Split into {} cassette(s).", written));
+	}
+}
+
+/// Replaces the album metadata of the cassette at `path`, rewriting its TOC and re-sealing
+/// it with a fresh CRC32. Returns the new CRC. Cassettes older than `ALBUM_VERSION` have
+/// nowhere to store it, and signed cassettes would lose their signature, so both are refused.
+pub fn rewrite_album(path: &str, album: AlbumInfo) -> Result<u32, String> {
+	let album = album.validated()?;
+	let mut source = open_source(path)?;
+	let header = &source.layout.header;
+	if header.version < ALBUM_VERSION {
+		return Err(format!("This cassette uses format v{}, which has no album metadata. Re-record it to add some.", header.version));
+	}
+	if header.signature.is_some() {
+		return Err("This cassette is signed, and changing its album metadata would break the signature.".to_string());
+	}
+
+	let cover_len = source.layout.toc_start;
+	let offsets = source.layout.track_offsets();
+	let header = CassetteHeader { album: Some(album), ..source.layout.header.clone() };
+	let mut cover = source.file.try_clone().map_err(|e| format!("Cannot reopen '{}': {}", path, e))?;
+	write_cassette(path, &mut cover, cover_len, header, &mut [(&mut source.file, offsets)])
+}
+
+/// Writes a complete cassette: the first `cover_len` bytes of `cover`, the TOC, then the
+/// audio of each entry in order, sealed with a CRC32. Returns the CRC. Each `(file, offsets)`
+/// pair gives where the next entries' audio starts in `file`; padding is laid out afresh
+/// for `header`'s alignment. The output is only moved into place once sealed, so it may
+/// replace one of the sources.
+fn write_cassette(output_path: &str, cover: &mut File, cover_len: u64, mut header: CassetteHeader, audio: &mut [(&mut File, Vec<u64>)]) -> Result<u32, String> {
+	let (pending, output) = PendingFile::create(output_path)?;
+	let mut writer = BufWriter::new(output);
+	let mut hasher = Hasher::new();
+
+	cover.seek(SeekFrom::Start(0))
+		.and_then(|_| transfer(&mut cover.take(cover_len), &mut writer, &mut hasher))
+		.map_err(|e| format!("Failed to copy cover art: {}", e))?;
+
+	// Written again once the track CRCs are known (same size)
+	let toc_start = writer.stream_position()
+		.and_then(|pos| writer.write_all(&header.to_bytes()).map(|_| pos))
+		.map_err(|e| format!("Failed to write the TOC: {}", e))?;
+
+	// Tracks are hashed one by one for their CRCs; the padding only counts towards the seal
+	let mut audio_hasher = Hasher::new();
+	let align = header.align;
+	let mut entries = header.entries.iter_mut();
+	for (file, offsets) in audio.iter_mut() {
+		for &offset in offsets.iter() {
+			let Some(entry) = entries.next() else {
+				return Err("The TOC doesn't match the audio being copied.".to_string());
+			};
+			let copy_error = |e: io::Error| format!("Failed to copy audio: {}", e);
+			let padding = writer.stream_position().map(|position| padding_before(position, align)).map_err(copy_error)?;
+			transfer(&mut io::repeat(0).take(padding), &mut writer, &mut audio_hasher).map_err(copy_error)?;
+			file.seek(SeekFrom::Start(offset)).map_err(copy_error)?;
+			let mut track_hasher = Hasher::new();
+			let copied = transfer(&mut (&mut **file).take(entry.size), &mut writer, &mut track_hasher).map_err(copy_error)?;
+			if copied != entry.size {
+				return Err("A source cassette ended before all of its audio was copied.".to_string());
+			}
+			audio_hasher.combine(&track_hasher);
+			entry.crc = Some(track_hasher.finalize());
+		}
+	}
+	if entries.next().is_some() {
+		return Err("The TOC doesn't match the audio being copied.".to_string());
+	}
+
+	let toc = header.to_bytes();
+	writer.seek(SeekFrom::Start(toc_start))
+		.and_then(|_| writer.write_all(&toc))
+		.and_then(|_| writer.seek(SeekFrom::End(0)))
+		.map_err(|e| format!("Failed to write the TOC: {}", e))?;
+	hasher.update(&toc);
+	hasher.combine(&audio_hasher);
+
+	let crc = hasher.finalize();
+	let region = SealedRegion::of_layout(toc_start, &header).ok_or("The cassette is too large to seal.")?;
+	write_seal(&mut writer, region, crc).map_err(|e| format!("Failed to seal the cassette: {}", e))?;
+	pending.commit(writer)?;
+	Ok(crc)
+}
+
+/// Opens a cassette, checks its CRC32 seal and reads its layout.
+fn open_source(path: &str) -> Result<Source, String> {
+	let mut file = open_file(path)?;
+	let mut line = Progress::new(&format!("Verifying {}", path));
+	let seal = Cassette::verify(&mut file, &mut |done, total| line.update(done, total))?;
+	drop(line);
+	if !seal.is_intact() {
+		return Err("This cassette has been damaged! Checksum mismatch detected.".to_string());
+	}
+
+	let layout = CassetteLayout::read(&mut file)?;
+	if layout.header.entries.is_empty() {
+		return Err(BLANK_CASSETTE.to_string());
+	}
+	Ok(Source { path: path.to_string(), file, layout })
+}
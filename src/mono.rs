@@ -0,0 +1,89 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// MONO MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Downmix for `--mono`, wrapped as a rodio `Source`. Every sample of a frame is
+// replaced by the average of that frame's channels, so a single speaker (or a
+// listener who only hears one side) gets the whole mix, and a recording with
+// one bad channel is at least audible on both. The channel count is kept, so
+// the output device and the balance control behave exactly as before. Disabled
+// (the default) or on mono tracks, samples pass through untouched.
+
+use std::time::Duration;
+use rodio::source::SeekError;
+use rodio::{ChannelCount, Sample, SampleRate, Source};
+
+/// A rodio source that averages the channels of every frame when enabled.
+pub struct Mono<S: Source> {
+	input: S,
+	enabled: bool,
+	/// Average of the frame being played.
+	value: Sample,
+	/// Copies of `value` still to be played for this frame.
+	remaining: u16,
+}
+
+impl<S: Source> Mono<S> {
+	pub fn new(input: S, enabled: bool) -> Self {
+		Mono { input, enabled, value: 0.0, remaining: 0 }
+	}
+}
+
+impl<S: Source> Iterator for Mono<S> {
+	type Item = Sample;
+
+	fn next(&mut self) -> Option<Sample> {
+		if self.remaining > 0 {
+			self.remaining -= 1;
+			return Some(self.value);
+		}
+
+		let channels = self.input.channels();
+		let first = self.input.next()?;
+		if !self.enabled || channels < 2 {
+			return Some(first);
+		}
+
+		// A track that ends mid-frame averages whatever channels it still had
+		let mut sum = first;
+		let mut count = 1;
+		while count < channels {
+			let Some(sample) = self.input.next() else { break };
+			sum += sample;
+			count += 1;
+		}
+		self.value = sum / count as Sample;
+		self.remaining = count - 1;
+		Some(self.value)
+	}
+
+	fn size_hint(&self) -> (usize, Option<usize>) {
+		let (low, high) = self.input.size_hint();
+		let pending = self.remaining as usize;
+		(low + pending, high.map(|high| high + pending))
+	}
+}
+
+impl<S: Source> Source for Mono<S> {
+	fn current_span_len(&self) -> Option<usize> {
+		self.input.current_span_len().map(|len| len + self.remaining as usize)
+	}
+
+	fn channels(&self) -> ChannelCount {
+		self.input.channels()
+	}
+
+	fn sample_rate(&self) -> SampleRate {
+		self.input.sample_rate()
+	}
+
+	fn total_duration(&self) -> Option<Duration> {
+		self.input.total_duration()
+	}
+
+	fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+		self.input.try_seek(pos)?;
+		self.remaining = 0;
+		Ok(())
+	}
+}
@@ -0,0 +1,135 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// MPRIS MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Media key and desktop widget integration through the MPRIS D-Bus interface.
+// The D-Bus server runs on its own thread with a small async executor; players
+// poll `MediaControls::try_recv` for Play/Pause/Next/Previous/Stop requests and
+// call `publish` to keep the desktop's "now playing" metadata in sync.
+//
+// Only available on Linux with the `mpris` feature (on by default). Everywhere
+// else `MediaControls::start` returns `None` and players carry on without it.
+
+/// A control request from the desktop (media keys, panel widgets, playerctl).
+#[cfg_attr(not(all(target_os = "linux", feature = "mpris")), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaCommand {
+	Play,
+	Pause,
+	PlayPause,
+	Next,
+	Previous,
+	Stop,
+}
+
+/// Playback status as reported to the desktop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaStatus {
+	Playing,
+	Paused,
+	Stopped,
+}
+
+/// Metadata for the track currently loaded in the player.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NowPlaying {
+	pub title: String,
+	pub artist: String,
+	pub duration_secs: u64,
+}
+
+/// Handle to the running MPRIS server. Dropping it shuts the server down.
+pub struct MediaControls {
+	#[cfg(all(target_os = "linux", feature = "mpris"))]
+	commands: std::sync::mpsc::Receiver<MediaCommand>,
+	#[cfg(all(target_os = "linux", feature = "mpris"))]
+	updates: async_channel::Sender<(MediaStatus, Option<NowPlaying>)>,
+	/// Last state sent, so unchanged state isn't re-broadcast on every tick
+	published: Option<(MediaStatus, Option<NowPlaying>)>,
+}
+
+impl MediaControls {
+	/// Registers Rewind on the session bus. Returns `None` if there is no session bus.
+	#[cfg(all(target_os = "linux", feature = "mpris"))]
+	pub fn start() -> Option<Self> {
+		use std::sync::mpsc;
+		use mpris_server::{Metadata, PlaybackStatus, Player, Time};
+
+		let (command_tx, commands) = mpsc::channel();
+		let (updates, update_rx) = async_channel::unbounded::<(MediaStatus, Option<NowPlaying>)>();
+		let (ready_tx, ready_rx) = mpsc::channel();
+
+		std::thread::spawn(move || futures_lite::future::block_on(async move {
+			let player = Player::builder("Rewind")
+				.identity("Rewind.png")
+				.can_play(true)
+				.can_pause(true)
+				.can_go_next(true)
+				.can_go_previous(true)
+				.build()
+				.await;
+			let player = match player {
+				Ok(player) => { let _ = ready_tx.send(true); player }
+				Err(_) => { let _ = ready_tx.send(false); return; }
+			};
+
+			let forward = |command: MediaCommand| {
+				let tx = command_tx.clone();
+				move |_: &Player| { let _ = tx.send(command); }
+			};
+			player.connect_play(forward(MediaCommand::Play));
+			player.connect_pause(forward(MediaCommand::Pause));
+			player.connect_play_pause(forward(MediaCommand::PlayPause));
+			player.connect_next(forward(MediaCommand::Next));
+			player.connect_previous(forward(MediaCommand::Previous));
+			player.connect_stop(forward(MediaCommand::Stop));
+
+			// Push state changes from the player until it drops its `MediaControls`
+			let sync = async {
+				while let Ok((status, track)) = update_rx.recv().await {
+					let metadata = match track {
+						Some(track) => Metadata::builder()
+							.title(track.title)
+							.artist([track.artist])
+							.length(Time::from_secs(track.duration_secs as i64))
+							.build(),
+						None => Metadata::new(),
+					};
+					let _ = player.set_metadata(metadata).await;
+					let _ = player.set_playback_status(match status {
+						MediaStatus::Playing => PlaybackStatus::Playing,
+						MediaStatus::Paused => PlaybackStatus::Paused,
+						MediaStatus::Stopped => PlaybackStatus::Stopped,
+					}).await;
+				}
+			};
+			futures_lite::future::or(player.run(), sync).await;
+		}));
+
+		ready_rx.recv().ok().filter(|&ok| ok)?;
+		Some(MediaControls { commands, updates, published: None })
+	}
+
+	/// MPRIS is unavailable on this platform/build.
+	#[cfg(not(all(target_os = "linux", feature = "mpris")))]
+	pub fn start() -> Option<Self> {
+		None
+	}
+
+	/// Next pending control request, if any.
+	pub fn try_recv(&self) -> Option<MediaCommand> {
+		#[cfg(all(target_os = "linux", feature = "mpris"))]
+		return self.commands.try_recv().ok();
+		#[cfg(not(all(target_os = "linux", feature = "mpris")))]
+		None
+	}
+
+	/// Reports the current status and track to the desktop (only when they changed).
+	pub fn publish(&mut self, status: MediaStatus, track: Option<NowPlaying>) {
+		let state = (status, track);
+		if self.published.as_ref() == Some(&state) { return; }
+		#[cfg(all(target_os = "linux", feature = "mpris"))]
+		let _ = self.updates.try_send(state.clone());
+		self.published = Some(state);
+	}
+}
@@ -0,0 +1,23 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// NOTIFY MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Desktop notifications for track changes (`--notify`), so a minimized player
+// still tells you what's on. Sent from a background thread because some
+// platforms block on the notification daemon; failures (no daemon, unsupported
+// platform) are silently ignored.
+
+use std::thread;
+use notify_rust::Notification;
+
+/// Shows "Now playing: Artist - Title" as an OS notification, if the platform supports it.
+pub fn notify_now_playing(artist: &str, title: &str) {
+	let body = format!("{} - {}", artist, title);
+	thread::spawn(move || {
+		let _ = Notification::new()
+			.summary("Now playing")
+			.body(&body)
+			.appname("Rewind.png")
+			.show();
+	});
+}
@@ -0,0 +1,125 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// OGG MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Detects chained Ogg files: several complete streams stored one after the
+// other, each opening with its own beginning-of-stream (BOS) page. Some
+// encoders and podcast tools write them, but decoders (rodio included) and
+// tag readers usually stop after the first link, so the rest of the audio is
+// silently lost. `record` refuses them and `inspect` reports the length of
+// every link together.
+//
+// Only page headers are read. Streams that are multiplexed (several BOS pages
+// before any data) count as one link.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::time::Duration;
+
+/// Header type flag marking the first page of a logical stream.
+const BOS_FLAG: u8 = 0x02;
+/// Opus granule positions always count 48 kHz samples.
+const OPUS_GRANULE_RATE: u64 = 48_000;
+
+/// What a chained Ogg file holds.
+pub struct OggChain {
+	/// Number of streams played one after the other (1 for an ordinary file).
+	pub links: usize,
+	/// Combined length of every link, when each link's codec is known.
+	pub duration: Option<Duration>,
+}
+
+/// Timing of one link, from its identification header and last granule position.
+struct Link {
+	serial: u32,
+	sample_rate: Option<u64>,
+	pre_skip: u64,
+	last_granule: u64,
+}
+
+impl Link {
+	fn duration(&self) -> Option<Duration> {
+		let rate = self.sample_rate.filter(|&rate| rate > 0)?;
+		let samples = self.last_granule.saturating_sub(self.pre_skip);
+		Some(Duration::from_secs_f64(samples as f64 / rate as f64))
+	}
+}
+
+/// Walks the page headers of an Ogg stream starting at the reader's position 0.
+/// Returns `None` if it isn't an Ogg stream at all.
+pub fn read_chain<R: Read + Seek>(reader: &mut R) -> Option<OggChain> {
+	reader.seek(SeekFrom::Start(0)).ok()?;
+	let mut links: Vec<Link> = Vec::new();
+	let mut in_headers = false;
+
+	loop {
+		let mut header = [0u8; 27];
+		if reader.read_exact(&mut header).is_err() || &header[..4] != b"OggS" {
+			break;
+		}
+		let flags = header[5];
+		let granule = u64::from_le_bytes(header[6..14].try_into().ok()?);
+		let serial = u32::from_le_bytes(header[14..18].try_into().ok()?);
+		let mut lacing = vec![0u8; header[26] as usize];
+		if reader.read_exact(&mut lacing).is_err() {
+			break;
+		}
+		let body_len: u64 = lacing.iter().map(|&l| l as u64).sum();
+
+		let mut body_read = 0;
+		if flags & BOS_FLAG != 0 {
+			// A BOS page after data starts a new link; several in a row are multiplexed streams
+			if !in_headers {
+				let mut start = [0u8; 19];
+				let n = read_up_to(reader, &mut start[..body_len.min(19) as usize]);
+				body_read = n as u64;
+				let (sample_rate, pre_skip) = identify(&start[..n]);
+				links.push(Link { serial, sample_rate, pre_skip, last_granule: 0 });
+			}
+			in_headers = true;
+		} else {
+			in_headers = false;
+			// Pages that finish no packet carry granule -1
+			if let Some(link) = links.last_mut()
+				&& link.serial == serial
+				&& granule != u64::MAX
+			{
+				link.last_granule = link.last_granule.max(granule);
+			}
+		}
+
+		if reader.seek(SeekFrom::Current((body_len - body_read) as i64)).is_err() {
+			break;
+		}
+	}
+
+	if links.is_empty() {
+		return None;
+	}
+	let duration = links.iter().map(Link::duration).sum::<Option<Duration>>();
+	Some(OggChain { links: links.len(), duration })
+}
+
+/// Sample rate (for granule positions) and pre-skip of a stream, from the start of its
+/// identification packet. Unknown codecs get no rate.
+fn identify(packet: &[u8]) -> (Option<u64>, u64) {
+	if packet.starts_with(b"\x01vorbis") && packet.len() >= 16 {
+		let rate = u32::from_le_bytes([packet[12], packet[13], packet[14], packet[15]]);
+		(Some(rate as u64), 0)
+	} else if packet.starts_with(b"OpusHead") && packet.len() >= 12 {
+		(Some(OPUS_GRANULE_RATE), u16::from_le_bytes([packet[10], packet[11]]) as u64)
+	} else {
+		(None, 0)
+	}
+}
+
+/// Reads as much of `buf` as the reader has, returning how many bytes were read.
+fn read_up_to<R: Read>(reader: &mut R, buf: &mut [u8]) -> usize {
+	let mut filled = 0;
+	while filled < buf.len() {
+		match reader.read(&mut buf[filled..]) {
+			Ok(0) | Err(_) => break,
+			Ok(n) => filled += n,
+		}
+	}
+	filled
+}
@@ -6,173 +6,557 @@
 // plays them using rodio. Supports random track selection for testing.
 
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom, Cursor};
-use rand::Rng;
-use rodio::{Decoder, OutputStreamBuilder, Sink};
-use lofty::file::{AudioFile, TaggedFileExt};
+use std::path::Path;
+use std::io::{IsTerminal, Read, Seek, SeekFrom, Cursor};
+use rand::{Rng, SeedableRng};
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use std::time::Duration;
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use lofty::file::{AudioFile, FileType, TaggedFileExt};
 use lofty::probe::Probe;
 use lofty::tag::Accessor;
-use crate::io::{open_file, find_iend, format_duration};
-use crate::logger::{log, LogLevel};
+use crate::io::{open_file, format_duration, parse_position, track_buffer};
+use crate::constants::FLIP_PAUSE;
+use crate::format::{CassetteLayout, Side, TocEntry, BLANK_CASSETTE};
+use crate::logger::{log, LogLevel, trace_event, trace_span};
+use crate::mono::Mono;
+use crate::notify::notify_now_playing;
+use crate::history::{record_play, track_plays};
+
+/// Short tape clunk with a little hiss, played between tracks with `--tape-sfx`.
+pub const TAPE_SFX: &[u8] = include_bytes!("../assets/sfx/tape-clunk.wav");
+
+/// Why a track can't be played. Every player (CLI, TUI, GUI, daemon) reports failures
+/// through this, so the same problem is always explained the same way.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlaybackError {
+	/// No output device could be opened.
+	NoAudioDevice(String),
+	/// The track was read, but no decoder understands it.
+	UnsupportedCodec(String),
+	/// The cassette file couldn't be read.
+	FileRead(String),
+}
+
+impl std::fmt::Display for PlaybackError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			PlaybackError::NoAudioDevice(e) => write!(f, "No audio device ({}). Check your speakers or run `rewind doctor`.", e),
+			PlaybackError::UnsupportedCodec(e) => write!(f, "Unsupported or damaged audio ({}).", e),
+			PlaybackError::FileRead(e) => write!(f, "Cannot read the cassette ({}).", e),
+		}
+	}
+}
 
-/// Helper function to load cassette TOC and track data
-fn load_cassette_toc(path: &str) -> Option<(File, Vec<(String, u64)>, Vec<u64>)> {
+/// Helper function to load cassette TOC and track offsets.
+/// With `strict`, files that aren't unambiguously cassettes are refused.
+fn load_cassette_toc(path: &str, strict: bool) -> Option<(File, Vec<TocEntry>, Vec<u64>)> {
 	let mut file = match open_file(path) {
 		Ok(f) => f,
 		Err(e) => { log(LogLevel::Error, &e); return None; }
 	};
 
-	// Find TOC position (after IEND)
-	let toc_pos = match find_iend(&mut file) {
-		Some(pos) => pos,
-		None => { log(LogLevel::Error, "This cassette appears to be blank. No IEND chunk found."); return None; }
+	let layout = match CassetteLayout::read(&mut file) {
+		Ok(layout) => layout,
+		Err(e) => { log(LogLevel::Error, &e); return None; }
 	};
 
-	// Read TOC
-	file.seek(SeekFrom::Start(toc_pos)).unwrap();
-
-	let mut count_buf = [0u8; 4];
-	file.read_exact(&mut count_buf).unwrap();
-	let track_count = u32::from_le_bytes(count_buf) as usize;
+	if strict && let Err(e) = layout.check_strict(&mut file) {
+		log(LogLevel::Error, &e);
+		return None;
+	}
 
-	if track_count == 0 {
-		log(LogLevel::Error, "This cassette is blank. No tracks found.");
+	if layout.header.entries.is_empty() {
+		log(LogLevel::Warning, BLANK_CASSETTE);
 		return None;
 	}
 
-	// Parse TOC entries
-	let mut entries: Vec<(String, u64)> = Vec::new();
-	for _ in 0..track_count {
-		let mut len_buf = [0u8; 4];
-		file.read_exact(&mut len_buf).unwrap();
-		let name_len = u32::from_le_bytes(len_buf) as usize;
+	let offsets = layout.track_offsets();
+	Some((file, layout.header.entries, offsets))
+}
 
-		let mut name_buf = vec![0u8; name_len];
-		file.read_exact(&mut name_buf).unwrap();
-		let name = String::from_utf8_lossy(&name_buf).to_string();
+/// Announces the end of a side. Waits for Enter when attached to a terminal,
+/// unless `auto_flip` is set (or stdin isn't a terminal), in which case it just pauses.
+fn flip_the_tape(finished: Side, auto_flip: bool) {
+	log(LogLevel::Info, &format!("{} finished. ⟳ Flip the tape...", finished));
+	if !auto_flip && std::io::stdin().is_terminal() {
+		log(LogLevel::Info, "Press Enter to continue...");
+		let _ = std::io::stdin().read_line(&mut String::new());
+	} else {
+		std::thread::sleep(FLIP_PAUSE);
+	}
+}
 
-		let mut size_buf = [0u8; 8];
-		file.read_exact(&mut size_buf).unwrap();
-		let size = u64::from_le_bytes(size_buf);
+/// Options for `play_all`.
+pub struct PlayAllOptions {
+	/// Silence between tracks on the same side.
+	pub gap: Duration,
+	/// Play a short tape clunk/hiss between tracks instead of pure silence.
+	pub tape_sfx: bool,
+	/// Flip the tape between sides without waiting for Enter.
+	pub auto_flip: bool,
+	/// Show a desktop notification when each track starts.
+	pub notify: bool,
+	/// Refuse files that aren't unambiguously cassettes.
+	pub strict: bool,
+	/// Output device to play through (the default device when unset).
+	pub device: Option<String>,
+	/// 1-based track to start at; the first track when unset.
+	pub from: Option<usize>,
+	/// Start over from the first track after the last one, until stopped.
+	pub repeat: bool,
+	/// Play every track once in random order (reshuffled for each pass with `repeat`).
+	pub shuffle: bool,
+	/// Seed for the shuffle, so the same seed always gives the same order.
+	pub seed: Option<u64>,
+	/// Playback volume in percent.
+	pub volume: u8,
+	/// Downmix stereo tracks to mono.
+	pub mono: bool,
+}
 
-		entries.push((name, size));
+/// Names of the audio host's output devices, in the order it lists them.
+pub fn output_device_names() -> Vec<String> {
+	rodio::cpal::default_host().output_devices()
+		.map(|devices| devices.filter_map(|d| d.name().ok()).collect())
+		.unwrap_or_default()
+}
+
+/// Opens an output stream on the device called `device` (case-insensitive), or on the
+/// default device when `device` is `None`. A name that matches no device falls back to
+/// the default device; the second value is then a warning for the user.
+pub fn open_output_stream(device: Option<&str>) -> Result<(OutputStream, Option<String>), PlaybackError> {
+	let found = device.and_then(|name| rodio::cpal::default_host().output_devices().ok()?
+		.find(|d| d.name().is_ok_and(|n| n.eq_ignore_ascii_case(name))));
+	let warning = match (device, &found) {
+		(Some(name), None) => Some(format!("Output device '{}' not found. Using the default device.", name)),
+		_ => None,
+	};
+	let stream = match found {
+		Some(d) => OutputStreamBuilder::from_device(d).and_then(|builder| builder.open_stream_or_fallback()),
+		None => OutputStreamBuilder::open_default_stream(),
+	};
+	stream.map(|stream| (stream, warning)).map_err(|e| PlaybackError::NoAudioDevice(e.to_string()))
+}
+
+/// Opens the output device with a sink attached at `volume` percent. Both must be kept
+/// alive during playback.
+fn open_output(device: Option<&str>, volume: u8) -> Option<(OutputStream, Sink)> {
+	match open_output_stream(device) {
+		Ok((stream, warning)) => {
+			if let Some(warning) = warning {
+				log(LogLevel::Warning, &warning);
+			}
+			let sink = Sink::connect_new(stream.mixer());
+			sink.set_volume(volume as f32 / 100.0);
+			Some((stream, sink))
+		}
+		Err(e) => { log(LogLevel::Error, &e.to_string()); None }
 	}
+}
 
-	// Calculate track offsets
-	let audio_start = file.stream_position().unwrap();
-	let mut offsets: Vec<u64> = Vec::new();
-	let mut offset = audio_start;
-	for (_, size) in &entries {
-		offsets.push(offset);
-		offset += size;
+/// Plays the bundled tape clunk/hiss on `sink` and waits for it to finish.
+fn play_tape_sfx(sink: &Sink) {
+	if let Ok(source) = Decoder::new(Cursor::new(TAPE_SFX)) {
+		sink.append(source);
+		sink.sleep_until_end();
 	}
+}
 
-	Some((file, entries, offsets))
+/// Turns a 1-based `--track` into a 0-based index, clamping (with a warning) when out of range.
+pub fn start_index(requested: Option<usize>, track_count: usize) -> usize {
+	let Some(track) = requested else { return 0 };
+	let last = track_count.max(1);
+	if track == 0 || track > last {
+		let clamped = track.clamp(1, last);
+		log(LogLevel::Warning, &format!("There is no track {} on this cassette ({} track(s)). Starting at track {}.", track, track_count, clamped));
+		return clamped - 1;
+	}
+	track - 1
 }
 
-/// Helper function to play a single track
-fn play_track(file: &mut File, entries: &[(String, u64)], offsets: &[u64], track_idx: usize, show_selection: bool) -> bool {
-	let (ref name, size) = entries[track_idx];
-	let track_offset = offsets[track_idx];
+/// The format hint for a track: its stored codec (format v12+), or on older cassettes the
+/// extension of its name, lowercased. Names picked with `--names` or `--name-from-tags` may
+/// have no extension or the wrong one, so the name is only a fallback.
+pub fn format_hint(entry: &TocEntry) -> Option<String> {
+	match entry.codec {
+		Some(codec) => codec_hint(codec).map(str::to_string),
+		None => Path::new(&entry.name).extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase()),
+	}
+}
 
-	if show_selection {
-		log(LogLevel::Info, &format!("Selected track {} of {}: {}", track_idx + 1, entries.len(), name));
+/// The extension symphonia knows a Lofty file type by.
+pub fn codec_hint(codec: FileType) -> Option<&'static str> {
+	match codec {
+		FileType::Flac => Some("flac"),
+		FileType::Mpeg => Some("mp3"),
+		FileType::Mp4 => Some("m4a"),
+		FileType::Aac => Some("aac"),
+		FileType::Vorbis => Some("ogg"),
+		FileType::Wav => Some("wav"),
+		FileType::Aiff => Some("aiff"),
+		FileType::Opus => Some("opus"),
+		FileType::Speex => Some("spx"),
+		FileType::WavPack => Some("wv"),
+		FileType::Ape => Some("ape"),
+		FileType::Mpc => Some("mpc"),
+		_ => None,
 	}
+}
+
+/// Builds a decoder for one track's bytes. Telling symphonia the exact length and that the
+/// data is seekable lets it report durations and seek within FLAC/MP3/OGG tracks. With a
+/// `hint` (see `format_hint`) the matching demuxer is tried first instead of probing every
+/// format; without one, or when the hint is wrong, the format is sniffed as usual.
+pub fn decode_with_hint<R: Read + Seek + Send + Sync + 'static>(data: R, byte_len: u64, hint: Option<&str>) -> Result<Decoder<R>, PlaybackError> {
+	let _span = trace_span!("decode", byte_len, hint = ?hint);
+	let builder = Decoder::builder()
+		.with_data(data)
+		.with_byte_len(byte_len)
+		.with_seekable(true);
+	match hint {
+		Some(hint) => builder.with_hint(hint),
+		None => builder,
+	}
+	.build()
+	.map_err(|e| PlaybackError::UnsupportedCodec(e.to_string()))
+}
+
+/// A random number generator seeded with `seed`, or from the OS without one.
+fn seeded_rng(seed: Option<u64>) -> StdRng {
+	match seed {
+		Some(seed) => StdRng::seed_from_u64(seed),
+		None => StdRng::from_os_rng(),
+	}
+}
+
+/// A random track index, or `None` on a blank cassette (`random_range(0..0)` would panic).
+fn random_track(rng: &mut StdRng, track_count: usize) -> Option<usize> {
+	(track_count > 0).then(|| rng.random_range(0..track_count))
+}
 
-	if show_selection {
-		log(LogLevel::Info, &format!("Selected track {} of {}: {}", track_idx + 1, entries.len(), name));
+/// The indices `0..len` in random order (a Fisher–Yates shuffle).
+fn shuffled(len: usize, rng: &mut StdRng) -> Vec<usize> {
+	let mut order: Vec<usize> = (0..len).collect();
+	order.shuffle(rng);
+	order
+}
+
+/// Options for `play_one`.
+pub struct PlayOneOptions {
+	/// 1-based track to play; a random one is picked when unset.
+	pub track: Option<usize>,
+	/// Seed for the random pick, so the same seed always plays the same track.
+	pub seed: Option<u64>,
+	/// Favor tracks that were played less often (see `history`) in the random pick.
+	pub smart: bool,
+	/// Where to start, as "M:SS", seconds or a percentage of the track ("50%").
+	pub seek: Option<String>,
+	/// Play the track again every time it ends, until stopped. The seek only applies the first time.
+	pub repeat_track: bool,
+	/// Show a desktop notification when the track starts.
+	pub notify: bool,
+	/// Refuse files that aren't unambiguously cassettes.
+	pub strict: bool,
+	/// Output device to play through (the default device when unset).
+	pub device: Option<String>,
+	/// Playback volume in percent.
+	pub volume: u8,
+	/// Downmix stereo tracks to mono.
+	pub mono: bool,
+}
+
+/// How each track is played, shared by `play_one` and `play_all`.
+#[derive(Clone, Copy)]
+struct TrackSettings<'a> {
+	/// The cassette's path, for the play history.
+	cassette: &'a str,
+	/// Show a desktop notification when the track starts.
+	notify: bool,
+	/// Downmix to mono.
+	mono: bool,
+}
+
+/// Helper function to play a single track, optionally starting at `seek` (see `parse_position`).
+/// Returns `Ok(false)` if the track couldn't be decoded (later tracks may still play),
+/// and `Err` if the cassette itself can no longer be read or `seek` can't be honoured.
+fn play_track(sink: &Sink, file: &mut File, entries: &[TocEntry], offsets: &[u64], track_idx: usize, settings: TrackSettings, seek: Option<&str>) -> Result<bool, String> {
+	let TocEntry { ref name, size, .. } = entries[track_idx];
+	let track_offset = offsets[track_idx];
+	let _span = trace_span!("play_track", track = track_idx + 1, name = %name, offset = track_offset, size);
+
+	if size == 0 {
+		log(LogLevel::Warning, &format!("'{}' is an empty track, skipping it.", name));
+		return Ok(false);
 	}
 
 	// Read track into memory
-	file.seek(SeekFrom::Start(track_offset)).unwrap();
-	let mut audio_data = vec![0u8; size as usize];
-	file.read_exact(&mut audio_data).unwrap();
+	let mut audio_data = track_buffer(size).map_err(|e| format!("'{}': {}", name, e))?;
+	file.seek(SeekFrom::Start(track_offset))
+		.and_then(|_| file.read_exact(&mut audio_data))
+		.map_err(|e| PlaybackError::FileRead(e.to_string()).to_string())?;
+	trace_event!("track read into memory");
 
 	// Get metadata for display
-	let (artist, title, duration_secs) = match Probe::new(Cursor::new(&audio_data)).guess_file_type() {
+	let (artist, title, duration) = match Probe::new(Cursor::new(&audio_data)).guess_file_type() {
 		Ok(probe) => match probe.read() {
 			Ok(tagged) => {
 				let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
 				let artist = tag.and_then(|t| t.artist()).map(|s| s.to_string()).unwrap_or_else(|| "Unknown".into());
 				let title = tag.and_then(|t| t.title()).map(|s| s.to_string()).unwrap_or_else(|| name.clone());
-				let duration = tagged.properties().duration().as_secs();
-				(artist, title, duration)
+				(artist, title, tagged.properties().duration())
 			},
-			Err(_) => ("Unknown".into(), name.clone(), 0)
+			Err(_) => ("Unknown".into(), name.clone(), Duration::ZERO)
 		},
-		Err(_) => ("Unknown".into(), name.clone(), 0)
+		Err(_) => ("Unknown".into(), name.clone(), Duration::ZERO)
 	};
 
-	log(LogLevel::Success, &format!("▶ Now Playing: {} - {} [{}]", artist, title, format_duration(duration_secs)));
-
-	// Play audio
-	let stream_handle = match OutputStreamBuilder::open_default_stream() {
-		Ok(s) => s,
-		Err(e) => { log(LogLevel::Error, &format!("Cannot access audio output device: {}", e)); return false; }
+	// Work out the start position before anything is heard
+	let start_at = match seek {
+		Some(text) => {
+			let position = parse_position(text, duration.as_secs_f64()).ok_or_else(|| if text.trim().ends_with('%') && duration.is_zero() {
+				format!("Cannot seek to '{}': the length of '{}' is unknown. Use M:SS instead.", text, name)
+			} else {
+				format!("Invalid position '{}'. Use M:SS, seconds, or a percentage like 50%.", text)
+			})?;
+			if !duration.is_zero() && position >= duration {
+				return Err(format!("Position {} is past the end of '{}' ({}).", format_duration(position.as_secs()), name, format_duration(duration.as_secs())));
+			}
+			Some(position)
+		}
+		None => None,
 	};
 
-	let sink = Sink::connect_new(&stream_handle.mixer());
+	log(LogLevel::Success, &format!("▶ Now Playing: {} - {} [{}]", artist, title, format_duration(duration.as_secs())));
+	if settings.notify {
+		notify_now_playing(&artist, &title);
+	}
 
-	let cursor = Cursor::new(audio_data);
-	let source = match Decoder::new(cursor) {
+	// Play audio
+	let source = match decode_with_hint(Cursor::new(audio_data), size, format_hint(&entries[track_idx]).as_deref()) {
 		Ok(s) => s,
-		Err(e) => { log(LogLevel::Error, &format!("This track is damaged and cannot be played: {}", e)); return false; }
+		Err(e) => { log(LogLevel::Warning, &format!("Cannot play '{}': {}", name, e)); return Ok(false); }
 	};
 
-	sink.append(source);
+	sink.append(Mono::new(source, settings.mono));
+	if let Err(e) = record_play(Path::new(settings.cassette), name) {
+		log(LogLevel::Warning, &e);
+	}
+
+	if let Some(position) = start_at {
+		if let Err(e) = sink.try_seek(position) {
+			sink.clear();
+			return Err(format!("Cannot seek within '{}': {}", name, e));
+		}
+		log(LogLevel::Info, &format!("Starting at {}.", format_duration(position.as_secs())));
+	}
 
 	// Block until done
 	sink.sleep_until_end();
 
-	true
+	Ok(true)
 }
 
-/// Plays a random track from the cassette file.
-/// Blocks until the track finishes or Ctrl+C is pressed.
-pub fn play_random(path: &str) {
+/// Plays one track from the cassette file: the requested one, or a random pick (favoring the
+/// least played tracks with `smart`). The same `seed`
+/// always picks the same track; without one the pick is seeded from the OS.
+/// Blocks until the track finishes (for good, with `repeat_track`) or Ctrl+C is pressed.
+pub fn play_one(path: &str, options: &PlayOneOptions) {
 	log(LogLevel::Info, &format!("Loading cassette: {}", path));
 
-	let (mut file, entries, offsets) = match load_cassette_toc(path) {
+	let (mut file, entries, offsets) = match load_cassette_toc(path, options.strict) {
 		Some(data) => data,
 		None => return,
 	};
 
-	// Pick random track
-	let mut rng = rand::rng();
-	let track_idx = rng.random_range(0..entries.len());
+	let picked = match options.track {
+		Some(_) => Some(start_index(options.track, entries.len())),
+		None if options.smart => {
+			// Weight each track by the inverse of its play count, so the neglected ones come up more
+			let plays = track_plays(Path::new(path), entries.iter().map(|entry| entry.name.as_str()));
+			let weights = plays.iter().map(|&count| 1.0 / (count as f64 + 1.0));
+			match WeightedIndex::new(weights) {
+				Ok(weighted) => Some(weighted.sample(&mut seeded_rng(options.seed))),
+				Err(_) => random_track(&mut seeded_rng(options.seed), entries.len()),
+			}
+		}
+		None => random_track(&mut seeded_rng(options.seed), entries.len()),
+	};
+	let Some(track_idx) = picked.filter(|&idx| idx < entries.len()) else {
+		log(LogLevel::Warning, BLANK_CASSETTE);
+		return;
+	};
+	log(LogLevel::Info, &format!("Selected track {} of {}: {}", track_idx + 1, entries.len(), entries[track_idx].name));
 
 	log(LogLevel::Info, "Press Ctrl+C to stop.");
-	
-	if play_track(&mut file, &entries, &offsets, track_idx, true) {
-		log(LogLevel::Success, "Playback finished.");
+
+	let Some((_stream, sink)) = open_output(options.device.as_deref(), options.volume) else { return };
+	let mut seek = options.seek.as_deref();
+	loop {
+		match play_track(&sink, &mut file, &entries, &offsets, track_idx, TrackSettings { cassette: path, notify: options.notify, mono: options.mono }, seek) {
+			Ok(true) if options.repeat_track => log(LogLevel::Info, "⟲ Repeating the track..."),
+			Ok(true) => { log(LogLevel::Success, "Playback finished."); return; }
+			Ok(false) => return,
+			Err(e) => { log(LogLevel::Error, &e); return; }
+		}
+		seek = None;
 	}
 }
 
-/// Plays all tracks sequentially from the cassette file.
+/// Plays all tracks sequentially from the cassette file, over and over with `repeat`.
 /// Blocks until all tracks finish or Ctrl+C is pressed.
-pub fn play_all(path: &str) {
+pub fn play_all(path: &str, options: &PlayAllOptions) {
 	log(LogLevel::Info, &format!("Loading cassette: {}", path));
 
-	let (mut file, entries, offsets) = match load_cassette_toc(path) {
+	let (mut file, entries, offsets) = match load_cassette_toc(path, options.strict) {
 		Some(data) => data,
 		None => return,
 	};
 
-	log(LogLevel::Info, &format!("Playing all {} track(s) in sequence...", entries.len()));
+	let first = start_index(options.from, entries.len());
+	let mut rng = seeded_rng(options.seed);
+	let mut order: Vec<usize> = if options.shuffle { shuffled(entries.len(), &mut rng) } else { (first..entries.len()).collect() };
+	if options.shuffle {
+		log(LogLevel::Info, &format!("Playing all {} track(s) in shuffled order...", entries.len()));
+	} else if first == 0 {
+		log(LogLevel::Info, &format!("Playing all {} track(s) in sequence...", entries.len()));
+	} else {
+		log(LogLevel::Info, &format!("Playing tracks {} to {} in sequence...", first + 1, entries.len()));
+	}
 	log(LogLevel::Info, "Press Ctrl+C to stop.");
 
-	for i in 0..entries.len() {
-		log(LogLevel::Info, &format!("━━━ Track {} of {} ━━━", i + 1, entries.len()));
-		
-		if !play_track(&mut file, &entries, &offsets, i, false) {
+	let Some((_stream, sink)) = open_output(options.device.as_deref(), options.volume) else { return };
+
+	let settings = TrackSettings { cassette: path, notify: options.notify, mono: options.mono };
+	let mut played = 0;
+	let mut skipped = 0;
+	loop {
+		let played_before = played;
+		for (pos, &i) in order.iter().enumerate() {
+			log(LogLevel::Info, &format!("━━━ Track {} of {} ━━━", i + 1, entries.len()));
+
+			// A damaged track is skipped; only an unreadable cassette stops the tape
+			match play_track(&sink, &mut file, &entries, &offsets, i, settings, None) {
+				Ok(true) => played += 1,
+				Ok(false) => {
+					log(LogLevel::Warning, &format!("Skipping track {}.", i + 1));
+					skipped += 1;
+				}
+				Err(e) => {
+					log(LogLevel::Error, &e);
+					log(LogLevel::Warning, &format!("Stopped after {} track(s) ({} skipped).", played, skipped));
+					return;
+				}
+			}
+
+			// Between sides, wait for the listener to "flip the tape"; otherwise the configured gap.
+			// With `repeat`, the last track is followed by the first. A shuffle has no sides to flip.
+			let next = match order.get(pos + 1) {
+				Some(&next) => next,
+				None if options.repeat => 0,
+				None => continue,
+			};
+			if options.tape_sfx {
+				play_tape_sfx(&sink);
+			}
+			if !options.shuffle && entries[next].side != entries[i].side {
+				flip_the_tape(entries[i].side, options.auto_flip);
+			} else {
+				std::thread::sleep(options.gap);
+			}
+		}
+
+		if !options.repeat {
 			break;
 		}
-		
-		// Small pause between tracks
-		if i < entries.len() - 1 {
-			std::thread::sleep(std::time::Duration::from_millis(500));
+		// Looping a tape where nothing decodes would just spin
+		if played == played_before {
+			log(LogLevel::Error, "No track in this pass could be played. Stopping the repeat.");
+			return;
+		}
+		if options.shuffle {
+			log(LogLevel::Info, "⟲ Shuffling again...");
+			order = shuffled(entries.len(), &mut rng);
+		} else {
+			log(LogLevel::Info, "⟲ Rewinding to track 1...");
+			order = (0..entries.len()).collect();
 		}
 	}
 
-	log(LogLevel::Success, "All tracks played. Cassette complete.");
+	if skipped == 0 {
+		log(LogLevel::Success, "All tracks played. Cassette complete.");
+	} else {
+		// Counted as they went by, so repeats and shuffles add up too
+		log(LogLevel::Warning, &format!("Cassette complete. Played {} of {} track(s), skipped {} damaged.", played, played + skipped, skipped));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use lofty::file::AudioFile;
+	use lofty::probe::Probe;
+	use rodio::Source;
+	use crate::format::CassetteHeader;
+	use crate::logger::take_logged;
+
+	/// 256 stereo frames of a 24-bit, 96 kHz sine, stored verbatim (uncompressed subframes).
+	const HIRES_FLAC: &[u8] = include_bytes!("../tests/fixtures/hires-24bit-96k.flac");
+
+	#[test]
+	fn hi_res_flac_decodes_at_full_depth() {
+		let tagged = Probe::new(Cursor::new(HIRES_FLAC)).guess_file_type().unwrap().read().expect("probing the FLAC");
+		let properties = tagged.properties();
+		assert_eq!(properties.bit_depth(), Some(24));
+		assert_eq!(properties.sample_rate(), Some(96_000));
+		assert_eq!(properties.channels(), Some(2));
+
+		let decoder = decode_with_hint(Cursor::new(HIRES_FLAC), HIRES_FLAC.len() as u64, Some("flac")).expect("decoding the FLAC");
+		assert_eq!((decoder.sample_rate(), decoder.channels()), (96_000, 2));
+		let samples: Vec<f32> = decoder.collect();
+		assert_eq!(samples.len(), 512);
+		// The sine peaks at 90% of full scale; reading only the low 16 bits would come out near silent
+		let peak = samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+		assert!((0.85..0.95).contains(&peak), "peak {}", peak);
+	}
+
+	#[test]
+	fn random_track_is_none_on_a_blank_cassette() {
+		let mut rng = seeded_rng(Some(7));
+		assert_eq!(random_track(&mut rng, 0), None);
+		assert!(random_track(&mut rng, 3).is_some_and(|idx| idx < 3));
+	}
+
+	#[test]
+	fn play_one_reports_a_blank_cassette() {
+		let mut cover = Cursor::new(Vec::new());
+		image::RgbaImage::from_pixel(1, 1, image::Rgba([200, 40, 40, 255]))
+			.write_to(&mut cover, image::ImageFormat::Png)
+			.expect("encoding a 1×1 PNG");
+		let mut bytes = [cover.into_inner(), CassetteHeader::new(Vec::new()).to_bytes()].concat();
+		bytes.extend_from_slice(&crc32fast::hash(&bytes).to_le_bytes());
+		let path = std::env::temp_dir().join(format!("rewind-test-{}-blank.png", std::process::id()));
+		std::fs::write(&path, bytes).unwrap();
+
+		let options = PlayOneOptions {
+			track: None,
+			seed: Some(7),
+			smart: false,
+			seek: None,
+			repeat_track: false,
+			notify: false,
+			strict: true,
+			device: None,
+			volume: 100,
+			mono: false,
+		};
+		take_logged();
+		play_one(&path.to_string_lossy(), &options);
+		std::fs::remove_file(&path).unwrap();
+		assert!(take_logged().iter().any(|message| message == BLANK_CASSETTE));
+	}
 }
@@ -0,0 +1,225 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// PNG MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Minimal PNG structure parsing. Walks the chunk list of the cover image
+// (length, type, data, CRC) from the signature up to IEND, which gives the exact
+// end of the image rather than the first byte sequence that happens to look
+// like an IEND chunk. Each chunk's own CRC32 (over its type and data) can be
+// re-checked to tell a damaged cover apart from a damaged tape.
+//
+// Animated PNGs (APNG) work the same way: their extra acTL/fcTL/fdAT chunks are
+// walked like any other, and the tape still starts after the one real IEND.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use crc32fast::Hasher;
+use crate::io::hash_only;
+
+/// The 8-byte signature every PNG file starts with.
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// File extensions offered for covers and cassettes (`apng` for animated covers).
+pub const COVER_EXTENSIONS: [&str; 2] = ["png", "apng"];
+
+/// Bytes of chunk framing around the data: length (4) + type (4) + CRC (4).
+const CHUNK_OVERHEAD: u64 = 12;
+
+/// A chunk header as found in the file.
+pub struct Chunk {
+	/// Chunk type, e.g. `IHDR`, `IDAT`, `IEND`.
+	pub kind: [u8; 4],
+	/// Offset of the chunk's length field.
+	pub offset: u64,
+	/// Length of the chunk data (excluding framing).
+	pub length: u32,
+	/// CRC32 stored after the data, covering the type and data.
+	pub crc: u32,
+}
+
+impl Chunk {
+	/// Offset of the first byte after this chunk (after its CRC).
+	pub fn end(&self) -> u64 {
+		self.offset + CHUNK_OVERHEAD + self.length as u64
+	}
+}
+
+/// Walks every chunk from the signature through IEND.
+pub fn read_chunks<R: Read + Seek>(reader: &mut R) -> Result<Vec<Chunk>, String> {
+	reader.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+
+	let mut signature = [0u8; 8];
+	reader.read_exact(&mut signature).map_err(|_| "This file is too short to be a PNG.".to_string())?;
+	if signature != PNG_SIGNATURE {
+		return Err("This file doesn't start with a PNG signature.".to_string());
+	}
+
+	let mut chunks = Vec::new();
+	let mut offset = PNG_SIGNATURE.len() as u64;
+	loop {
+		let mut header = [0u8; 8];
+		reader.read_exact(&mut header)
+			.map_err(|_| "The cover image ends before its IEND chunk.".to_string())?;
+		let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+		let kind = [header[4], header[5], header[6], header[7]];
+
+		// Skip the data and make sure the CRC is actually there
+		let end = offset + CHUNK_OVERHEAD + length as u64;
+		reader.seek(SeekFrom::Start(end - 4)).map_err(|e| e.to_string())?;
+		let mut crc = [0u8; 4];
+		reader.read_exact(&mut crc)
+			.map_err(|_| "The cover image ends before its IEND chunk.".to_string())?;
+
+		let chunk = Chunk { kind, offset, length, crc: u32::from_be_bytes(crc) };
+		offset = end;
+
+		let is_end = &chunk.kind == b"IEND";
+		chunks.push(chunk);
+		if is_end { break; }
+	}
+
+	Ok(chunks)
+}
+
+/// Image properties from the IHDR chunk.
+pub struct ImageInfo {
+	pub width: u32,
+	pub height: u32,
+	pub bit_depth: u8,
+	pub color_type: u8,
+}
+
+impl ImageInfo {
+	/// Short description, e.g. "1200×1200, 8-bit RGBA".
+	pub fn describe(&self) -> String {
+		let color = match self.color_type {
+			0 => "grayscale",
+			2 => "RGB",
+			3 => "indexed",
+			4 => "grayscale + alpha",
+			6 => "RGBA",
+			_ => "unknown color type",
+		};
+		format!("{}×{}, {}-bit {}", self.width, self.height, self.bit_depth, color)
+	}
+}
+
+/// Reads the image size and pixel format from the IHDR chunk, which must directly follow
+/// the signature. Nothing else in the image is read.
+pub fn read_image_info<R: Read + Seek>(reader: &mut R) -> Result<ImageInfo, String> {
+	reader.seek(SeekFrom::Start(0)).map_err(|e| e.to_string())?;
+
+	// Signature (8) + length (4) + type (4) + the first 10 bytes of IHDR data
+	let mut head = [0u8; 26];
+	reader.read_exact(&mut head).map_err(|_| "This file is too short to be a PNG.".to_string())?;
+	if head[..8] != PNG_SIGNATURE {
+		return Err("The cover isn't a PNG image.".to_string());
+	}
+	if &head[12..16] != b"IHDR" {
+		return Err("The cover image doesn't start with an IHDR chunk.".to_string());
+	}
+	Ok(ImageInfo {
+		width: u32::from_be_bytes([head[16], head[17], head[18], head[19]]),
+		height: u32::from_be_bytes([head[20], head[21], head[22], head[23]]),
+		bit_depth: head[24],
+		color_type: head[25],
+	})
+}
+
+/// Returns the offset immediately after the image's IEND chunk.
+pub fn image_end<R: Read + Seek>(reader: &mut R) -> Result<u64, String> {
+	let chunks = read_chunks(reader)?;
+	Ok(chunks.last().map(Chunk::end).unwrap_or(0))
+}
+
+/// Recomputes a chunk's CRC32 from its type and data, for comparison with `chunk.crc`.
+pub fn compute_chunk_crc<R: Read + Seek>(reader: &mut R, chunk: &Chunk) -> io::Result<u32> {
+	// The CRC covers everything after the length field: type (4 bytes) and data
+	reader.seek(SeekFrom::Start(chunk.offset + 4))?;
+	let mut hasher = Hasher::new();
+	let covered = 4 + chunk.length as u64;
+	if hash_only(reader, &mut hasher, covered)? < covered {
+		return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "chunk is cut short"));
+	}
+	Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Cursor;
+	use crate::format::{Cassette, CassetteHeader, Side, TocEntry};
+	use crate::io::find_iend;
+
+	/// One PNG chunk: length, type, data and the CRC32 of type and data.
+	fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+		let mut hasher = Hasher::new();
+		hasher.update(kind);
+		hasher.update(data);
+		[&(data.len() as u32).to_be_bytes()[..], kind, data, &hasher.finalize().to_be_bytes()].concat()
+	}
+
+	/// A two-frame 1×1 APNG. A text chunk before the real IEND holds a copy of an IEND chunk,
+	/// which a byte scan for IEND would stop at.
+	fn animated_png() -> Vec<u8> {
+		let mut png = Cursor::new(Vec::new());
+		image::RgbaImage::from_pixel(1, 1, image::Rgba([200, 40, 40, 255]))
+			.write_to(&mut png, image::ImageFormat::Png)
+			.expect("encoding a 1×1 PNG");
+		let png = png.into_inner();
+		let chunks = read_chunks(&mut Cursor::new(&png)).expect("walking the 1×1 PNG");
+		let data = |kind: &[u8; 4]| chunks.iter().find(|chunk| &chunk.kind == kind)
+			.map(|chunk| &png[chunk.offset as usize + 8..chunk.end() as usize - 4])
+			.expect("the 1×1 PNG has IHDR and IDAT");
+		// Sequence number, 1×1 at (0, 0), a 1/2 s delay, no dispose or blend
+		let frame_control = |sequence: u32| [&sequence.to_be_bytes()[..], &1u32.to_be_bytes(), &1u32.to_be_bytes(), &[0; 8], &1u16.to_be_bytes(), &2u16.to_be_bytes(), &[0, 0]].concat();
+		let lookalike = png_chunk(b"IEND", &[]);
+
+		[
+			png[..8].to_vec(),
+			png_chunk(b"IHDR", data(b"IHDR")),
+			png_chunk(b"acTL", &[2u32.to_be_bytes(), 0u32.to_be_bytes()].concat()),
+			png_chunk(b"fcTL", &frame_control(0)),
+			png_chunk(b"IDAT", data(b"IDAT")),
+			png_chunk(b"fcTL", &frame_control(1)),
+			png_chunk(b"fdAT", &[&2u32.to_be_bytes()[..], data(b"IDAT")].concat()),
+			png_chunk(b"tEXt", &[&b"Comment\0"[..], &lookalike].concat()),
+			lookalike,
+		].concat()
+	}
+
+	#[test]
+	fn animated_cover_is_read_past_its_last_frame() {
+		let cover = animated_png();
+		let kinds: Vec<_> = read_chunks(&mut Cursor::new(&cover)).unwrap().iter().map(|chunk| chunk.kind).collect();
+		assert_eq!(kinds, [*b"IHDR", *b"acTL", *b"fcTL", *b"IDAT", *b"fcTL", *b"fdAT", *b"tEXt", *b"IEND"]);
+		let decoded = image::load_from_memory(&cover).expect("the APNG decodes as an image");
+		assert_eq!((decoded.width(), decoded.height()), (1, 1));
+
+		// Cover, TOC, one track and the CRC32 seal
+		let audio = b"not really audio".to_vec();
+		let entry = TocEntry {
+			name: "track.wav".to_string(),
+			size: audio.len() as u64,
+			side: Side::A,
+			art: Vec::new(),
+			seek_index: Vec::new(),
+			crc: Some(crc32fast::hash(&audio)),
+			number: None,
+			lyrics: String::new(),
+			codec: None,
+		};
+		let mut bytes = [cover.clone(), CassetteHeader::new(vec![entry]).to_bytes(), audio.clone()].concat();
+		bytes.extend_from_slice(&crc32fast::hash(&bytes).to_le_bytes());
+
+		// The IEND copy inside the text chunk fools a byte scan, but not the chunk walk
+		let mut reader = Cursor::new(&bytes);
+		assert!(find_iend(&mut reader).is_some_and(|end| end < cover.len() as u64));
+		assert_eq!(image_end(&mut reader), Ok(cover.len() as u64));
+		assert_eq!(read_image_info(&mut reader).map(|info| (info.width, info.height)), Ok((1, 1)));
+
+		let cassette = Cassette::from_bytes(bytes.clone()).expect("an APNG cassette opens");
+		assert_eq!(cassette.layout.toc_start, cover.len() as u64);
+		assert_eq!(cassette.layout.check_strict(&mut reader), Ok(()));
+		assert_eq!(cassette.track(0), Some(audio.as_slice()));
+	}
+}
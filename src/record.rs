@@ -7,79 +7,585 @@
 // after the PNG IEND chunk, and seals the file with a CRC32 integrity checksum.
 
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::io::{self, BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
+use std::num::{NonZeroU32, NonZeroU64};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use crc32fast::Hasher;
-use crate::io::{open_file, create_file, validate_audio, transfer};
-use crate::logger::{log, LogLevel};
+use clap::ValueEnum;
+use lofty::file::{AudioFile, FileType, TaggedFileExt};
+use lofty::probe::Probe;
+use lofty::tag::Accessor;
+use crate::constants::FRAGILE_SIZE_RATIO;
+use crate::io::{open_file, validate_audio, PendingFile, TempFile, Progress, ProgressReader, is_default_format, is_lossy, transfer, human_size, format_duration, sanitize_name};
+use crate::format::{padding_before, AlbumInfo, Cassette, CassetteHeader, CassetteLayout, CassetteSignature, SealedRegion, SeekPoint, Side, TocEntry, CRC_LEN, MAX_ALIGN, PUBLIC_KEY_LEN, SIGNATURE_LEN, write_seal};
+use crate::cover::{CoverSource, CoverText, default_cover, generate_cover};
+use crate::transcode::{TrimSpec, trim_to_wav};
+use crate::art::{ArtSpec, make_thumbnail};
+use crate::lyrics::{LyricsSpec, load_lyrics, parse_lrc};
+use crate::seek_index::build_seek_index;
+use crate::ogg::read_chain;
+use crate::png::image_end;
+use crate::sign::{load_signing_key, sign, start_digest};
+use crate::playback::{codec_hint, decode_with_hint};
+use crate::logger::{log, LogLevel, trace_event, trace_span};
+
+/// Optional behaviour for `record`.
+#[derive(Default)]
+pub struct RecordOptions {
+	/// Display names stored in the TOC instead of the file paths, matched positionally.
+	pub names: Option<Vec<String>>,
+	/// Name tracks after their title tag (keeping the file extension) when they have one.
+	pub name_from_tags: bool,
+	/// Track numbers stored in the TOC instead of the positions, matched positionally.
+	pub track_numbers: Option<Vec<u32>>,
+	/// Album title stored in the TOC; `None` takes it from the tags.
+	pub album: Option<String>,
+	/// Album artist stored in the TOC; `None` takes it from the tags.
+	pub artist: Option<String>,
+	/// Release year stored in the TOC; `None` takes it from the tags.
+	pub year: Option<u16>,
+	/// Nominal tape length in minutes (from `--tape C90`); longer audio gets a warning.
+	pub tape_minutes: Option<u16>,
+	/// Time ranges to keep from specific tracks (re-encoded to WAV).
+	pub trims: Vec<TrimSpec>,
+	/// Tape side of each track, matched positionally. Tracks without an entry go on Side A.
+	pub sides: Vec<Side>,
+	/// Thumbnail images for specific tracks.
+	pub art: Vec<ArtSpec>,
+	/// LRC files with synced lyrics for specific tracks.
+	pub lyrics: Vec<LyricsSpec>,
+	/// Build a seek index for FLAC/OGG tracks (needs a pass over each track's frames).
+	pub seek_index: bool,
+	/// Skip the note about lossy sources.
+	pub allow_lossy: bool,
+	/// Formats accepted on top of the default ones (from `--allow-format`).
+	pub allow_formats: Vec<ExtraFormat>,
+	/// PKCS#8 PEM file with an Ed25519 key to sign the cassette with.
+	pub sign_key: Option<String>,
+	/// Once written, read the cassette back and check its seal and TOC.
+	pub verify_after: bool,
+	/// Refuse to record when the finished cassette would be larger than this many bytes.
+	pub max_size: Option<NonZeroU64>,
+	/// Start every track on a multiple of this many bytes (from `--align`); `None` packs them.
+	pub align: Option<NonZeroU32>,
+}
+
+/// A format `record` only accepts when asked to with `--allow-format`. Only formats the
+/// players can decode are listed; WavPack, Monkey's Audio, Opus, Speex and Musepack have
+/// no decoder in this build, so they are refused outright.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExtraFormat {
+	/// AIFF (uncompressed PCM)
+	Aiff,
+}
+
+impl ExtraFormat {
+	/// The opt-in format a probed file is in, if it's one of them.
+	fn of(file_type: FileType) -> Option<Self> {
+		match file_type {
+			FileType::Aiff => Some(ExtraFormat::Aiff),
+			_ => None,
+		}
+	}
+
+	/// Name of the format for messages.
+	fn label(self) -> &'static str {
+		match self {
+			ExtraFormat::Aiff => "AIFF",
+		}
+	}
+
+	/// The value `--allow-format` takes for this format.
+	fn flag_value(self) -> String {
+		self.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+	}
+}
+
+/// Audio waiting to be recorded. Seekable, so signing can read it once before it is written.
+trait AudioInput: Read + Seek {}
+impl<T: Read + Seek> AudioInput for T {}
 
 /// Injects audio files into the PNG image, producing a cassette file.
-pub fn record(image_path: &str, audio_paths: &[&str], output_path: &str) {
-	log(LogLevel::Info, &format!("Injecting {} audio file(s) into {}", audio_paths.len(), image_path));
+/// `on_progress` is told `(audio bytes written, total audio bytes)` while the tracks are copied.
+/// Returns whether the cassette was written (and, with `verify_after`, read back intact).
+pub fn record(cover: &CoverSource, audio_paths: &[PathBuf], output_path: &str, options: &RecordOptions, on_progress: &mut dyn FnMut(u64, u64)) -> bool {
+	let cover_name = match cover {
+		CoverSource::File(path) => path.as_str(),
+		CoverSource::Default(_) | CoverSource::Generated(_) => "a generated cover",
+	};
+	log(LogLevel::Info, &format!("Injecting {} audio file(s) into {}", audio_paths.len(), cover_name));
+	let _span = trace_span!("record", output = output_path, tracks = audio_paths.len());
+
+	// Readers find the TOC right after IEND, so a cover without one can't become a cassette.
+	// Only the picture is copied: anything after it (say, an older tape) would nest inside this one
+	let mut cover_len = None;
+	if let CoverSource::File(path) = cover {
+		let measured = open_file(path).and_then(|mut file| {
+			let end = image_end(&mut file)?;
+			let len = file.metadata().map_err(|e| e.to_string())?.len();
+			Ok((end, len))
+		});
+		match measured {
+			Ok((end, len)) => {
+				if end < len {
+					log(LogLevel::Warning, &format!("'{}' has {} of data after the image (is it already a cassette?). Only the picture is used as the cover.", path, human_size(len - end)));
+				}
+				cover_len = Some(end);
+			}
+			Err(e) => {
+				log(LogLevel::Error, &format!("'{}' can't be used as a cover: {}", path, e));
+				return false;
+			}
+		}
+	}
+
+	if let Some(ref names) = options.names
+		&& names.len() != audio_paths.len()
+	{
+		log(LogLevel::Error, &format!("Got {} name(s) for {} audio file(s). Provide exactly one name per track.", names.len(), audio_paths.len()));
+		return false;
+	}
+
+	if let Some(ref numbers) = options.track_numbers {
+		if numbers.len() != audio_paths.len() {
+			log(LogLevel::Error, &format!("Got {} track number(s) for {} audio file(s). Provide exactly one number per track.", numbers.len(), audio_paths.len()));
+			return false;
+		}
+		if let Some(number) = numbers.iter().enumerate().find_map(|(i, n)| numbers[..i].contains(n).then_some(n)) {
+			log(LogLevel::Error, &format!("Track number {} is given more than once. Every track needs its own number.", number));
+			return false;
+		}
+	}
 
-	// 1. Validate and collect audio file info
-	let mut audio_files: Vec<(File, String, u64)> = Vec::new();
+	if let Some(trim) = options.trims.iter().find(|t| t.track > audio_paths.len()) {
+		log(LogLevel::Error, &format!("Cannot trim track {}: only {} audio file(s) were given.", trim.track, audio_paths.len()));
+		return false;
+	}
 
-	for &path in audio_paths {
+	if let Some(art) = options.art.iter().find(|a| a.track > audio_paths.len()) {
+		log(LogLevel::Error, &format!("Cannot add art to track {}: only {} audio file(s) were given.", art.track, audio_paths.len()));
+		return false;
+	}
+
+	if let Some(lyrics) = options.lyrics.iter().find(|l| l.track > audio_paths.len()) {
+		log(LogLevel::Error, &format!("Cannot add lyrics to track {}: only {} audio file(s) were given.", lyrics.track, audio_paths.len()));
+		return false;
+	}
+
+	// Load the signing key up front too
+	let signing_key = match options.sign_key.as_deref().map(load_signing_key).transpose() {
+		Ok(key) => key,
+		Err(e) => { log(LogLevel::Error, &e); return false; }
+	};
+
+	// Shrink track artwork to thumbnails up front, so a bad image fails before anything is written
+	let mut thumbnails = vec![Vec::new(); audio_paths.len()];
+	for art in &options.art {
+		match make_thumbnail(&art.path) {
+			Ok(png) => {
+				log(LogLevel::Info, &format!("Artwork for track {}: {} ({} as thumbnail)", art.track, art.path, human_size(png.len() as u64)));
+				thumbnails[art.track - 1] = png;
+			}
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		}
+	}
+
+	let mut lyrics = vec![String::new(); audio_paths.len()];
+	for spec in &options.lyrics {
+		match load_lyrics(&spec.path) {
+			Ok(text) => {
+				log(LogLevel::Info, &format!("Lyrics for track {}: {} ({} lines)", spec.track, spec.path, parse_lrc(&text).len()));
+				lyrics[spec.track - 1] = text;
+			}
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		}
+	}
+
+	// 1. Validate and collect audio file info. Trimmed tracks wait in temporary files, which
+	// are declared first so they are deleted only after `audio_files` has closed them.
+	let mut trimmed: Vec<TempFile> = Vec::new();
+	let mut audio_files: Vec<(Box<dyn AudioInput>, String, u64)> = Vec::new();
+	let mut seek_indexes: Vec<Vec<SeekPoint>> = Vec::new();
+	let mut codecs: Vec<FileType> = Vec::new();
+	let mut cover_tags: Vec<(Option<String>, Option<String>)> = Vec::new();
+	let mut tag_year = None;
+	let mut total_duration = Duration::ZERO;
+
+	for (i, path) in audio_paths.iter().enumerate() {
 		let mut file = match open_file(path) {
 			Ok(f) => f,
-			Err(e) => { log(LogLevel::Error, &e); return; }
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		};
+		if file.metadata().is_ok_and(|m| m.len() == 0) {
+			log(LogLevel::Error, &format!("'{}' rejected: This audio file is empty.", path.display()));
+			return false;
+		}
+
+		let tagged = match validate_audio(&mut file) {
+			Ok(t) => t,
+			Err(e) => { log(LogLevel::Error, &format!("'{}' rejected: {}", path.display(), e)); return false; }
+		};
+		if !is_default_format(tagged.file_type()) {
+			let Some(format) = ExtraFormat::of(tagged.file_type()).filter(|format| options.allow_formats.contains(format)) else {
+				let message = match ExtraFormat::of(tagged.file_type()) {
+					Some(format) => format!("{} isn't one of the formats recorded by default (FLAC, MP3, AAC/MP4, Ogg Vorbis, WAV). Pass `--allow-format {}` to record it anyway.", format.label(), format.flag_value()),
+					None => format!("Rewind's players can't decode {:?} files, so they can't be recorded. Convert it to FLAC (lossless) or Ogg Vorbis first.", tagged.file_type()),
+				};
+				log(LogLevel::Error, &format!("'{}' rejected: {}", path.display(), message));
+				return false;
+			};
+			if let Err(e) = check_decodable(&mut file, tagged.file_type()) {
+				log(LogLevel::Error, &format!("'{}' rejected: {} was allowed, but this build's players can't decode this file. {}", path.display(), format.label(), e));
+				return false;
+			}
+		}
+		// Players stop after the first stream of a chained Ogg, so the rest would never be heard
+		let chain = read_chain(&mut BufReader::new(&mut file));
+		if let Err(e) = file.rewind() {
+			log(LogLevel::Error, &format!("Cannot read '{}': {}", path.display(), e));
+			return false;
+		}
+		if let Some(chain) = chain.filter(|c| c.links > 1) {
+			log(LogLevel::Error, &format!("'{}' rejected: This is a chained Ogg file ({} streams joined end to end), and players only play the first one. Re-encode it into a single stream first.", path.display(), chain.links));
+			return false;
+		}
+		if !options.allow_lossy && is_lossy(tagged.file_type()) {
+			log(LogLevel::Info, &format!("Note: {} is lossy; cassette quality is limited by the source.", path.display()));
+		}
+		let duration = tagged.properties().duration();
+		let tag = tagged.primary_tag().or_else(|| tagged.first_tag());
+		cover_tags.push((
+			tag.and_then(|t| t.album()).map(|s| s.to_string()),
+			tag.and_then(|t| t.artist()).map(|s| s.to_string()),
+		));
+		tag_year = tag_year.or_else(|| tag.and_then(|t| t.year()).and_then(|year| u16::try_from(year).ok()).filter(|&year| year != 0));
+
+		let title = tag.and_then(|t| t.title()).map(|title| sanitize_name(&title)).filter(|title| !title.is_empty());
+		let mut name = match (&options.names, title) {
+			(Some(names), _) => names[i].clone(),
+			(None, Some(title)) if options.name_from_tags => {
+				let name = match path.extension() {
+					Some(ext) => format!("{}.{}", title, ext.to_string_lossy()),
+					None => title,
+				};
+				log(LogLevel::Info, &format!("Named from tags: {} → {}", path.display(), name));
+				name
+			}
+			_ => {
+				// The name is stored as UTF-8, so characters that don't convert become U+FFFD
+				if path.to_str().is_none() {
+					log(LogLevel::Warning, &format!("'{}' isn't valid Unicode, so its track name shows � for the characters that can't be converted. Use --names to pick one.", path.display()));
+				}
+				path.to_string_lossy().to_string()
+			}
 		};
 
-		if let Err(e) = validate_audio(&mut file) {
-			log(LogLevel::Error, &format!("'{}' rejected: {}", path, e));
-			return;
+		if let Some(trim) = options.trims.iter().find(|t| t.track == i + 1) {
+			// Trimmed tracks are decoded and re-encoded to WAV before embedding
+			let (temp, mut wav) = match trim_to_wav(path, trim) {
+				Ok(trimmed) => trimmed,
+				Err(e) => { log(LogLevel::Error, &e); return false; }
+			};
+			trimmed.push(temp);
+			if options.names.is_none() {
+				name = Path::new(&name).with_extension("wav").to_string_lossy().to_string();
+			}
+			let size = wav.metadata().map(|m| m.len()).unwrap_or(0);
+			log(LogLevel::Info, &format!("Validated and trimmed: {} ({} bytes as WAV)", path.display(), size));
+			total_duration += Probe::new(BufReader::new(&wav)).guess_file_type().ok()
+				.and_then(|probe| probe.read().ok())
+				.map(|tagged| tagged.properties().duration())
+				.unwrap_or_default();
+			if let Err(e) = wav.rewind() {
+				log(LogLevel::Error, &format!("Cannot read the trimmed '{}': {}", path.display(), e));
+				return false;
+			}
+			audio_files.push((Box::new(BufReader::new(wav)), name, size));
+			seek_indexes.push(Vec::new());
+			codecs.push(FileType::Wav);
+			continue;
 		}
 
+		total_duration += duration;
 		let size = file.metadata().map(|m| m.len()).unwrap_or(0);
-		audio_files.push((file, path.to_string(), size));
-		log(LogLevel::Info, &format!("Validated: {}", path));
+		seek_indexes.push(if options.seek_index { track_seek_index(path) } else { Vec::new() });
+		codecs.push(tagged.file_type());
+		audio_files.push((Box::new(BufReader::new(file)), name, size));
+		log(LogLevel::Info, &format!("Validated: {}", path.display()));
+	}
+
+	if let Some(minutes) = options.tape_minutes {
+		report_tape(minutes, total_duration);
 	}
 
-	// 2. Open image input and output
-	let mut image_in = match open_file(image_path) {
-		Ok(f) => f,
-		Err(e) => { log(LogLevel::Error, &e); return; }
+	let album = match album_info(options, &cover_tags, tag_year).validated() {
+		Ok(album) => album,
+		Err(e) => { log(LogLevel::Error, &e); return false; }
 	};
 
-	let output = match create_file(output_path) {
-		Ok(f) => f,
-		Err(e) => { log(LogLevel::Error, &e); return; }
+	// 2. Open the image input; its size is known up front, so the whole layout is too
+	let (mut image_in, image_size): (Box<dyn Read>, u64) = match cover {
+		CoverSource::File(path) => match open_file(path) {
+			Ok(f) => {
+				let len = cover_len.unwrap_or_default();
+				(Box::new(BufReader::new(f).take(len)), len)
+			}
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		},
+		CoverSource::Default(theme) => match default_cover(*theme) {
+			Ok(bytes) => { let len = bytes.len() as u64; (Box::new(Cursor::new(bytes)), len) }
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		},
+		CoverSource::Generated(theme) => match generate_cover(&CoverText::from_tags(&cover_tags), *theme) {
+			Ok(bytes) => { let len = bytes.len() as u64; (Box::new(Cursor::new(bytes)), len) }
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		},
+	};
+	let payload_size: u64 = audio_files.iter().map(|(_, _, size)| size).sum();
+
+	// 3. Build the TOC
+	let mut header = CassetteHeader::new(audio_files.iter().zip(thumbnails).zip(seek_indexes).zip(lyrics).enumerate()
+		.map(|(i, ((((_, name, size), art), seek_index), lyrics))| TocEntry {
+			name: name.clone(),
+			size: *size,
+			side: options.sides.get(i).copied().unwrap_or_default(),
+			art,
+			seek_index,
+			crc: None,
+			number: options.track_numbers.as_ref().map(|numbers| numbers[i]),
+			lyrics,
+			codec: codecs.get(i).copied(),
+		})
+		.collect());
+	header.album = Some(album);
+	header.tape_minutes = options.tape_minutes;
+	header.align = options.align.map(NonZeroU32::get).filter(|&align| align > 1);
+	if signing_key.is_some() {
+		// Room for the signature is reserved now, as it moves the audio (and its padding)
+		header.signature = Some(CassetteSignature { public_key: [0; PUBLIC_KEY_LEN], signature: [0; SIGNATURE_LEN] });
+	}
+
+	let Some(region) = SealedRegion::of_layout(image_size, &header) else {
+		log(LogLevel::Error, "The cassette is too large to seal.");
+		return false;
+	};
+	trace_event!(image_size, payload_size, align = ?header.align, trailer_start = region.trailer_start, "layout");
+	if let Some(budget) = options.max_size.map(NonZeroU64::get) {
+		let projected = region.trailer_start + CRC_LEN as u64;
+		if projected > budget {
+			log(LogLevel::Error, &format!("The cassette would be {} ({} bytes), {} over the {} budget. Nothing was written.",
+				human_size(projected), projected, human_size(projected - budget), human_size(budget)));
+			return false;
+		}
+		log(LogLevel::Info, &format!("Projected size: {} of the {} budget.", human_size(projected), human_size(budget)));
+	}
+
+	if let Some(ref key) = signing_key {
+		// The signature covers the TOC (without the signature itself) and every audio byte, padding included
+		let mut position = image_size + header.to_bytes().len() as u64;
+		let mut digest = start_digest(&header.unsigned_bytes());
+		for (track, name, size) in audio_files.iter_mut() {
+			let padding = padding_before(position, header.align);
+			position += padding + *size;
+			let hashed = io::copy(&mut io::repeat(0).take(padding), &mut digest)
+				.and_then(|_| io::copy(track, &mut digest))
+				.and_then(|_| track.seek(SeekFrom::Start(0)));
+			if let Err(e) = hashed {
+				log(LogLevel::Error, &format!("Failed to read track '{}' for signing: {}", name, e));
+				return false;
+			}
+		}
+		match sign(key, digest) {
+			Ok(signature) => header.signature = Some(signature),
+			Err(e) => { log(LogLevel::Error, &e); return false; }
+		}
+		log(LogLevel::Info, "Cassette signed.");
+	}
+
+	// Written under a temporary name until sealed; any early return deletes it
+	let (pending, output) = match PendingFile::create(output_path) {
+		Ok(created) => created,
+		Err(e) => { log(LogLevel::Error, &e); return false; }
 	};
 
 	let mut writer = BufWriter::new(output);
 	let mut hasher = Hasher::new();
 
-	// 3. Copy image
-	if let Err(e) = transfer(&mut BufReader::new(&mut image_in), &mut writer, &mut hasher) {
+	// 4. Copy the image and write the TOC
+	if let Err(e) = transfer(&mut image_in, &mut writer, &mut hasher) {
 		log(LogLevel::Error, &format!("Failed to copy cover art: {}", e));
-		return;
+		return false;
 	}
 	log(LogLevel::Info, "Cover art transferred.");
+	// The track CRCs aren't known yet; a same-sized placeholder TOC is rewritten once they are
+	let toc_start = match writer.stream_position().and_then(|pos| writer.write_all(&header.to_bytes()).map(|_| pos)) {
+		Ok(pos) => pos,
+		Err(e) => { log(LogLevel::Error, &format!("Failed to write TOC: {}", e)); return false; }
+	};
+	trace_event!(toc_start, toc_len = header.to_bytes().len(), "placeholder TOC written");
 
-	// 4. Build and write TOC
-	let mut toc = Vec::new();
-	toc.extend_from_slice(&(audio_files.len() as u32).to_le_bytes());
-	for (_, name, size) in &audio_files {
-		let name_bytes = name.as_bytes();
-		toc.extend_from_slice(&(name_bytes.len() as u32).to_le_bytes());
-		toc.extend_from_slice(name_bytes);
-		toc.extend_from_slice(&size.to_le_bytes());
-	}
-	writer.write_all(&toc).unwrap();
-	hasher.update(&toc);
-	log(LogLevel::Info, "TOC written.");
-
-	// 5. Append audio data
-	for (mut file, name, _) in audio_files {
-		if let Err(e) = transfer(&mut BufReader::new(&mut file), &mut writer, &mut hasher) {
+	// 5. Append audio data, with a CRC32 per track (alignment padding only counts towards the seal)
+	let mut audio_hasher = Hasher::new();
+	let mut recorded = 0;
+	for (i, (mut track, name, _)) in audio_files.into_iter().enumerate() {
+		let padded = writer.stream_position().and_then(|position| {
+			let padding = padding_before(position, header.align);
+			trace_event!(track = %name, offset = position + padding, padding, size = header.entries[i].size, "writing track");
+			transfer(&mut io::repeat(0).take(padding), &mut writer, &mut audio_hasher)
+		});
+		if let Err(e) = padded {
 			log(LogLevel::Error, &format!("Failed to record track '{}': {}", name, e));
-			return;
+			return false;
+		}
+		let mut track_hasher = Hasher::new();
+		let mut observer = |done, total| on_progress(recorded + done, total);
+		match transfer(&mut ProgressReader::new(&mut track, payload_size, &mut observer), &mut writer, &mut track_hasher) {
+			Ok(n) => recorded += n,
+			Err(e) => {
+				log(LogLevel::Error, &format!("Failed to record track '{}': {}", name, e));
+				return false;
+			}
 		}
+		audio_hasher.combine(&track_hasher);
+		header.entries[i].crc = Some(track_hasher.finalize());
+		trace_event!(track = %name, crc = header.entries[i].crc.unwrap_or_default(), "track recorded");
 		log(LogLevel::Info, &format!("Recorded: {}", name));
 	}
 
+	let toc = header.to_bytes();
+	let patched = writer.seek(SeekFrom::Start(toc_start))
+		.and_then(|_| writer.write_all(&toc))
+		.and_then(|_| writer.seek(SeekFrom::End(0)));
+	if let Err(e) = patched {
+		log(LogLevel::Error, &format!("Failed to write TOC: {}", e));
+		return false;
+	}
+	hasher.update(&toc);
+	hasher.combine(&audio_hasher);
+	log(LogLevel::Info, "TOC written.");
+
 	// 6. Write CRC
 	let crc = hasher.finalize();
-	writer.write_all(&crc.to_le_bytes()).unwrap();
+	trace_event!(crc, bytes = recorded, "sealing");
+	if let Err(e) = write_seal(&mut writer, region, crc).map_err(|e| format!("Failed to write CRC: {}", e)).and_then(|_| pending.commit(writer)) {
+		log(LogLevel::Error, &e);
+		return false;
+	}
 	log(LogLevel::Success, &format!("🎵 Cassette recording complete! Sealed with CRC32: {:08X}", crc));
+	if payload_size >= image_size.saturating_mul(FRAGILE_SIZE_RATIO) {
+		log(LogLevel::Info, &format!("Note: The audio ({}) is {}× the size of the cover image ({}). Sites that shrink or re-encode big images will likely break a cassette like this; share it as a file or archive instead.",
+			human_size(payload_size), payload_size / image_size.max(1), human_size(image_size)));
+	}
+
+	if options.verify_after {
+		if let Err(e) = verify_written(output_path, crc) {
+			log(LogLevel::Error, &format!("Verification failed: {} Keep the source files and record again.", e));
+			return false;
+		}
+		log(LogLevel::Success, "Verified: the cassette on disk matches its seal and its TOC accounts for every byte. Safe to tidy up the sources.");
+	}
+	true
+}
+
+/// Reads a freshly written cassette back from disk: the CRC32 over the whole file must
+/// match the seal `crc` that was written, and the TOC must account for every byte.
+fn verify_written(path: &str, crc: u32) -> Result<(), String> {
+	let mut file = open_file(path)?;
+	let mut line = Progress::new("Verifying");
+	let seal = Cassette::verify(&mut file, &mut |done, total| line.update(done, total))?;
+	drop(line);
+	if seal.stored != crc || !seal.is_intact() {
+		return Err(format!("The file on disk doesn't match what was written (sealed {:08X}, found {:08X}, computed {:08X}).", crc, seal.stored, seal.computed));
+	}
+	let layout = CassetteLayout::read(&mut file)?;
+	layout.check_strict(&mut file).map_err(|e| e.trim_start_matches("Strict mode: ").to_string())
+}
+
+/// Decodes the start of an opted-in file the way the players will, so a format Lofty can read
+/// but the decoder can't play is refused now rather than on playback. Leaves `file` rewound.
+fn check_decodable(file: &mut File, file_type: FileType) -> Result<(), String> {
+	let len = file.metadata().map_err(|e| e.to_string())?.len();
+	let reader = file.try_clone().map_err(|e| e.to_string())?;
+	let decoded = decode_with_hint(BufReader::new(reader), len, codec_hint(file_type))
+		.map_err(|e| e.to_string())
+		.and_then(|mut decoder| decoder.next().map(|_| ()).ok_or_else(|| "No audio could be decoded.".to_string()));
+	file.rewind().map_err(|e| e.to_string())?;
+	decoded
+}
+
+/// Parses a track alignment in bytes for `--align`, e.g. `4096`. 1 means no alignment.
+pub fn parse_align(text: &str) -> Result<NonZeroU32, String> {
+	text.trim().parse::<NonZeroU32>().ok()
+		.filter(|align| align.get() <= MAX_ALIGN)
+		.ok_or_else(|| format!("Invalid alignment '{}'. Expected a number of bytes from 1 to {}, e.g. 4096", text, MAX_ALIGN))
+}
+
+/// Parses a size budget for `--max-size`: plain bytes, or a number with a decimal
+/// (`KB`, `MB`, `GB`) or binary (`KiB`, `MiB`, `GiB`) unit, e.g. `25MB` or `8 MiB`.
+pub fn parse_size(text: &str) -> Result<NonZeroU64, String> {
+	let text = text.trim();
+	let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+	let (number, unit) = text.split_at(split);
+	let scale: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+		"" | "b" => 1,
+		"k" | "kb" => 1000,
+		"kib" => 1 << 10,
+		"m" | "mb" => 1_000_000,
+		"mib" => 1 << 20,
+		"g" | "gb" => 1_000_000_000,
+		"gib" => 1 << 30,
+		_ => 0,
+	};
+	number.parse::<f64>().ok()
+		.map(|value| value * scale as f64)
+		.filter(|&bytes| bytes < u64::MAX as f64)
+		.and_then(|bytes| NonZeroU64::new(bytes as u64))
+		.ok_or_else(|| format!("Invalid size '{}'. Expected a number of bytes, optionally with a unit, e.g. 25MB or 8MiB", text))
+}
+
+/// Parses a tape type such as `C60` or `c90` into its length in minutes.
+pub fn parse_tape(text: &str) -> Result<u16, String> {
+	text.trim().strip_prefix(['C', 'c'])
+		.and_then(|minutes| minutes.parse::<u16>().ok())
+		.filter(|&minutes| minutes != 0)
+		.ok_or_else(|| format!("Invalid tape '{}'. Expected C and the length in minutes, e.g. C60 or C90", text))
+}
+
+/// Compares the audio with the nominal tape length, warning when it won't fit.
+pub fn report_tape(minutes: u16, audio: Duration) {
+	let capacity = u64::from(minutes) * 60;
+	let used = audio.as_secs();
+	if used > capacity {
+		log(LogLevel::Warning, &format!("Audio exceeds C{} tape length by {} ({} of {}).",
+			minutes, format_duration(used - capacity), format_duration(used), format_duration(capacity)));
+	} else {
+		log(LogLevel::Info, &format!("Tape: C{}, {} of {} used.", minutes, format_duration(used), format_duration(capacity)));
+	}
+}
+
+/// Album metadata for the TOC: the options where given, otherwise the first album and year
+/// tags, and the artist tags if they all agree ("Various Artists" if they don't).
+fn album_info(options: &RecordOptions, tags: &[(Option<String>, Option<String>)], tag_year: Option<u16>) -> AlbumInfo {
+	let album = options.album.clone()
+		.or_else(|| tags.iter().find_map(|(album, _)| album.clone()))
+		.unwrap_or_default();
+	let artist = options.artist.clone().unwrap_or_else(|| {
+		let mut artists = tags.iter().filter_map(|(_, artist)| artist.as_deref());
+		match artists.next() {
+			Some(first) if artists.all(|a| a == first) => first.to_string(),
+			Some(_) => "Various Artists".to_string(),
+			None => String::new(),
+		}
+	});
+	AlbumInfo { album, artist, year: options.year.or(tag_year) }
+}
+
+/// Scans a FLAC/OGG file for seek points. Other formats (and unreadable files) get none.
+fn track_seek_index(path: &Path) -> Vec<SeekPoint> {
+	let points = std::fs::read(path).map(|bytes| build_seek_index(&bytes)).unwrap_or_default();
+	if !points.is_empty() {
+		log(LogLevel::Info, &format!("Seek index for {}: {} point(s)", path.display(), points.len()));
+	}
+	points
 }
@@ -0,0 +1,201 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// SEEK INDEX MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Builds the optional per-track seek index stored by `record --seek-index`
+// (format v4+). The index maps a timestamp to the byte offset (within the
+// track) of the first frame or page starting at or after it, roughly every
+// SEEK_INTERVAL, so a player can jump into a long track without decoding
+// everything before the target.
+//
+// FLAC: frames are found by their sync code and validated with the header's
+// CRC-8; the frame/sample number in the header gives the timestamp.
+// Ogg Vorbis: pages are walked by their headers; the granule position of the
+// previous page gives the first sample of each page.
+// Other formats get no index.
+
+use crate::format::SeekPoint;
+
+/// Desired spacing between seek points, in milliseconds.
+const SEEK_INTERVAL_MS: u64 = 10_000;
+
+/// Builds a seek index for a FLAC or Ogg Vorbis track. Returns an empty index for
+/// other formats or if the stream can't be parsed.
+pub fn build_seek_index(audio: &[u8]) -> Vec<SeekPoint> {
+	let points = if audio.starts_with(b"fLaC") {
+		flac_points(audio)
+	} else if audio.starts_with(b"OggS") {
+		ogg_vorbis_points(audio)
+	} else {
+		None
+	};
+	points.unwrap_or_default()
+}
+
+/// Keeps the first point of every SEEK_INTERVAL from `(millis, offset)` candidates in stream order.
+fn thin_out(candidates: impl Iterator<Item = (u64, u64)>) -> Vec<SeekPoint> {
+	let mut points: Vec<SeekPoint> = Vec::new();
+	for (millis, offset) in candidates {
+		let due = points.last().map(|p| p.millis + SEEK_INTERVAL_MS).unwrap_or(0);
+		if millis >= due {
+			points.push(SeekPoint { millis, offset });
+		}
+	}
+	points
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// FLAC
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// Walks the metadata blocks for STREAMINFO, then scans the frames that follow.
+fn flac_points(audio: &[u8]) -> Option<Vec<SeekPoint>> {
+	let mut pos = 4;
+	let mut stream_info = None;
+	loop {
+		let header = audio.get(pos..pos + 4)?;
+		let is_last = header[0] & 0x80 != 0;
+		let block_type = header[0] & 0x7F;
+		let len = u32::from_be_bytes([0, header[1], header[2], header[3]]) as usize;
+		let body = audio.get(pos + 4..pos + 4 + len)?;
+		if block_type == 0 && len >= 18 {
+			let min_block = u16::from_be_bytes([body[0], body[1]]) as u64;
+			let max_block = u16::from_be_bytes([body[2], body[3]]) as u64;
+			let sample_rate = (u32::from_be_bytes([0, body[10], body[11], body[12]]) >> 4) as u64;
+			stream_info = Some((min_block, max_block, sample_rate));
+		}
+		pos += 4 + len;
+		if is_last { break; }
+	}
+
+	let (min_block, max_block, sample_rate) = stream_info?;
+	if sample_rate == 0 {
+		return None;
+	}
+	let fixed_block = (min_block == max_block).then_some(min_block);
+
+	let frames = (pos..audio.len().saturating_sub(1))
+		.filter(|&p| audio[p] == 0xFF && audio[p + 1] & 0xFE == 0xF8)
+		.filter_map(|p| flac_frame_sample(&audio[p..], fixed_block).map(|sample| (sample * 1000 / sample_rate, p as u64)));
+	Some(thin_out(frames))
+}
+
+/// Parses a FLAC frame header at the start of `frame` and returns the number of its first sample.
+/// Returns `None` if the bytes aren't a valid frame header (bad CRC-8 or reserved values).
+fn flac_frame_sample(frame: &[u8], fixed_block: Option<u64>) -> Option<u64> {
+	let variable = frame[1] & 0x01 != 0;
+	let block_code = frame.get(2)? >> 4;
+	let rate_code = frame[2] & 0x0F;
+	let channel_code = frame.get(3)? >> 4;
+	let size_code = (frame[3] >> 1) & 0x07;
+	if block_code == 0 || rate_code == 0x0F || channel_code > 10 || size_code == 3 || frame[3] & 0x01 != 0 {
+		return None;
+	}
+
+	// Frame or sample number, UTF-8 style variable-length
+	let (number, mut len) = read_utf8_number(frame.get(4..)?)?;
+	len += 4;
+	len += match block_code { 6 => 1, 7 => 2, _ => 0 };
+	len += match rate_code { 12 => 1, 13 | 14 => 2, _ => 0 };
+
+	let crc = *frame.get(len)?;
+	if crc8(&frame[..len]) != crc {
+		return None;
+	}
+
+	if variable {
+		Some(number)
+	} else {
+		Some(number * fixed_block?)
+	}
+}
+
+/// Decodes FLAC's UTF-8-like coded number. Returns the value and its length in bytes.
+fn read_utf8_number(bytes: &[u8]) -> Option<(u64, usize)> {
+	let first = *bytes.first()?;
+	let extra = match first.leading_ones() {
+		0 => return Some((first as u64, 1)),
+		n @ 2..=7 => n as usize - 1,
+		_ => return None,
+	};
+	let mut value = (first & (0x7F >> (extra + 1))) as u64;
+	for &byte in bytes.get(1..=extra)? {
+		if byte & 0xC0 != 0x80 {
+			return None;
+		}
+		value = (value << 6) | (byte & 0x3F) as u64;
+	}
+	Some((value, extra + 1))
+}
+
+/// CRC-8 used by FLAC frame headers (polynomial x^8 + x^2 + x + 1).
+fn crc8(bytes: &[u8]) -> u8 {
+	bytes.iter().fold(0u8, |mut crc, &byte| {
+		crc ^= byte;
+		for _ in 0..8 {
+			crc = if crc & 0x80 != 0 { (crc << 1) ^ 0x07 } else { crc << 1 };
+		}
+		crc
+	})
+}
+
+// ──────────────────────────────────────────────────────────────────────────────
+// OGG VORBIS
+// ──────────────────────────────────────────────────────────────────────────────
+
+/// One Ogg page header: where it starts, its continuation flag and granule position.
+struct OggPage {
+	offset: usize,
+	continued: bool,
+	granule: u64,
+}
+
+/// Walks the page headers of an Ogg stream.
+fn ogg_pages(audio: &[u8]) -> impl Iterator<Item = OggPage> + '_ {
+	let mut pos = 0;
+	std::iter::from_fn(move || {
+		let header = audio.get(pos..pos + 27)?;
+		if &header[..4] != b"OggS" {
+			return None;
+		}
+		let segments = header[26] as usize;
+		let lacing = audio.get(pos + 27..pos + 27 + segments)?;
+		let len = 27 + segments + lacing.iter().map(|&l| l as usize).sum::<usize>();
+		let page = OggPage {
+			offset: pos,
+			continued: header[5] & 0x01 != 0,
+			granule: u64::from_le_bytes(header[6..14].try_into().ok()?),
+		};
+		pos += len;
+		Some(page)
+	})
+}
+
+/// Seek points at page boundaries of a Vorbis stream, timed by the previous page's granule position.
+fn ogg_vorbis_points(audio: &[u8]) -> Option<Vec<SeekPoint>> {
+	// The identification header opens the first page: 0x01 "vorbis", version, channels, rate
+	let first = ogg_pages(audio).next()?;
+	let packet = audio.get(first.offset + 27 + audio[first.offset + 26] as usize..)?;
+	if packet.get(..7)? != b"\x01vorbis" {
+		return None;
+	}
+	let sample_rate = u32::from_le_bytes(packet.get(12..16)?.try_into().ok()?) as u64;
+	if sample_rate == 0 {
+		return None;
+	}
+
+	// Header pages have granule 0; pages that finish no packet have granule -1
+	let mut samples_before = 0;
+	let mut candidates = Vec::new();
+	for page in ogg_pages(audio) {
+		if page.granule == 0 || page.granule == u64::MAX {
+			continue;
+		}
+		// Pages that start mid-packet can't be decoded on their own
+		if !page.continued {
+			candidates.push((samples_before * 1000 / sample_rate, page.offset as u64));
+		}
+		samples_before = page.granule;
+	}
+	Some(thin_out(candidates.into_iter()))
+}
@@ -0,0 +1,94 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// SELFTEST MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// `rewind selftest` runs a tiny cassette bundled into the binary (a 1×1 cover
+// and a fifth of a second of beep) through the same steps as a real one: the
+// CRC32 seal, the TOC, the strict layout checks, the track checksum, decoding
+// and finally playback. If this passes but a cassette doesn't, the cassette is
+// the problem; if this fails too, the install is. The bundled cassette is also
+// a known-good fixture for the tests (`assets/cassettes/selftest.png`).
+
+use std::io::Cursor;
+use std::time::Duration;
+use rodio::{Sink, Source};
+use crate::format::Cassette;
+use crate::logger::{log, LogLevel};
+use crate::playback::{decode_with_hint, format_hint, open_output_stream};
+
+/// A minimal valid cassette: one 8 kHz mono WAV track behind a 1×1 PNG.
+pub const SELFTEST_CASSETTE: &[u8] = include_bytes!("../assets/cassettes/selftest.png");
+
+/// Inspects the bundled cassette, decodes its track and (unless `silent`) plays it on the
+/// default output device. Prints a pass/fail line per step and returns whether all passed.
+pub fn selftest(silent: bool) -> bool {
+	log(LogLevel::Info, &format!("Testing the install with a bundled {} byte cassette...", SELFTEST_CASSETTE.len()));
+
+	let cassette = match Cassette::from_bytes(SELFTEST_CASSETTE.to_vec()) {
+		Ok(cassette) => cassette,
+		Err(e) => { log(LogLevel::Error, &format!("Reading: {}", e)); return false; }
+	};
+	let header = &cassette.layout.header;
+	log(LogLevel::Success, &format!("Reading: seal intact, format v{}, {} track(s).", header.version, header.entries.len()));
+
+	if let Err(e) = cassette.layout.check_strict(&mut Cursor::new(SELFTEST_CASSETTE)) {
+		log(LogLevel::Error, &format!("Layout: {}", e));
+		return false;
+	}
+	log(LogLevel::Success, "Layout: passes the strict checks.");
+
+	let (Some(entry), Some(audio)) = (header.entries.first(), cassette.track(0)) else {
+		log(LogLevel::Error, "Track: the bundled cassette has no readable track.");
+		return false;
+	};
+	if entry.crc.is_some_and(|crc| crc != crc32fast::hash(audio)) {
+		log(LogLevel::Error, &format!("Track: '{}' doesn't match its checksum.", entry.name));
+		return false;
+	}
+	log(LogLevel::Success, &format!("Track: '{}' ({} bytes) matches its checksum.", entry.name, audio.len()));
+
+	let hint = format_hint(entry);
+	let decode = || decode_with_hint(Cursor::new(audio.to_vec()), entry.size, hint.as_deref());
+	match decode() {
+		Ok(source) => {
+			let (rate, channels) = (source.sample_rate(), source.channels());
+			let samples = source.count();
+			if samples == 0 {
+				log(LogLevel::Error, "Decoding: the track decoded to no audio.");
+				return false;
+			}
+			log(LogLevel::Success, &format!("Decoding: {} samples at {} Hz, {} channel(s).", samples, rate, channels));
+		}
+		Err(e) => { log(LogLevel::Error, &format!("Decoding: {}", e)); return false; }
+	}
+
+	if silent {
+		log(LogLevel::Info, "Playback: skipped (--silent).");
+	} else if !play(decode().ok()) {
+		log(LogLevel::Info, "Everything but audio output works. `rewind doctor` looks at the sound setup in more detail.");
+		return false;
+	}
+
+	log(LogLevel::Success, "Self-test passed. This install reads and plays cassettes.");
+	true
+}
+
+/// Plays the decoded beep on the default device and waits for it to end.
+fn play<S: Source + Send + 'static>(source: Option<S>) -> bool {
+	let mut stream = match open_output_stream(None) {
+		Ok((stream, _)) => stream,
+		Err(e) => { log(LogLevel::Error, &format!("Playback: {}", e)); return false; }
+	};
+	stream.log_on_drop(false);
+	let Some(source) = source else {
+		log(LogLevel::Error, "Playback: the track could not be decoded a second time.");
+		return false;
+	};
+	let sink = Sink::connect_new(stream.mixer());
+	sink.append(source);
+	sink.sleep_until_end();
+	// Give the device a moment to drain before the stream is dropped
+	std::thread::sleep(Duration::from_millis(100));
+	log(LogLevel::Success, "Playback: the beep went out to the default device.");
+	true
+}
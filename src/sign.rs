@@ -0,0 +1,71 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// SIGN MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Optional creator signatures (format v5+). `record --sign <key.pem>` signs the
+// cassette with an Ed25519 key and stores the public key and signature in the
+// TOC; `inspect` reports whether a cassette is signed, unsigned or tampered
+// with, and `inspect --pubkey <key.pem>` also checks who signed it.
+//
+// The signature is Ed25519ph (RFC 8032) over SHA-512 of the TOC encoded
+// without its signature or track CRCs, followed by every audio byte. The cover image and the
+// CRC32 seal are not covered; the CRC still guards against accidental damage.
+// Keys are PKCS#8/SPKI PEM files, e.g. from
+//   openssl genpkey -algorithm ed25519 -out key.pem
+//   openssl pkey -in key.pem -pubout -out key.pub.pem
+
+use std::fs;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Digest, Sha512, Signature, SigningKey, VerifyingKey};
+use crate::format::CassetteSignature;
+
+/// Domain separation, so a cassette signature can't be replayed as anything else.
+const SIGNATURE_CONTEXT: &[u8] = b"rewind.png cassette";
+
+/// Outcome of checking a signed cassette.
+pub enum SignatureStatus {
+	/// Signed, and the signature matches the contents.
+	Valid { fingerprint: String },
+	/// Signed, but the contents or the signature were changed afterwards.
+	Invalid,
+}
+
+/// Loads an Ed25519 private key from a PKCS#8 PEM file.
+pub fn load_signing_key(path: &str) -> Result<SigningKey, String> {
+	let pem = fs::read_to_string(path).map_err(|e| format!("Cannot read signing key '{}': {}", path, e))?;
+	SigningKey::from_pkcs8_pem(&pem).map_err(|e| format!("'{}' is not an Ed25519 private key in PKCS#8 PEM form: {}", path, e))
+}
+
+/// Loads an Ed25519 public key from an SPKI PEM file.
+pub fn load_verifying_key(path: &str) -> Result<VerifyingKey, String> {
+	let pem = fs::read_to_string(path).map_err(|e| format!("Cannot read public key '{}': {}", path, e))?;
+	VerifyingKey::from_public_key_pem(&pem).map_err(|e| format!("'{}' is not an Ed25519 public key in PEM form: {}", path, e))
+}
+
+/// Starts the digest a signature covers; feed it the audio bytes next.
+pub fn start_digest(unsigned_toc: &[u8]) -> Sha512 {
+	let mut digest = Sha512::new();
+	digest.update(unsigned_toc);
+	digest
+}
+
+/// Signs a finished digest.
+pub fn sign(key: &SigningKey, digest: Sha512) -> Result<CassetteSignature, String> {
+	let signature = key.sign_prehashed(digest, Some(SIGNATURE_CONTEXT)).map_err(|e| format!("Signing failed: {}", e))?;
+	Ok(CassetteSignature { public_key: key.verifying_key().to_bytes(), signature: signature.to_bytes() })
+}
+
+/// Checks a stored signature against a finished digest.
+pub fn verify(signature: &CassetteSignature, digest: Sha512) -> SignatureStatus {
+	let Ok(key) = VerifyingKey::from_bytes(&signature.public_key) else { return SignatureStatus::Invalid };
+	let sig = Signature::from_bytes(&signature.signature);
+	match key.verify_prehashed_strict(digest, Some(SIGNATURE_CONTEXT), &sig) {
+		Ok(()) => SignatureStatus::Valid { fingerprint: fingerprint(&signature.public_key) },
+		Err(_) => SignatureStatus::Invalid,
+	}
+}
+
+/// Short, human-comparable form of a public key (first 8 bytes as hex pairs).
+pub fn fingerprint(public_key: &[u8]) -> String {
+	public_key.iter().take(8).map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(":")
+}
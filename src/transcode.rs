@@ -0,0 +1,211 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// TRANSCODE MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Decodes audio with rodio and re-encodes it. Used by `record --trim` to keep
+// only a time range of a source track, and by `extract --to` to convert tracks
+// on the way out. Trimmed output is always WAV (32-bit float PCM), so trimming
+// a compressed input (MP3/OGG) is not lossless: the decoded samples are stored
+// as-is, but the original encoding is gone. Extracting to MP3 is lossy as well.
+// Either way the source's tag (title, artist, album, art) is copied onto the
+// new file, as ID3v2, so the track still shows up right in players.
+
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek};
+use std::path::Path;
+use std::time::Duration;
+use clap::ValueEnum;
+use lofty::config::WriteOptions;
+use lofty::file::{FileType, TaggedFileExt};
+use lofty::probe::Probe;
+use lofty::tag::{Tag, TagExt};
+use mp3lame_encoder::{Bitrate, Builder, FlushNoGap, InterleavedPcm, MonoPcm, Quality};
+use rodio::{ChannelCount, Decoder, SampleRate, Source};
+use crate::io::{open_file, parse_timestamp, format_duration, TempFile};
+use crate::logger::{log, LogLevel};
+
+/// Bitrate for MP3 output. High enough to be transparent for most listeners.
+const MP3_BITRATE: Bitrate = Bitrate::Kbps256;
+
+/// Target format for `extract --to`.
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+	/// Uncompressed 16-bit PCM
+	Wav,
+	/// MP3 at 256 kbps (lossy)
+	Mp3,
+}
+
+impl OutputFormat {
+	pub fn extension(self) -> &'static str {
+		match self {
+			OutputFormat::Wav => "wav",
+			OutputFormat::Mp3 => "mp3",
+		}
+	}
+
+	fn file_type(self) -> FileType {
+		match self {
+			OutputFormat::Wav => FileType::Wav,
+			OutputFormat::Mp3 => FileType::Mpeg,
+		}
+	}
+}
+
+/// A time range to keep from one of the recorded tracks.
+#[derive(Clone, Debug)]
+pub struct TrimSpec {
+	/// 1-based track index, matching the order of the audio files.
+	pub track: usize,
+	pub start: Option<Duration>,
+	pub end: Option<Duration>,
+}
+
+/// Parses `<index>:<start>-<end>`, e.g. `2:1:30-3:00`, `1:10-` or `3:-0:45`.
+/// Either side of the range may be omitted to mean the start/end of the track.
+pub fn parse_trim(text: &str) -> Result<TrimSpec, String> {
+	let usage = || format!("Invalid trim '{}'. Expected <index>:<start>-<end>, e.g. 2:1:30-3:00", text);
+
+	let (index, range) = text.split_once(':').ok_or_else(usage)?;
+	let track: usize = index.trim().parse().map_err(|_| usage())?;
+	if track == 0 {
+		return Err("Track indices start at 1.".to_string());
+	}
+
+	let (start, end) = range.split_once('-').ok_or_else(usage)?;
+	let parse_side = |side: &str| -> Result<Option<Duration>, String> {
+		if side.trim().is_empty() { Ok(None) } else { parse_timestamp(side).map(Some).ok_or_else(usage) }
+	};
+	let (start, end) = (parse_side(start)?, parse_side(end)?);
+
+	if let (Some(s), Some(e)) = (start, end)
+		&& s >= e
+	{
+		return Err(format!("Trim '{}' ends before it starts.", text));
+	}
+
+	Ok(TrimSpec { track, start, end })
+}
+
+/// Decodes `path`, keeps only the range in `trim`, and writes it as a WAV file to a temporary
+/// file, returned rewound. Keep the `TempFile` until the file has been read.
+pub fn trim_to_wav(path: &Path, trim: &TrimSpec) -> Result<(TempFile, File), String> {
+	let file: File = open_file(path)?;
+	let decoder = Decoder::new(BufReader::new(file))
+		.map_err(|e| format!("Cannot decode '{}' for trimming: {}", path.display(), e))?;
+
+	let channels = decoder.channels();
+	let sample_rate = decoder.sample_rate();
+	let start = trim.start.unwrap_or_default();
+
+	let skipped = decoder.skip_duration(start);
+	let mut samples: Vec<f32> = match trim.end {
+		Some(end) => skipped.take_duration(end - start).collect(),
+		None => skipped.collect(),
+	};
+	// take_duration can stop mid-frame; keep whole frames only
+	samples.truncate(samples.len() - samples.len() % channels as usize);
+
+	if samples.is_empty() {
+		return Err(format!("Nothing left of '{}' after trimming from {}. Is the start past the end of the track?",
+			path.display(), format_duration(start.as_secs())));
+	}
+
+	let spec = hound::WavSpec {
+		channels,
+		sample_rate,
+		bits_per_sample: 32,
+		sample_format: hound::SampleFormat::Float,
+	};
+	let mut bytes = Cursor::new(Vec::new());
+	let mut writer = hound::WavWriter::new(&mut bytes, spec).map_err(|e| e.to_string())?;
+	for sample in samples {
+		writer.write_sample(sample).map_err(|e| e.to_string())?;
+	}
+	writer.finalize().map_err(|e| e.to_string())?;
+
+	let tag = open_file(path).ok().and_then(|file| read_tag(BufReader::new(file)));
+	let wav = with_tag(bytes.into_inner(), tag, FileType::Wav);
+	TempFile::with_contents(&format!("{}.wav", trim.track), &wav)
+}
+
+/// Decodes an embedded track (`audio` holds the stored file bytes) and re-encodes it as `format`.
+pub fn convert(audio: Vec<u8>, format: OutputFormat) -> Result<Vec<u8>, String> {
+	let tag = read_tag(Cursor::new(&audio));
+	let decoder = Decoder::new(Cursor::new(audio)).map_err(|e| format!("Cannot decode track: {}", e))?;
+	let channels = decoder.channels();
+	let sample_rate = decoder.sample_rate();
+	let samples: Vec<f32> = decoder.collect();
+
+	let encoded = match format {
+		OutputFormat::Wav => encode_wav_16(channels, sample_rate, &samples),
+		OutputFormat::Mp3 => encode_mp3(channels, sample_rate, &samples),
+	}?;
+	Ok(with_tag(encoded, tag, format.file_type()))
+}
+
+/// The tag players show for an audio file: its primary tag, or the first one it has.
+fn read_tag<R: Read + Seek>(reader: R) -> Option<Tag> {
+	let tagged = Probe::new(reader).guess_file_type().ok()?.read().ok()?;
+	tagged.primary_tag().or_else(|| tagged.first_tag()).cloned()
+}
+
+/// Writes `tag` into freshly encoded audio, converted to the usual tag type of `file_type`.
+/// Fields that type can't hold are dropped. If writing fails the audio is returned untagged.
+fn with_tag(encoded: Vec<u8>, tag: Option<Tag>, file_type: FileType) -> Vec<u8> {
+	let Some(mut tag) = tag else { return encoded };
+	tag.re_map(file_type.primary_tag_type());
+	let mut tagged = Cursor::new(encoded.clone());
+	match tag.save_to(&mut tagged, WriteOptions::default()) {
+		Ok(()) => tagged.into_inner(),
+		Err(e) => {
+			log(LogLevel::Warning, &format!("Cannot copy the tags to the converted audio: {}", e));
+			encoded
+		}
+	}
+}
+
+/// Encodes interleaved samples as 16-bit PCM WAV, which every device can play.
+fn encode_wav_16(channels: ChannelCount, sample_rate: SampleRate, samples: &[f32]) -> Result<Vec<u8>, String> {
+	let spec = hound::WavSpec {
+		channels,
+		sample_rate,
+		bits_per_sample: 16,
+		sample_format: hound::SampleFormat::Int,
+	};
+	let mut bytes = Cursor::new(Vec::new());
+	let mut writer = hound::WavWriter::new(&mut bytes, spec).map_err(|e| e.to_string())?;
+	for &sample in samples {
+		let sample = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+		writer.write_sample(sample).map_err(|e| e.to_string())?;
+	}
+	writer.finalize().map_err(|e| e.to_string())?;
+
+	Ok(bytes.into_inner())
+}
+
+/// Encodes interleaved samples as a constant-bitrate MP3 with LAME.
+fn encode_mp3(channels: ChannelCount, sample_rate: SampleRate, samples: &[f32]) -> Result<Vec<u8>, String> {
+	if !(1..=2).contains(&channels) {
+		return Err(format!("MP3 only supports mono and stereo, but this track has {} channels.", channels));
+	}
+
+	let mut builder = Builder::new().ok_or("Cannot initialize the MP3 encoder.")?;
+	builder.set_num_channels(channels as u8).map_err(|e| format!("MP3 encoder: {}", e))?;
+	builder.set_sample_rate(sample_rate).map_err(|e| format!("MP3 encoder: {}", e))?;
+	builder.set_brate(MP3_BITRATE).map_err(|e| format!("MP3 encoder: {}", e))?;
+	builder.set_quality(Quality::Best).map_err(|e| format!("MP3 encoder: {}", e))?;
+	let mut encoder = builder.build().map_err(|e| format!("MP3 encoder: {}", e))?;
+
+	let frames = samples.len() / channels as usize;
+	let mut mp3 = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(frames));
+	let encoded = if channels == 1 {
+		encoder.encode_to_vec(MonoPcm(samples), &mut mp3)
+	} else {
+		encoder.encode_to_vec(InterleavedPcm(&samples[..frames * 2]), &mut mp3)
+	};
+	encoded.map_err(|e| format!("MP3 encoding failed: {}", e))?;
+	encoder.flush_to_vec::<FlushNoGap>(&mut mp3).map_err(|e| format!("MP3 encoding failed: {}", e))?;
+
+	Ok(mp3)
+}
@@ -6,11 +6,13 @@
 // skeuomorphic cassette player with clickable buttons, volume control, and
 // a scrolling playlist. Fixed-size design inspired by vintage tape players.
 
-use std::io::{self, Read, Seek, SeekFrom, Cursor};
-use std::sync::{Arc, atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering}};
-use std::time::Duration;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Cursor};
+use std::sync::{Arc, atomic::{AtomicU64, AtomicU8, Ordering}};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
 use std::thread;
-use std::fs::OpenOptions;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
 
 use crossterm::{
 	event::{self, Event, KeyCode, KeyEventKind, MouseEvent, MouseEventKind, MouseButton, EnableMouseCapture, DisableMouseCapture},
@@ -25,12 +27,25 @@ use ratatui::{
 	widgets::Paragraph,
 	Frame, Terminal,
 };
-use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use rodio::{Decoder, OutputStream, Sink, Source};
 use lofty::file::{AudioFile, TaggedFileExt};
 use lofty::probe::Probe;
 use lofty::tag::Accessor;
 
-use crate::io::{open_file, find_iend, format_duration};
+use crate::io::{open_file, format_duration, fit, fit_center, truncate, track_buffer, Region};
+use crate::wizard::pick_cassette;
+use crate::constants::FLIP_PAUSE;
+use crate::notify::notify_now_playing;
+use crate::history::record_play;
+use crate::playback::{decode_with_hint, format_hint, open_output_stream, output_device_names, start_index, PlaybackError};
+use crate::mpris::{MediaCommand, MediaControls, MediaStatus, NowPlaying};
+use crate::equalizer::{BAND_COUNT, BAND_NAMES, EqSettings, Equalizer};
+use crate::balance::{Balance, BalanceSettings, describe};
+use crate::mono::Mono;
+use crate::format::{CassetteLayout, Side, TocEntry, BLANK_CASSETTE};
+use crate::art::{cover_thumbnail, preview_pixels};
+use crate::lyrics::{current_line, parse_lrc, LyricLine};
+use crate::png::COVER_EXTENSIONS;
 
 // ══════════════════════════════════════════════════════════════════════════════
 // CONSTANTS
@@ -51,6 +66,40 @@ const BTN_VOL_UP: (u16, u16, u16) = (51, 12, 5);
 // Playlist first item Y position
 const PLAYLIST_START_Y: u16 = 17;
 
+// Reel hub spoke frames (top row, bottom row) and how long each is shown
+pub const REEL_FRAMES: [(&str, &str); 4] = [("╭─╮", "╰─╯"), ("╭╲╮", "╰╲╯"), ("╭│╮", "╰│╯"), ("╭╱╮", "╰╱╯")];
+const REEL_FRAME_INTERVAL: Duration = Duration::from_millis(150);
+
+/// Longest wait for input before the loop checks on playback again
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Starting volume in percent when `TuiOptions::volume` is unset
+const DEFAULT_VOLUME: u8 = 80;
+
+/// How often the playback position is read when `TuiOptions::progress_interval` is unset
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Width of the cassette browser pane shown left of the player in library mode
+const LIBRARY_WIDTH: u16 = 30;
+
+// How long notices (e.g. "Loaded mixtape.png") stay in the hint line
+const NOTICE_DURATION: Duration = Duration::from_secs(3);
+
+// How far [ and ] move within the playing track
+const SEEK_STEP: Duration = Duration::from_secs(5);
+// Scrub preview: length of each blip, and how long after the last seek step playback resumes
+const SCRUB_BLIP: Duration = Duration::from_millis(150);
+const SCRUB_SETTLE: Duration = Duration::from_millis(350);
+
+// Equalizer adjustment per key press
+const EQ_STEP_DB: i32 = 2;
+// Balance adjustment per key press, in percent
+const BALANCE_STEP: i32 = 10;
+
+// Track art preview right of the player: pixels per side (two pixel rows per text row)
+const ART_PREVIEW_SIZE: u16 = 16;
+const ART_PANE_WIDTH: u16 = ART_PREVIEW_SIZE + 2;
+
 // ══════════════════════════════════════════════════════════════════════════════
 // DATA STRUCTURES
 // ══════════════════════════════════════════════════════════════════════════════
@@ -61,9 +110,40 @@ pub struct Track {
 	pub name: String,
 	pub size: u64,
 	pub offset: u64,
+	pub side: Side,
+	pub number: u64, // Playlist number: the stored track number, or the position
 	pub artist: String,
 	pub title: String,
 	pub duration_secs: u64,
+	pub art: Option<Vec<[u8; 3]>>, // Preview pixels: the track's own art, or the cassette cover's
+	pub lyrics: Vec<LyricLine>, // Synced lyrics, empty when the track has none
+	pub hint: Option<String>, // Decoder format hint (see `format_hint`)
+}
+
+/// A line in the playlist box: either a track or a side header
+#[derive(Clone, Copy, PartialEq)]
+pub enum PlaylistRow {
+	Side(Side),
+	Track(usize),
+}
+
+/// Lays out the playlist, inserting a header before each side when the tape has more than one
+fn playlist_rows(tracks: &[Track]) -> Vec<PlaylistRow> {
+	let has_sides = tracks.iter().any(|t| t.side != Side::A);
+	let mut rows = Vec::with_capacity(tracks.len() + 2);
+	for (idx, track) in tracks.iter().enumerate() {
+		if has_sides && (idx == 0 || tracks[idx - 1].side != track.side) {
+			rows.push(PlaylistRow::Side(track.side));
+		}
+		rows.push(PlaylistRow::Track(idx));
+	}
+	rows
+}
+
+/// A pause between sides while the listener "flips the tape"
+pub struct TapeFlip {
+	pub next_track: usize,
+	pub started: Instant,
 }
 
 /// Player state
@@ -74,48 +154,283 @@ pub enum PlayerState {
 	Paused,
 }
 
+/// A decoder reading one track straight from the cassette file
+type TrackSource = Decoder<BufReader<Region<File>>>;
+
+/// Outcome of opening a track on the loader thread
+enum TrackLoadResult {
+	Ready(TrackSource),
+	/// The cassette couldn't be read, or the track couldn't be decoded
+	Failed(PlaybackError),
+	/// Another track was started before this one finished loading
+	Cancelled,
+}
+
+/// A track being opened on a background thread
+pub struct TrackLoad {
+	pub track: usize,
+	generation: u64,
+	receiver: Receiver<TrackLoadResult>,
+}
+
+/// Seek steps in progress with the scrub preview on. The track is held while a short blip
+/// plays at each new position, and carries on from the last one once the keys are let go.
+pub struct Scrub {
+	target: Duration,
+	last_step: Instant,
+	/// Plays the blips, next to the held track
+	sink: Sink,
+}
+
+/// Which pane receives navigation keys in library mode
+#[derive(PartialEq, Clone, Copy)]
+pub enum Pane {
+	Library,
+	Player,
+}
+
+/// The cassettes found in a directory, shown in the left pane
+pub struct Library {
+	pub entries: Vec<PathBuf>,
+	pub selected: usize,
+	pub scroll: usize,
+}
+
+impl Library {
+	/// Lists every `*.png` (and `*.apng`) in `dir`, sorted by name
+	pub fn scan(dir: &Path) -> Result<Self, String> {
+		let read = fs::read_dir(dir).map_err(|e| format!("Cannot read directory '{}': {}", dir.display(), e))?;
+		let mut entries: Vec<PathBuf> = read
+			.filter_map(|entry| entry.ok().map(|e| e.path()))
+			.filter(|path| path.is_file() && path.extension().is_some_and(|ext| COVER_EXTENSIONS.iter().any(|known| ext.eq_ignore_ascii_case(known))))
+			.collect();
+		entries.sort();
+		if entries.is_empty() {
+			return Err(format!("No cassettes (*.png, *.apng) found in '{}'.", dir.display()));
+		}
+		Ok(Library { entries, selected: 0, scroll: 0 })
+	}
+
+	/// Move the selection by `step`, wrapping around, and keep it within `visible` rows
+	fn move_selection(&mut self, step: isize, visible: usize) {
+		let len = self.entries.len() as isize;
+		self.selected = (self.selected as isize + step).rem_euclid(len) as usize;
+		if self.selected < self.scroll {
+			self.scroll = self.selected;
+		} else if self.selected >= self.scroll + visible {
+			self.scroll = self.selected + 1 - visible;
+		}
+	}
+}
+
+/// Launch-time settings for the TUI (and the daemon, which reuses `App`)
+#[derive(Default)]
+pub struct TuiOptions {
+	pub auto_flip: bool,
+	pub notify: bool,
+	/// 1-based track to select on launch
+	pub start_track: Option<usize>,
+	/// Start playing the selected track right away
+	pub autoplay: bool,
+	/// Output device to play through (the default device when unset)
+	pub device: Option<String>,
+	/// Starting volume in percent (`DEFAULT_VOLUME` when unset)
+	pub volume: Option<u8>,
+	/// How often the progress bar follows the playback position (`PROGRESS_INTERVAL` when unset)
+	pub progress_interval: Option<Duration>,
+	/// Downmix stereo tracks to mono
+	pub mono: bool,
+	/// Play short blips while seeking (toggled with Z)
+	pub scrub_preview: bool,
+}
+
 /// Main application state
 pub struct App {
-	pub cassette_path: String,
+	pub cassette_path: PathBuf,
 	pub tracks: Vec<Track>,
 	pub selected_track: usize,
 	pub player_state: PlayerState,
 	pub current_track: Option<usize>,
-	pub progress_secs: Arc<AtomicU64>,
+	pub progress: Duration, // Position in the current track, as of the last progress update
+	pub progress_interval: Duration,
+	pub last_progress_update: Instant,
 	pub should_quit: bool,
 	pub stream: Option<OutputStream>,
 	pub sink: Option<Sink>,
-	pub is_playing: Arc<AtomicBool>,
-	pub is_paused: Arc<AtomicBool>,
 	pub playback_generation: Arc<AtomicU64>,
 	pub volume: Arc<AtomicU8>, // 0-100 (percentage)
-	pub playlist_scroll: usize,
+	pub playlist_scroll: usize, // First visible playlist row (rows include side headers)
+	pub cassette_error: Option<String>, // Set when the cassette file can no longer be read
+	pub playback_error: Option<PlaybackError>, // Why the last track failed to start (cleared by the next one that does)
+	pub flip: Option<TapeFlip>, // Set between sides until the tape is flipped
+	pub auto_flip: bool, // Continue after FLIP_PAUSE instead of waiting for a key
+	pub reel_frame: usize, // Index into REEL_FRAMES, advanced while the tape moves
+	pub last_reel_tick: Instant,
+	pub eq: Arc<EqSettings>, // Shared with the playing source; kept for the whole session
+	pub eq_band: Option<usize>, // Selected band while the EQ panel is open
+	pub balance: Arc<BalanceSettings>, // Left/right balance, kept for the whole session
+	pub mono: bool, // Downmix stereo tracks to mono (before the balance, so it still pans)
+	pub notify: bool, // Desktop notification on every track change
+	pub media: Option<MediaControls>, // MPRIS media keys, when available
+	pub notice: Option<(String, Instant)>, // Short-lived message shown in the hint line
+	pub library: Option<Library>, // Cassette browser, when launched on a directory
+	pub focus: Pane,
+	pub loading: Option<TrackLoad>, // Track being opened in the background
+	pub device: Option<String>, // Output device for the next track; `None` is the default device
+	pub auto_advance: bool, // Go on to the next track when one finishes (otherwise stop after each)
+	pub tape_minutes: Option<u16>, // Nominal tape length (90 for a C90), shown on the shell when set
+	pub scrub_preview: bool, // Play blips while seeking (decodes on every step, so it's opt-in)
+	pub scrub: Option<Scrub>, // Seek steps in progress with the scrub preview on
 }
 
 impl App {
-	/// Creates a new App from a cassette file path
-	pub fn new(cassette_path: &str) -> Result<Self, String> {
-		let tracks = load_tracks(cassette_path)?;
+	/// Creates a new App from a cassette file path.
+	/// `on_progress(done, total)` is called as each track's metadata is read.
+	pub fn new(cassette_path: &Path, options: &TuiOptions, on_progress: &mut dyn FnMut(usize, usize)) -> Result<Self, String> {
+		let (tracks, tape_minutes) = load_tracks(cassette_path, on_progress)?;
 		if tracks.is_empty() {
-			return Err("This cassette is blank. No tracks found.".to_string());
+			return Err(BLANK_CASSETTE.to_string());
 		}
+		let selected_track = start_index(options.start_track, tracks.len());
 
-		Ok(App {
-			cassette_path: cassette_path.to_string(),
+		let mut app = App {
+			cassette_path: cassette_path.to_path_buf(),
 			tracks,
-			selected_track: 0,
+			selected_track,
 			player_state: PlayerState::Stopped,
 			current_track: None,
-			progress_secs: Arc::new(AtomicU64::new(0)),
+			progress: Duration::ZERO,
+			progress_interval: options.progress_interval.unwrap_or(PROGRESS_INTERVAL),
+			last_progress_update: Instant::now(),
 			should_quit: false,
 			stream: None,
 			sink: None,
-			is_playing: Arc::new(AtomicBool::new(false)),
-			is_paused: Arc::new(AtomicBool::new(false)),
 			playback_generation: Arc::new(AtomicU64::new(0)),
-			volume: Arc::new(AtomicU8::new(80)), // Default volume 80%
+			volume: Arc::new(AtomicU8::new(options.volume.unwrap_or(DEFAULT_VOLUME))), // 0-100 (percentage)
 			playlist_scroll: 0,
-		})
+			cassette_error: None,
+			playback_error: None,
+			flip: None,
+			auto_flip: options.auto_flip,
+			reel_frame: 0,
+			last_reel_tick: Instant::now(),
+			eq: Arc::new(EqSettings::default()),
+			eq_band: None,
+			balance: Arc::new(BalanceSettings::default()),
+			mono: options.mono,
+			notify: options.notify,
+			media: MediaControls::start(),
+			notice: None,
+			library: None,
+			focus: Pane::Player,
+			loading: None,
+			device: options.device.clone(),
+			auto_advance: true,
+			tape_minutes,
+			scrub_preview: options.scrub_preview,
+			scrub: None,
+		};
+		app.update_scroll();
+		if options.autoplay {
+			app.play_selected();
+		}
+		Ok(app)
+	}
+
+	/// Re-read the cassette from disk (e.g. after it was moved back or a mount came back)
+	pub fn reload(&mut self) {
+		match load_tracks(&self.cassette_path, &mut |_, _| {}) {
+			Ok((tracks, tape_minutes)) if !tracks.is_empty() => {
+				self.stop();
+				self.tracks = tracks;
+				self.tape_minutes = tape_minutes;
+				self.selected_track = self.selected_track.min(self.tracks.len() - 1);
+				self.playlist_scroll = 0;
+				self.update_scroll();
+				self.cassette_error = None;
+			}
+			Ok(_) => self.cassette_error = Some(BLANK_CASSETTE.to_string()),
+			Err(e) => self.cassette_error = Some(e),
+		}
+	}
+
+	/// Swap in another cassette, resetting playback and selection.
+	/// If it can't be loaded, the current cassette stays and a notice explains why.
+	pub fn open(&mut self, path: &Path) {
+		let (tracks, tape_minutes) = match load_tracks(path, &mut |_, _| {}) {
+			Ok((tracks, tape_minutes)) if !tracks.is_empty() => (tracks, tape_minutes),
+			Ok(_) => return self.show_notice(BLANK_CASSETTE),
+			Err(e) => return self.show_notice(&e),
+		};
+
+		self.stop();
+		self.cassette_path = path.to_path_buf();
+		self.tracks = tracks;
+		self.tape_minutes = tape_minutes;
+		self.selected_track = 0;
+		self.playlist_scroll = 0;
+		self.cassette_error = None;
+		let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+		self.show_notice(&format!("Loaded {}", name));
+	}
+
+	/// Open the cassette selected in the library pane
+	pub fn open_library_selection(&mut self) {
+		let Some(path) = self.library.as_ref().map(|lib| lib.entries[lib.selected].clone()) else { return };
+		if path != self.cassette_path {
+			self.open(&path);
+		}
+	}
+
+	/// Switch keyboard focus between the library and the player (library mode only)
+	pub fn toggle_focus(&mut self) {
+		if self.library.is_some() {
+			self.focus = if self.focus == Pane::Player { Pane::Library } else { Pane::Player };
+		}
+	}
+
+	/// Move the library selection (does not open it until Enter)
+	pub fn move_library_selection(&mut self, step: isize) {
+		if let Some(ref mut library) = self.library {
+			library.move_selection(step, library_rows_visible(&self.tracks));
+		}
+	}
+
+	/// Open the cassette on the clicked library row
+	pub fn click_library_row(&mut self, y: u16) {
+		let visible = library_rows_visible(&self.tracks);
+		let Some(ref mut library) = self.library else { return };
+		// Row 0 is the pane's top border
+		let Some(row) = (y as usize).checked_sub(1).filter(|&r| r < visible) else { return };
+		if library.scroll + row < library.entries.len() {
+			library.selected = library.scroll + row;
+			self.focus = Pane::Library;
+			self.open_library_selection();
+		}
+	}
+
+	/// Left edge of the player widget
+	fn player_x(&self) -> u16 {
+		if self.library.is_some() { LIBRARY_WIDTH } else { 0 }
+	}
+
+	/// Pick a cassette with the native file dialog and open it (cancelling keeps the current one)
+	pub fn open_with_picker(&mut self) {
+		if let Some(path) = pick_cassette() {
+			self.open(&path);
+		}
+	}
+
+	/// Stop and explain why the track couldn't be played
+	fn playback_failed(&mut self, error: PlaybackError) {
+		self.stop();
+		self.show_notice(&error.to_string());
+		self.playback_error = Some(error);
+	}
+
+	/// Show a message in the hint line for NOTICE_DURATION
+	fn show_notice(&mut self, message: &str) {
+		self.notice = Some((message.to_string(), Instant::now()));
 	}
 
 	/// Move selection up
@@ -142,11 +457,17 @@ impl App {
 
 	/// Update scroll position to keep selection visible
 	fn update_scroll(&mut self) {
-		let visible = self.tracks.len().min(MAX_PLAYLIST_VISIBLE);
-		if self.selected_track < self.playlist_scroll {
-			self.playlist_scroll = self.selected_track;
-		} else if self.selected_track >= self.playlist_scroll + visible {
-			self.playlist_scroll = self.selected_track - visible + 1;
+		let rows = playlist_rows(&self.tracks);
+		let visible = rows.len().min(MAX_PLAYLIST_VISIBLE);
+		let Some(mut row) = rows.iter().position(|r| *r == PlaylistRow::Track(self.selected_track)) else { return };
+		if row < self.playlist_scroll {
+			// Scrolling up onto the first track of a side also reveals its header
+			if row > 0 && matches!(rows[row - 1], PlaylistRow::Side(_)) {
+				row -= 1;
+			}
+			self.playlist_scroll = row;
+		} else if row >= self.playlist_scroll + visible {
+			self.playlist_scroll = row + 1 - visible;
 		}
 	}
 
@@ -164,68 +485,103 @@ impl App {
 
 	/// Play a specific track
 	pub fn play_track(&mut self, idx: usize) {
-		if idx >= self.tracks.len() { return; }
+		if idx >= self.tracks.len() || self.cassette_error.is_some() { return; }
+		if self.tracks[idx].size == 0 {
+			self.stop();
+			return self.show_notice("That track is empty.");
+		}
 		self.stop_internal();
+		self.flip = None;
 
 		self.current_track = Some(idx);
 		self.selected_track = idx;
 		self.update_scroll();
-		self.progress_secs.store(0, Ordering::SeqCst);
+		self.progress = Duration::ZERO;
 
-		// Get track info before borrowing for load
+		// Open and decode off the UI thread; `poll_track_load` picks up the result.
+		// Starting another track (or stopping) bumps the generation, so stale loads are dropped.
+		let new_gen = self.playback_generation.fetch_add(1, Ordering::SeqCst) + 1;
+		let generation = Arc::clone(&self.playback_generation);
 		let cassette_path = self.cassette_path.clone();
-		let track_offset = self.tracks[idx].offset;
-		let track_size = self.tracks[idx].size;
-		let track_duration = self.tracks[idx].duration_secs;
-
-		// Load audio data
-		let audio_data = match load_track_data_raw(&cassette_path, track_offset, track_size) {
-			Ok(data) => data,
-			Err(_) => return,
+		let (offset, size) = (self.tracks[idx].offset, self.tracks[idx].size);
+		let hint = self.tracks[idx].hint.clone();
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			let _ = sender.send(open_track(&cassette_path, offset, size, hint.as_deref(), &generation, new_gen));
+		});
+		self.loading = Some(TrackLoad { track: idx, generation: new_gen, receiver });
+	}
+
+	/// Start playing a track once its background load finishes
+	pub fn poll_track_load(&mut self) {
+		let Some(ref load) = self.loading else { return };
+		let result = match load.receiver.try_recv() {
+			Ok(result) => result,
+			Err(TryRecvError::Empty) => return,
+			Err(TryRecvError::Disconnected) => TrackLoadResult::Cancelled,
 		};
+		let Some(load) = self.loading.take() else { return };
+		if load.generation != self.playback_generation.load(Ordering::SeqCst) { return; }
+
+		match result {
+			TrackLoadResult::Ready(source) => self.start_playback(load.track, source),
+			TrackLoadResult::Failed(e @ PlaybackError::FileRead(_)) => {
+				// Keep the playlist visible, but block playback until the cassette is reloaded
+				self.stop();
+				self.cassette_error = Some(e.to_string());
+			}
+			TrackLoadResult::Failed(e) => self.playback_failed(e),
+			TrackLoadResult::Cancelled => self.stop(),
+		}
+	}
 
+	/// Hand a loaded track to a fresh sink
+	fn start_playback(&mut self, idx: usize, source: TrackSource) {
 		// Set up audio output
-		let stream_handle = match OutputStreamBuilder::open_default_stream() {
-			Ok(s) => s,
-			Err(_) => return,
+		let stream_handle = match open_output_stream(self.device.as_deref()) {
+			Ok((s, warning)) => {
+				if let Some(warning) = warning {
+					// Only warn once; the default device is used from now on
+					self.device = None;
+					self.show_notice(&warning);
+				}
+				s
+			}
+			Err(e) => return self.playback_failed(e),
 		};
 
-		let sink = Sink::connect_new(&stream_handle.mixer());
+		let sink = Sink::connect_new(stream_handle.mixer());
 		sink.set_volume(self.get_volume_float());
 
-		let cursor = Cursor::new(audio_data);
-		let source = match Decoder::new(cursor) {
-			Ok(s) => s,
-			Err(_) => return,
-		};
-
-		sink.append(source);
+		sink.append(Balance::new(Equalizer::new(Mono::new(source, self.mono), Arc::clone(&self.eq)), Arc::clone(&self.balance)));
 		self.stream = Some(stream_handle);
 		self.sink = Some(sink);
 		self.player_state = PlayerState::Playing;
-		self.is_playing.store(true, Ordering::SeqCst);
-		self.is_paused.store(false, Ordering::SeqCst);
+		self.playback_error = None;
+		if let Err(e) = record_play(&self.cassette_path, &self.tracks[idx].name) {
+			self.show_notice(&e);
+		}
+		if self.notify {
+			notify_now_playing(&self.tracks[idx].artist, &self.tracks[idx].title);
+		}
+	}
 
-		// Start progress tracker
-		let new_gen = self.playback_generation.fetch_add(1, Ordering::SeqCst) + 1;
-		let progress = Arc::clone(&self.progress_secs);
-		let is_playing = Arc::clone(&self.is_playing);
-		let is_paused = Arc::clone(&self.is_paused);
-		let generation = Arc::clone(&self.playback_generation);
-		let duration = track_duration;
+	/// Follow the sink's playback position, at most once per `progress_interval`
+	pub fn update_progress(&mut self) {
+		if self.last_progress_update.elapsed() < self.progress_interval {
+			return;
+		}
+		self.last_progress_update = Instant::now();
+		self.progress = match (&self.scrub, &self.sink, self.current_track) {
+			(Some(scrub), _, _) => scrub.target,
+			(None, Some(sink), Some(idx)) => sink.get_pos().min(Duration::from_secs(self.tracks[idx].duration_secs)),
+			_ => Duration::ZERO,
+		};
+	}
 
-		thread::spawn(move || {
-			let mut elapsed = 0u64;
-			while is_playing.load(Ordering::SeqCst) {
-				if generation.load(Ordering::SeqCst) != new_gen { break; }
-				if elapsed >= duration { break; }
-				if !is_paused.load(Ordering::SeqCst) {
-					elapsed += 1;
-					progress.store(elapsed, Ordering::SeqCst);
-				}
-				thread::sleep(Duration::from_secs(1));
-			}
-		});
+	/// Time until the next progress update is due, so the event poll can wake up for it
+	fn until_progress_update(&self) -> Duration {
+		self.progress_interval.saturating_sub(self.last_progress_update.elapsed())
 	}
 
 	/// Toggle pause/resume
@@ -234,14 +590,12 @@ impl App {
 			PlayerState::Playing => {
 				if let Some(ref sink) = self.sink {
 					sink.pause();
-					self.is_paused.store(true, Ordering::SeqCst);
 					self.player_state = PlayerState::Paused;
 				}
 			}
 			PlayerState::Paused => {
 				if let Some(ref sink) = self.sink {
 					sink.play();
-					self.is_paused.store(false, Ordering::SeqCst);
 					self.player_state = PlayerState::Playing;
 				}
 			}
@@ -251,7 +605,12 @@ impl App {
 
 	/// Stop playback (internal, doesn't reset current_track for display)
 	fn stop_internal(&mut self) {
-		self.is_playing.store(false, Ordering::SeqCst);
+		// Cancels any track still loading
+		self.playback_generation.fetch_add(1, Ordering::SeqCst);
+		self.loading = None;
+		if let Some(scrub) = self.scrub.take() {
+			scrub.sink.stop();
+		}
 		if let Some(sink) = self.sink.take() {
 			sink.stop();
 		}
@@ -261,9 +620,10 @@ impl App {
 	/// Stop playback completely
 	pub fn stop(&mut self) {
 		self.stop_internal();
+		self.flip = None;
 		self.player_state = PlayerState::Stopped;
 		self.current_track = None;
-		self.progress_secs.store(0, Ordering::SeqCst);
+		self.progress = Duration::ZERO;
 	}
 
 	/// Skip to next track
@@ -284,6 +644,76 @@ impl App {
 		self.play_track(prev);
 	}
 
+	/// Move through the playing track by `SEEK_STEP`, forward or back
+	pub fn seek(&mut self, forward: bool) {
+		let (Some(idx), Some(sink)) = (self.current_track, self.sink.as_ref()) else { return };
+		let from = self.scrub.as_ref().map_or_else(|| sink.get_pos(), |scrub| scrub.target);
+		let target = match (forward, self.tracks[idx].duration_secs) {
+			(false, _) => from.saturating_sub(SEEK_STEP),
+			(true, 0) => from + SEEK_STEP,
+			(true, secs) => (from + SEEK_STEP).min(Duration::from_secs(secs)),
+		};
+		self.progress = target;
+		if self.scrub_preview {
+			return self.scrub_to(idx, target);
+		}
+		if let Err(e) = sink.try_seek(target) {
+			self.show_notice(&format!("Cannot seek in this track ({}).", e));
+		}
+	}
+
+	/// Hold the track and play a blip at `target` instead; `finish_scrub` moves the track there
+	fn scrub_to(&mut self, idx: usize, target: Duration) {
+		if self.scrub.is_none() {
+			let Some(ref stream) = self.stream else { return };
+			if let Some(ref sink) = self.sink {
+				sink.pause();
+			}
+			let sink = Sink::connect_new(stream.mixer());
+			sink.set_volume(self.get_volume_float());
+			self.scrub = Some(Scrub { target, last_step: Instant::now(), sink });
+		}
+		// A fresh decoder per step, so the held track keeps its place until the scrub ends
+		let track = &self.tracks[idx];
+		let blip = open_source(&self.cassette_path, track.offset, track.size, track.hint.as_deref())
+			.ok()
+			.and_then(|mut source| source.try_seek(target).ok().map(|_| source));
+		let Some(ref mut scrub) = self.scrub else { return };
+		scrub.target = target;
+		scrub.last_step = Instant::now();
+		scrub.sink.clear();
+		// Steps that can't be decoded stay silent; the track still ends up at the target
+		if let Some(source) = blip {
+			scrub.sink.append(source.take_duration(SCRUB_BLIP));
+			scrub.sink.play();
+		}
+	}
+
+	/// Once no seek step came for `SCRUB_SETTLE`, move the track to where the scrub
+	/// ended and let it carry on (unless it was paused meanwhile)
+	pub fn finish_scrub(&mut self) {
+		if self.scrub.as_ref().is_none_or(|scrub| scrub.last_step.elapsed() < SCRUB_SETTLE) {
+			return;
+		}
+		let Some(scrub) = self.scrub.take() else { return };
+		scrub.sink.stop();
+		let Some(ref sink) = self.sink else { return };
+		let sought = sink.try_seek(scrub.target);
+		if self.player_state == PlayerState::Playing {
+			sink.play();
+		}
+		if let Err(e) = sought {
+			self.show_notice(&format!("Cannot seek in this track ({}).", e));
+		}
+	}
+
+	/// Turn the scrub preview on or off
+	pub fn toggle_scrub_preview(&mut self) {
+		self.scrub_preview = !self.scrub_preview;
+		let mode = if self.scrub_preview { "on (blips while seeking with [ and ])" } else { "off" };
+		self.show_notice(&format!("Scrub preview: {}", mode));
+	}
+
 	/// Increase volume
 	pub fn volume_up(&mut self) {
 		let current = self.volume.load(Ordering::SeqCst);
@@ -302,6 +732,64 @@ impl App {
 		}
 	}
 
+	/// Open or close the equalizer panel
+	pub fn toggle_eq_panel(&mut self) {
+		self.eq_band = match self.eq_band {
+			Some(_) => None,
+			None => Some(0),
+		};
+	}
+
+	/// Move the EQ band selection left (-1) or right (+1)
+	pub fn select_eq_band(&mut self, step: isize) {
+		if let Some(band) = self.eq_band {
+			self.eq_band = Some((band as isize + step).rem_euclid(BAND_COUNT as isize) as usize);
+		}
+	}
+
+	/// Raise or lower the selected EQ band (applies to the playing track immediately)
+	pub fn adjust_eq(&mut self, delta_db: i32) {
+		if let Some(band) = self.eq_band {
+			self.eq.adjust(band, delta_db);
+		}
+	}
+
+	/// Move the balance right (+) or left (-) (applies to the playing track immediately)
+	pub fn adjust_balance(&mut self, delta: i32) {
+		self.balance.adjust(delta);
+		self.show_notice(&format!("Balance: {}", describe(self.balance.get())));
+	}
+
+	/// Turn auto-advance on or off
+	pub fn toggle_auto_advance(&mut self) {
+		self.auto_advance = !self.auto_advance;
+		let mode = if self.auto_advance { "on" } else { "off (stop after each track)" };
+		self.show_notice(&format!("Auto-advance: {}", mode));
+	}
+
+	/// Switch to the next output device (after the last one, back to the default device).
+	/// The playing track stays where it is; the new device is used from the next track on.
+	pub fn cycle_output_device(&mut self) {
+		let names = output_device_names();
+		let next = match self.device.as_ref().and_then(|current| names.iter().position(|n| n.eq_ignore_ascii_case(current))) {
+			Some(i) => names.get(i + 1).cloned(),
+			None => names.first().cloned(),
+		};
+		self.device = next;
+		let name = self.device.as_deref().unwrap_or("default device");
+		if self.sink.is_some() {
+			self.show_notice(&format!("Output: {} (from the next track)", name));
+		} else {
+			self.show_notice(&format!("Output: {}", name));
+		}
+	}
+
+	/// Set volume to an exact percentage (0-100)
+	pub fn set_volume(&mut self, level: u8) {
+		self.volume.store(level.min(100), Ordering::SeqCst);
+		self.apply_volume();
+	}
+
 	/// Get volume as float (0.0 - 1.0)
 	fn get_volume_float(&self) -> f32 {
 		self.volume.load(Ordering::SeqCst) as f32 / 100.0
@@ -315,61 +803,107 @@ impl App {
 	}
 
 	/// Check if current track finished, auto-advance
+	/// (pausing to flip the tape when the next track is on the other side)
 	pub fn check_track_finished(&mut self) {
-		if let Some(ref sink) = self.sink {
-			if sink.empty() && self.player_state == PlayerState::Playing {
-				if let Some(idx) = self.current_track {
-					if idx < self.tracks.len() - 1 {
-						self.next_track();
-					} else {
-						self.stop();
-					}
-				}
+		if let Some(ref sink) = self.sink
+			&& sink.empty() && self.player_state == PlayerState::Playing
+			&& let Some(idx) = self.current_track
+		{
+			if idx >= self.tracks.len() - 1 {
+				self.stop();
+			} else if !self.auto_advance {
+				// Queue up the next track; Play starts it
+				self.stop();
+				self.selected_track = idx + 1;
+				self.update_scroll();
+				self.show_notice("Track finished. Press Play for the next one.");
+			} else if self.tracks[idx + 1].side != self.tracks[idx].side {
+				self.stop_internal();
+				self.player_state = PlayerState::Stopped;
+				self.flip = Some(TapeFlip { next_track: idx + 1, started: Instant::now() });
+			} else {
+				self.next_track();
+			}
+		}
+
+		if self.auto_flip
+			&& let Some(ref flip) = self.flip
+			&& flip.started.elapsed() >= FLIP_PAUSE
+		{
+			self.finish_flip();
+		}
+	}
+
+	/// Turn the reels while the tape is moving (playing or being flipped); they hold still otherwise
+	pub fn advance_reels(&mut self) {
+		let moving = self.player_state == PlayerState::Playing || self.flip.is_some();
+		if moving && self.last_reel_tick.elapsed() >= REEL_FRAME_INTERVAL {
+			self.reel_frame = (self.reel_frame + 1) % REEL_FRAMES.len();
+			self.last_reel_tick = Instant::now();
+		}
+	}
+
+	/// Apply play/pause/next/... requests from media keys and desktop widgets
+	pub fn handle_media_commands(&mut self) {
+		while let Some(command) = self.media.as_ref().and_then(|m| m.try_recv()) {
+			match command {
+				MediaCommand::Play if self.player_state != PlayerState::Playing => self.toggle_pause(),
+				MediaCommand::Pause if self.player_state == PlayerState::Playing => self.toggle_pause(),
+				MediaCommand::PlayPause => self.toggle_pause(),
+				MediaCommand::Next => self.next_track(),
+				MediaCommand::Previous => self.previous_track(),
+				MediaCommand::Stop => self.stop(),
+				_ => {}
 			}
 		}
 	}
+
+	/// Keep the desktop's "now playing" in sync with the player
+	pub fn publish_media(&mut self) {
+		let status = match self.player_state {
+			PlayerState::Playing => MediaStatus::Playing,
+			PlayerState::Paused => MediaStatus::Paused,
+			PlayerState::Stopped => MediaStatus::Stopped,
+		};
+		let track = self.current_track.map(|idx| NowPlaying {
+			title: self.tracks[idx].title.clone(),
+			artist: self.tracks[idx].artist.clone(),
+			duration_secs: self.tracks[idx].duration_secs,
+		});
+		if let Some(ref mut media) = self.media {
+			media.publish(status, track);
+		}
+	}
+
+	/// Tape flipped: start the first track of the next side
+	pub fn finish_flip(&mut self) {
+		if let Some(flip) = self.flip.take() {
+			self.play_track(flip.next_track);
+		}
+	}
 }
 
 // ══════════════════════════════════════════════════════════════════════════════
 // CASSETTE LOADING
 // ══════════════════════════════════════════════════════════════════════════════
 
-/// Load track metadata from a cassette file
-fn load_tracks(path: &str) -> Result<Vec<Track>, String> {
+/// Load track metadata and the nominal tape length from a cassette file,
+/// reporting `(done, total)` tracks as it goes
+fn load_tracks(path: &Path, on_progress: &mut dyn FnMut(usize, usize)) -> Result<(Vec<Track>, Option<u16>), String> {
 	let mut file = open_file(path)?;
-	let toc_pos = find_iend(&mut file)
-		.ok_or("This cassette appears to be blank. No IEND chunk found.")?;
-
-	file.seek(SeekFrom::Start(toc_pos)).map_err(|e| e.to_string())?;
-
-	let mut count_buf = [0u8; 4];
-	file.read_exact(&mut count_buf).map_err(|e| e.to_string())?;
-	let track_count = u32::from_le_bytes(count_buf) as usize;
-
-	let mut entries: Vec<(String, u64)> = Vec::new();
-	for _ in 0..track_count {
-		let mut len_buf = [0u8; 4];
-		file.read_exact(&mut len_buf).map_err(|e| e.to_string())?;
-		let name_len = u32::from_le_bytes(len_buf) as usize;
-
-		let mut name_buf = vec![0u8; name_len];
-		file.read_exact(&mut name_buf).map_err(|e| e.to_string())?;
-		let name = String::from_utf8_lossy(&name_buf).to_string();
-
-		let mut size_buf = [0u8; 8];
-		file.read_exact(&mut size_buf).map_err(|e| e.to_string())?;
-		let size = u64::from_le_bytes(size_buf);
-
-		entries.push((name, size));
-	}
-
-	let audio_start = file.stream_position().map_err(|e| e.to_string())?;
+	let layout = CassetteLayout::read(&mut file)?;
+	let offsets = layout.track_offsets();
+	let total = layout.header.entries.len();
 	let mut tracks = Vec::new();
-	let mut offset = audio_start;
+	on_progress(0, total);
+	let cover_art = cover_thumbnail(path).and_then(|png| preview_pixels(&png, ART_PREVIEW_SIZE as u32));
 
-	for (name, size) in entries {
+	for (idx, (entry, offset)) in layout.header.entries.into_iter().zip(offsets).enumerate() {
+		let number = entry.display_number(idx);
+		let hint = format_hint(&entry);
+		let TocEntry { name, size, side, art, lyrics, .. } = entry;
 		file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
-		let mut audio_data = vec![0u8; size as usize];
+		let mut audio_data = track_buffer(size).map_err(|e| format!("'{}': {}", name, e))?;
 		file.read_exact(&mut audio_data).map_err(|e| e.to_string())?;
 
 		let (artist, title, duration_secs) = match Probe::new(Cursor::new(&audio_data)).guess_file_type() {
@@ -386,20 +920,32 @@ fn load_tracks(path: &str) -> Result<Vec<Track>, String> {
 			Err(_) => ("Unknown".into(), name.clone(), 0)
 		};
 
-		tracks.push(Track { name, size, offset, artist, title, duration_secs });
-		offset += size;
+		let art = if art.is_empty() { cover_art.clone() } else { preview_pixels(&art, ART_PREVIEW_SIZE as u32) };
+		tracks.push(Track { name, size, offset, side, number, artist, title, duration_secs, art, lyrics: parse_lrc(&lyrics), hint });
+		on_progress(tracks.len(), total);
 	}
 
-	Ok(tracks)
+	Ok((tracks, layout.header.tape_minutes))
+}
+
+/// Open a track for streaming straight from the cassette (runs on a loader thread).
+/// Gives up early if another track was started in the meantime.
+fn open_track(cassette_path: &Path, offset: u64, size: u64, hint: Option<&str>, generation: &AtomicU64, expected: u64) -> TrackLoadResult {
+	if generation.load(Ordering::SeqCst) != expected {
+		return TrackLoadResult::Cancelled;
+	}
+	match open_source(cassette_path, offset, size, hint) {
+		Ok(source) => TrackLoadResult::Ready(source),
+		Err(e) => TrackLoadResult::Failed(e),
+	}
 }
 
-/// Load raw audio data by offset and size
-fn load_track_data_raw(cassette_path: &str, offset: u64, size: u64) -> Result<Vec<u8>, String> {
-	let mut file = open_file(cassette_path)?;
-	file.seek(SeekFrom::Start(offset)).map_err(|e| e.to_string())?;
-	let mut data = vec![0u8; size as usize];
-	file.read_exact(&mut data).map_err(|e| e.to_string())?;
-	Ok(data)
+/// Open a decoder for the track at `offset`, reading it straight from the cassette
+fn open_source(cassette_path: &Path, offset: u64, size: u64, hint: Option<&str>) -> Result<TrackSource, PlaybackError> {
+	let track = open_file(cassette_path)
+		.and_then(|file| Region::new(file, offset, size).map_err(|e| e.to_string()))
+		.map_err(PlaybackError::FileRead)?;
+	decode_with_hint(BufReader::new(track), size, hint)
 }
 
 // ══════════════════════════════════════════════════════════════════════════════
@@ -407,7 +953,7 @@ fn load_track_data_raw(cassette_path: &str, offset: u64, size: u64) -> Result<Ve
 // ══════════════════════════════════════════════════════════════════════════════
 
 /// Main entry point for the TUI
-pub fn run_tui(cassette_path: &str) -> Result<(), String> {
+pub fn run_tui(cassette_path: &str, options: &TuiOptions) -> Result<(), String> {
 	// Suppress stderr (rodio messages)
 	#[cfg(windows)]
 	let _stderr_redirect = OpenOptions::new().write(true).open("NUL")
@@ -416,8 +962,6 @@ pub fn run_tui(cassette_path: &str) -> Result<(), String> {
 	let _stderr_redirect = OpenOptions::new().write(true).open("/dev/null")
 		.ok().and_then(|f| gag::Redirect::stderr(f).ok());
 
-	let mut app = App::new(cassette_path)?;
-
 	enable_raw_mode().map_err(|e| e.to_string())?;
 	let mut stdout = io::stdout();
 	stdout.execute(EnterAlternateScreen).map_err(|e| e.to_string())?;
@@ -426,7 +970,16 @@ pub fn run_tui(cassette_path: &str) -> Result<(), String> {
 	let backend = CrosstermBackend::new(stdout);
 	let mut terminal = Terminal::new(backend).map_err(|e| e.to_string())?;
 
-	let result = run_app(&mut terminal, &mut app);
+	// Big cassettes take a while to probe, so show how far along we are first
+	let mut on_progress = |path: &str, done: usize, total: usize| {
+		let _ = terminal.draw(|f| draw_loading(f, path, done, total));
+	};
+	let app = if Path::new(cassette_path).is_dir() {
+		open_library(Path::new(cassette_path), options, &mut on_progress)
+	} else {
+		App::new(Path::new(cassette_path), options, &mut |done, total| on_progress(cassette_path, done, total))
+	};
+	let result = app.and_then(|mut app| run_app(&mut terminal, &mut app));
 
 	disable_raw_mode().map_err(|e| e.to_string())?;
 	io::stdout().execute(LeaveAlternateScreen).map_err(|e| e.to_string())?;
@@ -435,6 +988,22 @@ pub fn run_tui(cassette_path: &str) -> Result<(), String> {
 	result
 }
 
+/// Starts the TUI in library mode on the first cassette in `dir` that loads
+fn open_library(dir: &Path, options: &TuiOptions, on_progress: &mut dyn FnMut(&str, usize, usize)) -> Result<App, String> {
+	let mut library = Library::scan(dir)?;
+	for (idx, path) in library.entries.iter().enumerate() {
+		let name = path.to_string_lossy();
+		if let Ok(mut app) = App::new(path, options, &mut |done, total| on_progress(&name, done, total)) {
+			library.selected = idx;
+			library.move_selection(0, library_rows_visible(&app.tracks));
+			app.library = Some(library);
+			app.focus = Pane::Library;
+			return Ok(app);
+		}
+	}
+	Err(format!("None of the PNG files in '{}' are playable cassettes.", dir.display()))
+}
+
 /// Check if a click is within a button area
 fn is_click_in_button(x: u16, y: u16, btn: (u16, u16, u16), ui_x: u16, ui_y: u16) -> bool {
 	let bx = ui_x + btn.0;
@@ -442,19 +1011,18 @@ fn is_click_in_button(x: u16, y: u16, btn: (u16, u16, u16), ui_x: u16, ui_y: u16
 	x >= bx && x < bx + btn.2 && y == by
 }
 
-/// Check if a click is on a playlist item, returns track index if so
-fn get_playlist_click(x: u16, y: u16, ui_x: u16, ui_y: u16, scroll: usize, track_count: usize) -> Option<usize> {
+/// Check if a click is on a playlist item, returns track index if so (side headers aren't clickable)
+fn get_playlist_click(x: u16, y: u16, ui_x: u16, ui_y: u16, scroll: usize, rows: &[PlaylistRow]) -> Option<usize> {
 	let playlist_x_start = ui_x + 3;
 	let playlist_x_end = ui_x + 60;
-	let visible = track_count.min(MAX_PLAYLIST_VISIBLE);
+	let visible = rows.len().min(MAX_PLAYLIST_VISIBLE);
 
 	for i in 0..visible {
 		let item_y = ui_y + PLAYLIST_START_Y + i as u16;
-		if y == item_y && x >= playlist_x_start && x < playlist_x_end {
-			let track_idx = scroll + i;
-			if track_idx < track_count {
-				return Some(track_idx);
-			}
+		if y == item_y && x >= playlist_x_start && x < playlist_x_end
+			&& let Some(PlaylistRow::Track(track_idx)) = rows.get(scroll + i)
+		{
+			return Some(*track_idx);
 		}
 	}
 	None
@@ -462,15 +1030,32 @@ fn get_playlist_click(x: u16, y: u16, ui_x: u16, ui_y: u16, scroll: usize, track
 
 /// Main application loop
 fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App) -> Result<(), String> {
-	// Calculate UI position (centered or top-left)
-	let ui_x: u16 = 0;
+	// Calculate UI position (top-left, right of the library pane when browsing)
+	let ui_x: u16 = app.player_x();
 	let ui_y: u16 = 0;
 
+	// Only redraw when something visible changed (input, reels, progress or player state)
+	let mut needs_redraw = true;
+	let mut last_drawn = None;
+
 	loop {
+		app.poll_track_load();
 		app.check_track_finished();
-		terminal.draw(|f| draw_ui(f, app)).map_err(|e| e.to_string())?;
+		app.handle_media_commands();
+		app.publish_media();
+		app.advance_reels();
+		app.finish_scrub();
+		app.update_progress();
+
+		let snapshot = (app.reel_frame, app.progress, app.player_state, app.current_track, app.flip.is_some(), app.loading.is_some());
+		if needs_redraw || last_drawn != Some(snapshot) {
+			terminal.draw(|f| draw_ui(f, app)).map_err(|e| e.to_string())?;
+			last_drawn = Some(snapshot);
+			needs_redraw = false;
+		}
 
-		if event::poll(Duration::from_millis(100)).map_err(|e| e.to_string())? {
+		if event::poll(EVENT_POLL_INTERVAL.min(app.until_progress_update())).map_err(|e| e.to_string())? {
+			needs_redraw = true;
 			match event::read().map_err(|e| e.to_string())? {
 				Event::Key(key) if key.kind == KeyEventKind::Press => {
 					match key.code {
@@ -478,6 +1063,25 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
 							app.stop();
 							app.should_quit = true;
 						}
+						// Any other key flips the tape
+						_ if app.flip.is_some() => app.finish_flip(),
+						KeyCode::Tab => app.toggle_focus(),
+						KeyCode::Up | KeyCode::Char('k') if app.focus == Pane::Library => app.move_library_selection(-1),
+						KeyCode::Down | KeyCode::Char('j') if app.focus == Pane::Library => app.move_library_selection(1),
+						KeyCode::Enter if app.focus == Pane::Library => app.open_library_selection(),
+						KeyCode::Char('e') => app.toggle_eq_panel(),
+						KeyCode::Char(',') => app.adjust_balance(-BALANCE_STEP),
+						KeyCode::Char('.') => app.adjust_balance(BALANCE_STEP),
+						KeyCode::Char('o') => app.open_with_picker(),
+						KeyCode::Char('d') => app.cycle_output_device(),
+						KeyCode::Char('a') => app.toggle_auto_advance(),
+						KeyCode::Char('[') => app.seek(false),
+						KeyCode::Char(']') => app.seek(true),
+						KeyCode::Char('z') => app.toggle_scrub_preview(),
+						KeyCode::Left if app.eq_band.is_some() => app.select_eq_band(-1),
+						KeyCode::Right if app.eq_band.is_some() => app.select_eq_band(1),
+						KeyCode::Up if app.eq_band.is_some() => app.adjust_eq(EQ_STEP_DB),
+						KeyCode::Down if app.eq_band.is_some() => app.adjust_eq(-EQ_STEP_DB),
 						KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
 						KeyCode::Down | KeyCode::Char('j') => app.select_next(),
 						KeyCode::Enter => app.play_selected(),
@@ -487,12 +1091,15 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
 						KeyCode::Left | KeyCode::Char('p') => app.previous_track(),
 						KeyCode::Char('+') | KeyCode::Char('=') => app.volume_up(),
 						KeyCode::Char('-') => app.volume_down(),
+						KeyCode::Char('r') if app.cassette_error.is_some() => app.reload(),
 						_ => {}
 					}
 				}
 				Event::Mouse(MouseEvent { kind: MouseEventKind::Down(MouseButton::Left), column, row, .. }) => {
 					// Check button clicks
-					if is_click_in_button(column, row, BTN_PREV, ui_x, ui_y) {
+					if column < ui_x {
+						app.click_library_row(row);
+					} else if is_click_in_button(column, row, BTN_PREV, ui_x, ui_y) {
 						app.previous_track();
 					} else if is_click_in_button(column, row, BTN_PLAY, ui_x, ui_y) {
 						if app.player_state == PlayerState::Stopped {
@@ -512,7 +1119,7 @@ fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, app: &mut App)
 						app.volume_down();
 					} else if is_click_in_button(column, row, BTN_VOL_UP, ui_x, ui_y) {
 						app.volume_up();
-					} else if let Some(track_idx) = get_playlist_click(column, row, ui_x, ui_y, app.playlist_scroll, app.tracks.len()) {
+					} else if let Some(track_idx) = get_playlist_click(column, row, ui_x, ui_y, app.playlist_scroll, &playlist_rows(&app.tracks)) {
 						app.select_track(track_idx);
 						app.play_track(track_idx);
 					}
@@ -538,15 +1145,17 @@ fn draw_ui(f: &mut Frame, app: &App) {
 	let volume = app.volume.load(Ordering::SeqCst);
 	let (elapsed, duration, progress_ratio) = if let Some(idx) = app.current_track {
 		let track = &app.tracks[idx];
-		let e = app.progress_secs.load(Ordering::SeqCst);
 		let d = track.duration_secs.max(1);
-		(e, d, (e as f64 / d as f64).min(1.0))
+		(app.progress.as_secs(), d, (app.progress.as_secs_f64() / d as f64).min(1.0))
 	} else {
 		(0, 0, 0.0)
 	};
 
 	// Get current track info
-	let (artist_title, track_num_str) = if let Some(idx) = app.current_track {
+	let (artist_title, track_num_str) = if let Some(ref flip) = app.flip {
+		let hint = if app.auto_flip { "[flipping]" } else { "[any key]" };
+		("⟳ Flip the tape...".to_string(), format!("{} {}", app.tracks[flip.next_track].side, hint))
+	} else if let Some(idx) = app.current_track {
 		let track = &app.tracks[idx];
 		let display = format!("{} - {}", track.artist, track.title);
 		let num = format!("[{}/{}]", idx + 1, app.tracks.len());
@@ -555,13 +1164,8 @@ fn draw_ui(f: &mut Frame, app: &App) {
 		("No track loaded".to_string(), format!("[-/{}]", app.tracks.len()))
 	};
 
-	// Truncate artist_title to fit (max 24 chars)
-	let max_title_len = 24;
-	let artist_title_display: String = if artist_title.chars().count() > max_title_len {
-		artist_title.chars().take(max_title_len - 1).collect::<String>() + "…"
-	} else {
-		format!("{:<24}", artist_title)
-	};
+	// Truncate artist_title to fit (24 columns)
+	let artist_title_display = fit(&artist_title, 24);
 
 	// Build progress bar (24 chars wide)
 	let progress_width = 24;
@@ -569,12 +1173,19 @@ fn draw_ui(f: &mut Frame, app: &App) {
 	let empty = progress_width - filled;
 	let progress_bar = format!("{}{}", "═".repeat(filled), "╌".repeat(empty));
 
-	// Time display
-	let time_str = format!("{} / {}", format_duration(elapsed), format_duration(duration));
-	let time_display = format!("{:<13} {:>7}", time_str, track_num_str);
+	// Time display (the next side while flipping)
+	let time_display = if app.flip.is_some() {
+		track_num_str
+	} else {
+		let time_str = format!("{} / {}", format_duration(elapsed), format_duration(duration));
+		format!("{:<13} {:>7}", time_str, track_num_str)
+	};
+
+	let (hub_top, hub_bottom) = REEL_FRAMES[app.reel_frame];
 
 	// Dynamic playlist size
-	let playlist_visible = app.tracks.len().min(MAX_PLAYLIST_VISIBLE);
+	let rows = playlist_rows(&app.tracks);
+	let playlist_visible = rows.len().min(MAX_PLAYLIST_VISIBLE);
 
 	// Map volume percentage (0-100) to visual state (0-5)
 	// 0: Exclusive to 0% (Flat bar / Off)
@@ -614,8 +1225,12 @@ fn draw_ui(f: &mut Frame, app: &App) {
 
 	// Line 0: Top cassette border
 	lines.push(Line::from("       ╭──────────────────────────────────────────────╮"));
-	// Line 1: Cassette shell top
-	lines.push(Line::from("╭──────┤                                              ├──────╮"));
+	// Line 1: Cassette shell top, labelled with the open cassette
+	lines.push(Line::from(vec![
+		Span::raw("╭──────┤"),
+		Span::styled(title_label(&app.cassette_path, app.tracks.len()), Style::default().add_modifier(Modifier::BOLD)),
+		Span::raw("├──────╮"),
+	]));
 	// Line 2: Brand + left reel + title + right reel + volume top
 	lines.push(Line::from(vec![
 		Span::raw("│ "),
@@ -626,17 +1241,17 @@ fn draw_ui(f: &mut Frame, app: &App) {
 	]));
 	// Line 3: Reels inner + progress bar + volume slot 4
 	lines.push(Line::from(vec![
-		Span::raw("│      │   │ ╭─╮ │ "),
+		Span::raw(format!("│      │   │ {} │ ", hub_top)),
 		Span::styled(progress_bar.clone(), Style::default().fg(Color::Green)),
-		Span::raw(" │ ╭─╮ │   │ "),
+		Span::raw(format!(" │ {} │   │ ", hub_top)),
 		Span::raw(vol_slot(4)),
 		Span::raw(" │"),
 	]));
 	// Line 4: Reels + time display + volume slot 3
 	lines.push(Line::from(vec![
-		Span::raw("│      │   │ ╰─╯ │ "),
-		Span::styled(format!("{:<24}", time_display), Style::default().fg(Color::White)),
-		Span::raw(" │ ╰─╯ │   │ "),
+		Span::raw(format!("│      │   │ {} │ ", hub_bottom)),
+		Span::styled(fit(&time_display, 24), Style::default().fg(Color::White)),
+		Span::raw(format!(" │ {} │   │ ", hub_bottom)),
 		Span::raw(vol_slot(3)),
 		Span::raw(" │"),
 	]));
@@ -646,9 +1261,16 @@ fn draw_ui(f: &mut Frame, app: &App) {
 		Span::raw(vol_slot(2)),
 		Span::raw(" │"),
 	]));
-	// Line 6: Cassette body + volume slot 1
+	// Line 6: Cassette body (tape type and usage) + volume slot 1
+	let (tape_text, tape_style) = match tape_usage(app) {
+		Some((text, true)) => (text, Style::default().fg(Color::Yellow)),
+		Some((text, false)) => (text, Style::default().add_modifier(Modifier::DIM)),
+		None => (String::new(), Style::default()),
+	};
 	lines.push(Line::from(vec![
-		Span::raw("│      │                                              │ "),
+		Span::raw("│      │"),
+		Span::styled(fit_center(&tape_text, 46), tape_style),
+		Span::raw("│ "),
 		Span::raw(vol_slot(1)),
 		Span::raw(" │"),
 	]));
@@ -662,8 +1284,13 @@ fn draw_ui(f: &mut Frame, app: &App) {
 	lines.push(Line::from("│      ├───┴──────────────────────────────────────┴───┤ ╰──╯ │"));
 	// Line 9: Cassette bottom + volume percentage
 	lines.push(Line::from(format!("│      ╰──────────────────────────────────────────────╯ {:>3}% │", volume)));
-	// Line 10: Empty line before buttons
-	lines.push(Line::from("│                                                            │"));
+	// Line 10: Auto-advance mode, above the buttons
+	let advance = if app.auto_advance { "Auto-advance: on " } else { "Auto-advance: off" };
+	lines.push(Line::from(vec![
+		Span::raw("│ "),
+		Span::styled(format!("{:>58}", advance), Style::default().add_modifier(Modifier::DIM)),
+		Span::raw(" │"),
+	]));
 	// Line 11: Button tops
 	lines.push(Line::from("│      ┌───┐ ┌───┐ ┌───┐ ┌───┐ ┌───┐    │   ┌───┐ ┌───┐      │"));
 	// Line 12: Button icons
@@ -697,80 +1324,273 @@ fn draw_ui(f: &mut Frame, app: &App) {
 	lines.push(Line::from("│    ┌─ PLAYLIST ───────────────────────────────────────┐    │"));
 
 	// Playlist items (dynamic based on track count)
-	for i in 0..playlist_visible {
-		let track_idx = app.playlist_scroll + i;
-		if track_idx < app.tracks.len() {
-			let track = &app.tracks[track_idx];
-			let is_current = app.current_track == Some(track_idx);
-			let is_selected = app.selected_track == track_idx;
-
-			let prefix = if is_current {
-				match app.player_state {
-					PlayerState::Playing => "▶",
-					PlayerState::Paused => "⏸",
-					PlayerState::Stopped => " ",
-				}
-			} else if is_selected {
-				">"
-			} else {
-				" "
-			};
+	for row in rows.iter().skip(app.playlist_scroll).take(playlist_visible) {
+		let track_idx = match *row {
+			PlaylistRow::Track(idx) => idx,
+			PlaylistRow::Side(side) => {
+				// Side header, e.g. "── SIDE B ───…" across the box
+				let label = format!("── {} ", side.to_string().to_uppercase());
+				let fill = 48 - label.chars().count();
+				lines.push(Line::from(vec![
+					Span::raw("│    │ "),
+					Span::styled(format!("{}{}", label, "─".repeat(fill)), Style::default().fg(Color::Magenta)),
+					Span::raw(" │    │"),
+				]));
+				continue;
+			}
+		};
+		let track = &app.tracks[track_idx];
+		let is_current = app.current_track == Some(track_idx);
+		let is_selected = app.selected_track == track_idx;
+
+		let prefix = if is_current {
+			match app.player_state {
+				PlayerState::Playing => "▶",
+				PlayerState::Paused => "⏸",
+				PlayerState::Stopped => " ",
+			}
+		} else if is_selected {
+			">"
+		} else {
+			" "
+		};
 
-			// Format track: keep duration visible, truncate name more aggressively
-			let duration_str = format!("[{}]", format_duration(track.duration_secs));
-			let num_prefix = format!("{:2}. ", track_idx + 1);
-			let name_part = format!("{} - {}", track.artist, track.title);
-
-			// Content width: 46 chars to fit properly (shifted 4 left)
-			// = num_prefix(4) + name + space(1) + duration(~6)
-			let content_width = 46;
-			let available = content_width - num_prefix.len() - duration_str.len() - 1;
-			let name_display: String = if name_part.chars().count() > available {
-				name_part.chars().take(available - 1).collect::<String>() + "…"
-			} else {
-				format!("{:<width$}", name_part, width = available)
-			};
+		// Format track: keep duration visible, truncate name more aggressively
+		let duration_str = format!("[{}]", format_duration(track.duration_secs));
+		let num_prefix = format!("{:2}. ", track.number);
+		let name_part = format!("{} - {}", track.artist, track.title);
 
-			let content = format!("{}{} {}", num_prefix, name_display, duration_str);
+		// Content width: 46 chars to fit properly (shifted 4 left)
+		// = num_prefix(4) + name + space(1) + duration(~6)
+		let content_width = 46;
+		let available = content_width - num_prefix.len() - duration_str.len() - 1;
+		let name_display = fit(&name_part, available);
 
-			let style = if is_current {
-				Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
-			} else if is_selected {
-				Style::default().fg(Color::Yellow)
-			} else {
-				Style::default()
-			};
+		let content = format!("{}{} {}", num_prefix, name_display, duration_str);
+
+		let style = if is_current {
+			Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+		} else if is_selected {
+			Style::default().fg(Color::Yellow)
+		} else {
+			Style::default()
+		};
+
+		lines.push(Line::from(vec![
+			Span::raw("│    │ "),
+			Span::styled(prefix, style),
+			Span::raw(" "),
+			Span::styled(content, style),
+			Span::raw(" │    │"),
+		]));
+	}
 
+	// Playlist bottom - centered to match header
+	lines.push(Line::from("│    ╘══════════════════════════════════════════════════╛    │"));
+	// Synced lyrics of the playing track: the line being sung between the ones around it
+	if let Some(track) = app.current_track.map(|idx| &app.tracks[idx]).filter(|track| !track.lyrics.is_empty()) {
+		let current = current_line(&track.lyrics, app.progress).map_or(-1, |idx| idx as isize);
+		lines.push(Line::from("│    ┌─ LYRICS ─────────────────────────────────────────┐    │"));
+		for delta in -1..=1 {
+			let text = usize::try_from(current + delta).ok().and_then(|idx| track.lyrics.get(idx)).map_or("", |line| line.text.as_str());
+			let style = if delta == 0 { Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD) } else { Style::default().add_modifier(Modifier::DIM) };
 			lines.push(Line::from(vec![
 				Span::raw("│    │ "),
-				Span::styled(prefix, style),
-				Span::raw(" "),
-				Span::styled(content, style),
+				Span::styled(fit_center(text, 48), style),
 				Span::raw(" │    │"),
 			]));
 		}
+		lines.push(Line::from("│    ╘══════════════════════════════════════════════════╛    │"));
 	}
-
-	// Playlist bottom - centered to match header
-	lines.push(Line::from("│    ╘══════════════════════════════════════════════════╛    │"));
 	// Separator
 	lines.push(Line::from("├────────────────────────────────────────────────────────────┤"));
-	// Controls hint
-	lines.push(Line::from(vec![
-		Span::raw("│ "),
-		Span::styled("[⇅]", Style::default().fg(Color::Yellow)),
-		Span::raw(" Navigate  "),
-		Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
-		Span::raw(" Select  "),
-		Span::styled("[Space]", Style::default().fg(Color::Yellow)),
-		Span::raw(" Play/Pause  "),
-		Span::styled("[Q]", Style::default().fg(Color::Yellow)),
-		Span::raw(" Quit │"),
-	]));
+	// Controls hint (replaced by the error when the cassette can't be read)
+	if app.cassette_error.is_some() {
+		lines.push(Line::from(vec![
+			Span::raw("│ "),
+			Span::styled("⚠ Cassette no longer accessible.", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+			Span::raw("       "),
+			Span::styled("[R]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Retry  "),
+			Span::styled("[Q]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Quit │"),
+		]));
+	} else if let Some(ref load) = app.loading {
+		let text = format!("Loading {}…", app.tracks[load.track].title);
+		lines.push(Line::from(vec![
+			Span::raw("│ "),
+			Span::styled(fit(&text, 58), Style::default().fg(Color::Cyan)),
+			Span::raw(" │"),
+		]));
+	} else if let Some((ref message, shown)) = app.notice
+		&& shown.elapsed() < NOTICE_DURATION
+	{
+		lines.push(Line::from(vec![
+			Span::raw("│ "),
+			Span::styled(fit(message, 58), Style::default().fg(Color::Yellow)),
+			Span::raw(" │"),
+		]));
+	} else if let Some(selected_band) = app.eq_band {
+		// EQ panel: "EQ  Low  +0 dB  Mid  +4 dB  High  -2 dB      [⇄⇅] [E] Close"
+		let mut spans = vec![Span::raw("│ "), Span::styled("EQ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))];
+		let mut used = 3;
+		for (band, gain) in app.eq.gains().into_iter().enumerate() {
+			let text = format!(" {} {:+3} dB ", BAND_NAMES[band], gain);
+			used += text.chars().count();
+			let style = if band == selected_band {
+				Style::default().fg(Color::Black).bg(Color::Yellow)
+			} else {
+				Style::default()
+			};
+			spans.push(Span::styled(text, style));
+		}
+		let keys = "[⇄⇅] [E] Close";
+		spans.push(Span::raw(" ".repeat(60usize.saturating_sub(used + keys.chars().count() + 1))));
+		spans.push(Span::styled(keys, Style::default().fg(Color::Yellow)));
+		spans.push(Span::raw(" │"));
+		lines.push(Line::from(spans));
+	} else {
+		lines.push(Line::from(vec![
+			Span::raw("│ "),
+			Span::styled("[⇅]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Navigate  "),
+			Span::styled("[Enter]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Select  "),
+			Span::styled("[Space]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Play/Pause  "),
+			Span::styled("[Q]", Style::default().fg(Color::Yellow)),
+			Span::raw(" Quit │"),
+		]));
+	}
 	// Bottom border
 	lines.push(Line::from("╰────────────────────────────────────────────────────────────╯"));
 
 	let total_height = lines.len() as u16;
 	let paragraph = Paragraph::new(lines);
-	f.render_widget(paragraph, Rect::new(0, 0, UI_WIDTH, total_height));
+	f.render_widget(paragraph, Rect::new(app.player_x(), 0, UI_WIDTH, total_height));
+
+	if let Some(ref library) = app.library {
+		draw_library(f, app, library, total_height);
+	}
+
+	// Preview the selected track's art when the terminal has room for it
+	let art_x = app.player_x() + UI_WIDTH + 1;
+	if f.area().width >= art_x + ART_PANE_WIDTH
+		&& let Some(art) = app.tracks.get(app.selected_track).and_then(|t| t.art.as_ref())
+	{
+		draw_art(f, art, art_x);
+	}
+}
+
+/// Draw the "Reading cassette…" screen shown while track metadata is probed
+fn draw_loading(f: &mut Frame, path: &str, done: usize, total: usize) {
+	let name = Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.to_string());
+	let status = format!("Reading cassette… {}/{} tracks", done, total);
+
+	let lines = vec![
+		Line::from(format!("╭{}╮", "─".repeat(60))),
+		Line::from(vec![
+			Span::raw("│ "),
+			Span::styled(fit(&name, 58), Style::default().fg(Color::Cyan)),
+			Span::raw(" │"),
+		]),
+		Line::from(vec![
+			Span::raw("│ "),
+			Span::styled(format!("{:<58}", status), Style::default().fg(Color::Yellow)),
+			Span::raw(" │"),
+		]),
+		Line::from(format!("╰{}╯", "─".repeat(60))),
+	];
+	f.render_widget(Paragraph::new(lines), Rect::new(0, 0, UI_WIDTH, 4));
+}
+
+/// Tape type and time used, e.g. "C90 · 47:12 / 90:00", and whether the audio is longer than the tape.
+fn tape_usage(app: &App) -> Option<(String, bool)> {
+	let minutes = app.tape_minutes?;
+	let capacity = u64::from(minutes) * 60;
+	let used: u64 = app.tracks.iter().map(|track| track.duration_secs).sum();
+	Some((format!("C{} · {} / {}", minutes, format_duration(used), format_duration(capacity)), used > capacity))
+}
+
+/// Cassette file name and track count, centered in the 46 columns of the shell's top edge.
+/// Long names are cut short so the count stays visible.
+pub fn title_label(path: &Path, track_count: usize) -> String {
+	const WIDTH: usize = 46;
+	let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+	let count = if track_count == 1 { "1 track".to_string() } else { format!("{} tracks", track_count) };
+	// One space of padding on each side, plus " · " between the parts
+	let name = truncate(&name, WIDTH - count.chars().count() - 5);
+	fit_center(&format!(" {} · {} ", name, count), WIDTH)
+}
+
+/// Draw track art as half-block characters (top pixel in the foreground, bottom in the background)
+fn draw_art(f: &mut Frame, pixels: &[[u8; 3]], x: u16) {
+	let size = ART_PREVIEW_SIZE as usize;
+	let rgb = |[r, g, b]: [u8; 3]| Color::Rgb(r, g, b);
+
+	let mut lines: Vec<Line> = Vec::new();
+	lines.push(Line::from(format!("╭─ ART {}╮", "─".repeat(size - 6))));
+	for row in (0..size).step_by(2) {
+		let mut spans = vec![Span::raw("│")];
+		for col in 0..size {
+			let top = pixels[row * size + col];
+			let bottom = pixels[(row + 1) * size + col];
+			spans.push(Span::styled("▀", Style::default().fg(rgb(top)).bg(rgb(bottom))));
+		}
+		spans.push(Span::raw("│"));
+		lines.push(Line::from(spans));
+	}
+	lines.push(Line::from(format!("╰{}╯", "─".repeat(size))));
+
+	let height = lines.len() as u16;
+	f.render_widget(Paragraph::new(lines), Rect::new(x, 0, ART_PANE_WIDTH, height));
+}
+
+/// Rows available for cassettes in the library pane, whose box is as tall as the player
+fn library_rows_visible(tracks: &[Track]) -> usize {
+	// The player is 21 fixed lines plus the visible playlist rows; minus the pane's two borders
+	21 + playlist_rows(tracks).len().min(MAX_PLAYLIST_VISIBLE) - 2
+}
+
+/// Draw the cassette browser to the left of the player
+fn draw_library(f: &mut Frame, app: &App, library: &Library, height: u16) {
+	let inner = LIBRARY_WIDTH as usize - 2;
+	let focused = app.focus == Pane::Library;
+	let border = if focused { Style::default().fg(Color::Yellow) } else { Style::default() };
+	let visible = library_rows_visible(&app.tracks);
+
+	let mut lines: Vec<Line> = Vec::new();
+	let title = "─ CASSETTES ";
+	lines.push(Line::from(Span::styled(format!("╭{}{}╮", title, "─".repeat(inner - title.chars().count())), border)));
+
+	for row in 0..visible {
+		let idx = library.scroll + row;
+		let Some(path) = library.entries.get(idx) else {
+			lines.push(Line::from(vec![Span::styled("│", border), Span::raw(" ".repeat(inner)), Span::styled("│", border)]));
+			continue;
+		};
+
+		let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+		let name = fit(&name, inner - 4);
+		let is_loaded = *path == app.cassette_path;
+		let marker = if is_loaded { "●" } else { " " };
+		let style = if idx == library.selected && focused {
+			Style::default().fg(Color::Yellow)
+		} else if is_loaded {
+			Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+		} else {
+			Style::default()
+		};
+
+		lines.push(Line::from(vec![
+			Span::styled("│", border),
+			Span::styled(format!(" {} {} ", marker, name), style),
+			Span::styled("│", border),
+		]));
+	}
+
+	let hint = "─ [Tab] ";
+	lines.push(Line::from(Span::styled(format!("╰{}{}╯", hint, "─".repeat(inner - hint.chars().count())), border)));
+
+	f.render_widget(Paragraph::new(lines), Rect::new(0, 0, LIBRARY_WIDTH, height));
 }
@@ -0,0 +1,138 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// WAVEFORM MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// `rewind waveform` decodes one track and draws its waveform as a PNG, e.g. as
+// a preview image for a web player. Samples are folded into small blocks while
+// decoding (so long tracks aren't held in memory), the blocks are grouped into
+// one column per pixel, and each column shows the lowest and highest sample in
+// it, mirrored around the center line.
+
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use image::{ImageFormat, Rgb, RgbImage};
+use rodio::Decoder;
+use crate::cover::Theme;
+use crate::format::{CassetteLayout, BLANK_CASSETTE};
+use crate::io::{create_file, human_size, open_file, track_buffer};
+use crate::logger::{log, LogLevel};
+
+/// Samples (across all channels) folded into one min/max pair while decoding.
+const BLOCK_SAMPLES: usize = 1024;
+
+/// Options for `waveform`.
+pub struct WaveformOptions {
+	/// 1-based track to draw.
+	pub track: usize,
+	pub width: u32,
+	pub height: u32,
+	pub theme: Theme,
+}
+
+/// Decodes one track of the cassette at `path` and writes its waveform to `output_path`.
+pub fn waveform(path: &str, output_path: &str, options: &WaveformOptions) {
+	let mut file = match open_file(path) {
+		Ok(f) => f,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+	let layout = match CassetteLayout::read(&mut file) {
+		Ok(layout) => layout,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+	let entries = &layout.header.entries;
+	if entries.is_empty() {
+		log(LogLevel::Warning, BLANK_CASSETTE);
+		return;
+	}
+	let Some(entry) = options.track.checked_sub(1).and_then(|idx| entries.get(idx)) else {
+		log(LogLevel::Error, &format!("No track {}. This cassette has {} track(s).", options.track, entries.len()));
+		return;
+	};
+
+	let mut audio = match track_buffer(entry.size) {
+		Ok(buffer) => buffer,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot read track {} ({}): {}", options.track, entry.name, e)); return; }
+	};
+	let offset = layout.track_offsets()[options.track - 1];
+	if let Err(e) = file.seek(SeekFrom::Start(offset)).and_then(|_| file.read_exact(&mut audio)) {
+		log(LogLevel::Error, &format!("Cannot read track {} ({}): {}", options.track, entry.name, e));
+		return;
+	}
+
+	log(LogLevel::Info, &format!("Drawing the waveform of track {} ({})...", options.track, entry.name));
+	let decoder = match Decoder::new(Cursor::new(audio)) {
+		Ok(decoder) => decoder,
+		Err(e) => { log(LogLevel::Error, &format!("Cannot decode track {}: {}", options.track, e)); return; }
+	};
+	let blocks = block_envelope(decoder);
+	if blocks.is_empty() {
+		log(LogLevel::Error, &format!("Track {} decoded to no audio.", options.track));
+		return;
+	}
+
+	let columns = column_envelope(&blocks, options.width);
+	let (background, wave) = options.theme.waveform_colors();
+	let png = match encode_png(&render(&columns, options.height, background, wave)) {
+		Ok(png) => png,
+		Err(e) => { log(LogLevel::Error, &e); return; }
+	};
+
+	let written = create_file(output_path).and_then(|mut f| f.write_all(&png).map_err(|e| format!("Failed to write waveform: {}", e)));
+	match written {
+		Ok(()) => log(LogLevel::Success, &format!("Waveform saved to {} ({}×{}, {}).", output_path, options.width, options.height, human_size(png.len() as u64))),
+		Err(e) => log(LogLevel::Error, &e),
+	}
+}
+
+/// Min/max of every `BLOCK_SAMPLES` samples, in order.
+fn block_envelope(samples: impl Iterator<Item = f32>) -> Vec<(f32, f32)> {
+	let mut blocks = Vec::new();
+	let (mut low, mut high, mut count) = (0.0f32, 0.0f32, 0);
+	for sample in samples {
+		low = low.min(sample);
+		high = high.max(sample);
+		count += 1;
+		if count == BLOCK_SAMPLES {
+			blocks.push((low, high));
+			(low, high, count) = (0.0, 0.0, 0);
+		}
+	}
+	if count > 0 {
+		blocks.push((low, high));
+	}
+	blocks
+}
+
+/// Groups the blocks into `width` columns. Short tracks repeat blocks across columns.
+fn column_envelope(blocks: &[(f32, f32)], width: u32) -> Vec<(f32, f32)> {
+	let (count, width) = (blocks.len(), width as usize);
+	(0..width)
+		.map(|x| {
+			let start = x * count / width;
+			let end = ((x + 1) * count / width).max(start + 1);
+			blocks[start..end].iter().fold((0.0f32, 0.0f32), |(low, high), &(l, h)| (low.min(l), high.max(h)))
+		})
+		.collect()
+}
+
+/// Draws each column as a vertical bar from its min to its max.
+fn render(columns: &[(f32, f32)], height: u32, background: Rgb<u8>, wave: Rgb<u8>) -> RgbImage {
+	let mut img = RgbImage::from_pixel(columns.len() as u32, height, background);
+	let half = (height - 1) as f32 / 2.0;
+	let to_row = |sample: f32| ((1.0 - sample.clamp(-1.0, 1.0)) * half).round() as u32;
+
+	for (x, &(low, high)) in columns.iter().enumerate() {
+		// Silence still gets a one-pixel center line
+		for y in to_row(high)..=to_row(low) {
+			img.put_pixel(x as u32, y, wave);
+		}
+	}
+	img
+}
+
+/// Encodes an image to PNG bytes.
+fn encode_png(img: &RgbImage) -> Result<Vec<u8>, String> {
+	let mut bytes = Cursor::new(Vec::new());
+	img.write_to(&mut bytes, ImageFormat::Png)
+		.map_err(|e| format!("Failed to encode the waveform: {}", e))?;
+	Ok(bytes.into_inner())
+}
@@ -0,0 +1,131 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// WIZARD MODULE
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Interactive `rewind record` for people who'd rather not learn the argument
+// order. Running `record` with no arguments asks for a cover (file dialog or a
+// generated one), the audio files, their order and the output name, then hands
+// the answers to the regular `record` function. The cassette picker used by
+// the players lives here too, so all native file dialogs stay in one module.
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use clap::ValueEnum;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select, Sort};
+use rfd::FileDialog;
+use crate::cover::{CoverSource, Theme};
+use crate::png::COVER_EXTENSIONS;
+use crate::logger::{log, LogLevel};
+
+/// Opens a native file picker to select a PNG cassette file.
+/// Returns None if the user cancels or no file is selected.
+pub fn pick_cassette() -> Option<PathBuf> {
+	FileDialog::new()
+		.add_filter("PNG Cassettes", &COVER_EXTENSIONS)
+		.set_title("Select a Rewind.png Cassette")
+		.pick_file()
+}
+
+/// Everything `record` needs, as answered by the user.
+pub struct WizardAnswers {
+	pub cover: CoverSource,
+	pub audio_files: Vec<PathBuf>,
+	pub output: String,
+}
+
+/// Walks the user through recording a cassette.
+/// Returns `Ok(None)` if they cancel along the way.
+pub fn record_wizard() -> Result<Option<WizardAnswers>, String> {
+	if !std::io::stdin().is_terminal() {
+		return Err("Nothing to record. Pass an image and audio files, or run `rewind record` in a terminal for the interactive wizard.".to_string());
+	}
+	let theme = ColorfulTheme::default();
+	let prompt_err = |e: dialoguer::Error| e.to_string();
+
+	// 1. Cover
+	let cover_choice = Select::with_theme(&theme)
+		.with_prompt("Cover art")
+		.items(&["Pick an image file…", "Generate a label from the tracks' tags", "Plain cassette (no label)"])
+		.default(0)
+		.interact_opt()
+		.map_err(prompt_err)?;
+	let cover = match cover_choice {
+		None => return Ok(None),
+		Some(0) => {
+			let Some(path) = FileDialog::new()
+				.add_filter("PNG Images", &COVER_EXTENSIONS)
+				.set_title("Select the cover art")
+				.pick_file()
+			else { return Ok(None) };
+			CoverSource::File(path.to_string_lossy().to_string())
+		}
+		Some(choice) => {
+			let themes = Theme::value_variants();
+			let names: Vec<String> = themes.iter()
+				.filter_map(|t| t.to_possible_value().map(|v| v.get_name().to_string()))
+				.collect();
+			let Some(idx) = Select::with_theme(&theme)
+				.with_prompt("Color theme")
+				.items(&names)
+				.default(0)
+				.interact_opt()
+				.map_err(prompt_err)?
+			else { return Ok(None) };
+			if choice == 1 { CoverSource::Generated(themes[idx]) } else { CoverSource::Default(themes[idx]) }
+		}
+	};
+
+	// 2. Audio files, possibly from several folders
+	let mut audio_files: Vec<PathBuf> = Vec::new();
+	loop {
+		let picked = FileDialog::new()
+			.add_filter("Audio", &["flac", "mp3", "ogg", "wav"])
+			.set_title("Select audio files")
+			.pick_files()
+			.unwrap_or_default();
+		audio_files.extend(picked);
+
+		if audio_files.is_empty() {
+			log(LogLevel::Warning, "No audio files selected.");
+			return Ok(None);
+		}
+		log(LogLevel::Info, &format!("{} track(s) selected.", audio_files.len()));
+		let more = Confirm::with_theme(&theme)
+			.with_prompt("Add more files?")
+			.default(false)
+			.interact()
+			.map_err(prompt_err)?;
+		if !more { break; }
+	}
+
+	// 3. Track order
+	if audio_files.len() > 1 {
+		let labels: Vec<String> = audio_files.iter().map(|p| p.display().to_string()).collect();
+		let Some(order) = Sort::with_theme(&theme)
+			.with_prompt("Track order (space to pick up/drop, arrows to move, enter to confirm)")
+			.items(&labels)
+			.interact_opt()
+			.map_err(prompt_err)?
+		else { return Ok(None) };
+		audio_files = order.into_iter().map(|i| audio_files[i].clone()).collect();
+	}
+
+	// 4. Output
+	let output: String = Input::with_theme(&theme)
+		.with_prompt("Save cassette as")
+		.default("mixtape.png".to_string())
+		.interact_text()
+		.map_err(prompt_err)?;
+
+	let go = Confirm::with_theme(&theme)
+		.with_prompt(format!("Record {} track(s) to {}?", audio_files.len(), output))
+		.default(true)
+		.interact()
+		.map_err(prompt_err)?;
+	if !go {
+		return Ok(None);
+	}
+
+	Ok(Some(WizardAnswers { cover, audio_files, output }))
+}
@@ -0,0 +1,489 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// CASSETTE ROUND-TRIP TESTS
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Builds small cassettes in memory (a 1×1 PNG cover and a few generated WAV
+// tracks) and reads them back through the library, the way `inspect`, `list`,
+// `extract` and the players do: CRC32 seals, TOC contents, byte-exact track
+// extraction and the audio properties a player needs. The cassettes are
+// assembled here rather than by `record`, but the TOC is encoded by the same
+// `CassetteHeader` code, so a format change that breaks old or new files fails
+// here first.
+
+use std::fs;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use crc32fast::Hasher;
+use lofty::file::{AudioFile, FileType};
+use lofty::probe::Probe;
+use rodio::Source;
+use rewind::format::{padding_before, AlbumInfo, Cassette, CassetteHeader, CassetteLayout, SealedRegion, Side, TocEntry, CODEC_VERSION, FORMAT_VERSION, TRACK_CRC_VERSION, write_seal};
+use rewind::io::{display_width, fit, fit_center, hash_only, temp_path, track_buffer, track_len, truncate, PendingFile, Region, TempFile};
+use rewind::lyrics::{current_line, parse_lrc, LyricLine};
+use rewind::png::{image_end, read_image_info};
+
+/// Sample rate of the generated tracks (low, to keep them tiny).
+const SAMPLE_RATE: u32 = 8000;
+
+/// A 1×1 opaque PNG.
+fn pixel_png() -> Vec<u8> {
+	let mut png = Cursor::new(Vec::new());
+	image::RgbaImage::from_pixel(1, 1, image::Rgba([200, 40, 40, 255]))
+		.write_to(&mut png, image::ImageFormat::Png)
+		.expect("encoding a 1×1 PNG");
+	png.into_inner()
+}
+
+/// A 16-bit WAV of `frames` frames of a rising ramp, so every byte differs from its neighbours.
+fn tiny_wav(channels: u16, frames: u32) -> Vec<u8> {
+	let spec = hound::WavSpec { channels, sample_rate: SAMPLE_RATE, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+	let mut wav = Cursor::new(Vec::new());
+	let mut writer = hound::WavWriter::new(&mut wav, spec).expect("creating a WAV");
+	for i in 0..frames * channels as u32 {
+		writer.write_sample((i % 2000) as i16 * 16).expect("writing a sample");
+	}
+	writer.finalize().expect("finishing a WAV");
+	wav.into_inner()
+}
+
+/// A track to record: its TOC entry (size and CRC are filled in) and audio bytes.
+struct TestTrack {
+	name: &'static str,
+	number: Option<u32>,
+	lyrics: &'static str,
+	audio: Vec<u8>,
+}
+
+/// Lays out a cassette the way `record` does: cover, TOC, audio, CRC32 of everything before it.
+/// `template` gives the version and cassette-level fields; its entries are replaced by `tracks`.
+fn build_cassette(cover: &[u8], tracks: &[TestTrack], template: CassetteHeader) -> Vec<u8> {
+	let version = template.version;
+	let entries = tracks.iter().map(|track| TocEntry {
+		name: track.name.to_string(),
+		size: track.audio.len() as u64,
+		side: Side::A,
+		art: Vec::new(),
+		seek_index: Vec::new(),
+		crc: (version >= TRACK_CRC_VERSION).then(|| crc32fast::hash(&track.audio)),
+		number: track.number,
+		lyrics: track.lyrics.to_string(),
+		codec: (version >= CODEC_VERSION).then_some(FileType::Wav),
+	}).collect();
+	let header = CassetteHeader { entries, ..template };
+
+	let mut bytes = cover.to_vec();
+	header.write(&mut bytes).expect("writing to a Vec cannot fail");
+	for track in tracks {
+		let padding = padding_before(bytes.len() as u64, header.align);
+		bytes.resize(bytes.len() + padding as usize, 0);
+		bytes.extend_from_slice(&track.audio);
+	}
+	let region = SealedRegion::of_layout(cover.len() as u64, &header).expect("a tiny cassette fits");
+	let crc = crc32fast::hash(&bytes);
+	let mut sealed = Cursor::new(bytes);
+	sealed.seek(SeekFrom::End(0)).unwrap();
+	write_seal(&mut sealed, region, crc).expect("the writer ends where the hashed region does");
+	sealed.into_inner()
+}
+
+/// Synced lyrics for the first sample track.
+const SAMPLE_LYRICS: &str = "[ti:Intro]\n[00:00.00]Press play\n[00:00.25]Hear the hiss\n";
+
+/// Two tracks (mono and stereo) with explicit track numbers; the first has lyrics.
+fn sample_tracks() -> Vec<TestTrack> {
+	vec![
+		TestTrack { name: "intro.wav", number: Some(1), lyrics: SAMPLE_LYRICS, audio: tiny_wav(1, SAMPLE_RATE / 2) },
+		TestTrack { name: "outro.wav", number: Some(3), lyrics: "", audio: tiny_wav(2, SAMPLE_RATE / 4) },
+	]
+}
+
+fn sample_album() -> AlbumInfo {
+	AlbumInfo { album: "Test Tape".to_string(), artist: "Rewind".to_string(), year: Some(1984) }
+}
+
+/// Current-format header for the sample cassette: album metadata and a C60 tape.
+fn sample_header() -> CassetteHeader {
+	CassetteHeader { album: Some(sample_album()), tape_minutes: Some(60), ..CassetteHeader::new(Vec::new()) }
+}
+
+fn sample_cassette() -> Vec<u8> {
+	build_cassette(&pixel_png(), &sample_tracks(), sample_header())
+}
+
+/// Writes `bytes` to a file of its own in the temp directory.
+fn temp_cassette(name: &str, bytes: &[u8]) -> PathBuf {
+	let path = std::env::temp_dir().join(format!("rewind-test-{}-{}.png", std::process::id(), name));
+	fs::write(&path, bytes).expect("writing a temp cassette");
+	path
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// INSPECT
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn crc_seal_matches() {
+	let bytes = sample_cassette();
+	let cassette = Cassette::from_bytes(bytes.clone()).expect("a freshly built cassette opens");
+
+	// The streaming hash used by `inspect` agrees with the seal
+	let data_len = (bytes.len() - 4) as u64;
+	let mut hasher = Hasher::new();
+	hash_only(&mut Cursor::new(&bytes), &mut hasher, data_len).expect("hashing from memory");
+	assert_eq!(hasher.finalize().to_le_bytes(), bytes[bytes.len() - 4..]);
+
+	for (i, entry) in cassette.layout.header.entries.iter().enumerate() {
+		assert_eq!(entry.crc, Some(crc32fast::hash(cassette.track(i).unwrap())), "track {} CRC", i + 1);
+	}
+}
+
+#[test]
+fn header_changes_keep_record_and_verify_agreeing() {
+	// Every header field that grows the TOC moves the audio, and with it the end of the sealed region
+	let mut tracks = sample_tracks();
+	let plain = CassetteHeader::new(Vec::new());
+	let legacy = CassetteHeader { version: TRACK_CRC_VERSION, ..CassetteHeader::new(Vec::new()) };
+	let long_album = CassetteHeader { album: Some(AlbumInfo { album: "A".repeat(300), ..sample_album() }), ..sample_header() };
+	for template in [plain, legacy, sample_header(), long_album] {
+		let bytes = build_cassette(&pixel_png(), &tracks, template);
+		let cassette = Cassette::from_bytes(bytes.clone()).expect("the seal covers what the writer hashed");
+		let layout = &cassette.layout;
+		assert_eq!(SealedRegion::of_file(bytes.len() as u64), SealedRegion::of_layout(layout.toc_start, &layout.header));
+	}
+	tracks[1].lyrics = SAMPLE_LYRICS;
+	assert!(Cassette::from_bytes(build_cassette(&pixel_png(), &tracks, sample_header())).is_ok(), "more lyrics");
+
+	// Writing anything between the hashed bytes and the seal is refused instead of sealing the wrong region
+	let mut bytes = Cursor::new(vec![0u8; 10]);
+	bytes.seek(SeekFrom::End(0)).unwrap();
+	assert!(write_seal(&mut bytes, SealedRegion { trailer_start: 8 }, 0).is_err());
+	assert_eq!(SealedRegion::of_file(3), None);
+}
+
+#[test]
+fn verify_reports_progress_and_seal() {
+	let bytes = sample_cassette();
+	let data_len = (bytes.len() - 4) as u64;
+	let mut reported = Vec::new();
+	let seal = Cassette::verify(&mut Cursor::new(&bytes), &mut |done, total| reported.push((done, total)))
+		.expect("verifying from memory");
+
+	assert!(seal.is_intact());
+	assert_eq!(seal.computed, crc32fast::hash(&bytes[..bytes.len() - 4]));
+	assert!(reported.windows(2).all(|pair| pair[0].0 <= pair[1].0), "progress went backwards");
+	assert_eq!(reported.last(), Some(&(data_len, data_len)));
+}
+
+#[test]
+fn damaged_audio_is_detected() {
+	let mut bytes = sample_cassette();
+	let last_audio_byte = bytes.len() - 5;
+	bytes[last_audio_byte] ^= 0xFF;
+	let seal = Cassette::verify(&mut Cursor::new(&bytes), &mut |_, _| {}).expect("verifying from memory");
+	assert!(!seal.is_intact());
+	let error = Cassette::from_bytes(bytes).err().expect("a flipped byte breaks the seal");
+	assert!(error.contains("Checksum mismatch"), "unexpected error: {}", error);
+}
+
+#[test]
+fn strict_check_passes() {
+	let bytes = sample_cassette();
+	let cassette = Cassette::from_bytes(bytes.clone()).unwrap();
+	assert_eq!(cassette.layout.check_strict(&mut Cursor::new(&bytes)), Ok(()));
+}
+
+#[test]
+fn cover_stays_a_plain_png() {
+	let cover = pixel_png();
+	let bytes = sample_cassette();
+	let mut reader = Cursor::new(&bytes);
+
+	assert_eq!(image_end(&mut reader), Ok(cover.len() as u64));
+	let info = read_image_info(&mut reader).expect("reading the cover header");
+	assert_eq!((info.width, info.height), (1, 1));
+
+	// Image viewers ignore the trailing cassette data
+	let decoded = image::load_from_memory(&bytes).expect("the cassette decodes as an image");
+	assert_eq!((decoded.width(), decoded.height()), (1, 1));
+}
+
+#[test]
+fn truncated_cover_is_reported_as_a_broken_cover() {
+	let png = pixel_png();
+	// Cut the 12-byte IEND chunk off entirely, as an interrupted download would
+	let truncated = &png[..png.len() - 12];
+
+	let error = image_end(&mut Cursor::new(truncated)).expect_err("no IEND to find");
+	assert!(error.contains("before its IEND"), "unexpected error: {}", error);
+
+	let bytes = build_cassette(truncated, &sample_tracks(), sample_header());
+	let error = CassetteLayout::read(&mut Cursor::new(&bytes)).err().expect("the TOC can't be located");
+	assert!(error.contains("cover image is broken"), "unexpected error: {}", error);
+}
+
+#[test]
+fn nested_cassette_is_detected() {
+	let inner = sample_cassette();
+	let layout = CassetteLayout::read(&mut Cursor::new(&inner)).unwrap();
+	assert_eq!(layout.second_toc(&mut Cursor::new(&inner)), None);
+
+	// Recorded with a whole cassette as its cover: readers find the old TOC first
+	let nested = build_cassette(&inner, &sample_tracks(), sample_header());
+	let layout = CassetteLayout::read(&mut Cursor::new(&nested)).unwrap();
+	assert_eq!(layout.second_toc(&mut Cursor::new(&nested)), Some(inner.len() as u64));
+}
+
+#[test]
+fn plain_png_is_reported_as_having_no_cassette_data() {
+	let error = CassetteLayout::read(&mut Cursor::new(pixel_png())).err().expect("a PNG alone has no TOC");
+	assert!(error.contains("plain PNG"), "unexpected error: {}", error);
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// LIST
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn lists_tracks_and_album() {
+	let tracks = sample_tracks();
+	let cassette = Cassette::from_bytes(sample_cassette()).unwrap();
+	let header = &cassette.layout.header;
+
+	assert_eq!(header.version, FORMAT_VERSION);
+	assert_eq!(header.album, Some(sample_album()));
+	assert_eq!(header.tape_label().as_deref(), Some("C60"));
+	assert_eq!(header.signature, None);
+
+	let listed: Vec<_> = header.entries.iter().enumerate()
+		.map(|(i, entry)| (entry.display_number(i), entry.name.as_str(), entry.size))
+		.collect();
+	assert_eq!(listed, vec![
+		(1, "intro.wav", tracks[0].audio.len() as u64),
+		(3, "outro.wav", tracks[1].audio.len() as u64),
+	]);
+	assert_eq!(header.entries[0].lyrics, SAMPLE_LYRICS);
+	assert_eq!(header.entries[1].lyrics, "");
+}
+
+#[test]
+fn older_versions_read_with_defaults() {
+	let mut tracks = sample_tracks();
+	tracks.iter_mut().for_each(|track| track.number = None);
+	let template = CassetteHeader { version: TRACK_CRC_VERSION, ..CassetteHeader::new(Vec::new()) };
+	let bytes = build_cassette(&pixel_png(), &tracks, template);
+	let cassette = Cassette::from_bytes(bytes).expect("a v6 cassette opens");
+	let header = &cassette.layout.header;
+
+	assert_eq!(header.version, TRACK_CRC_VERSION);
+	assert_eq!(header.album, None);
+	assert_eq!(header.tape_minutes, None);
+	assert!(header.entries.iter().all(|entry| entry.lyrics.is_empty()), "v6 has no lyrics field");
+	assert_eq!(header.entries.iter().enumerate().map(|(i, entry)| entry.display_number(i)).collect::<Vec<_>>(), vec![1, 2]);
+	assert_eq!(cassette.track(1), Some(tracks[1].audio.as_slice()));
+}
+
+#[test]
+fn double_width_title_fits_the_box() {
+	// 18 characters, but 33 columns: each CJK character takes two
+	let title = "坂本龍一 - 戦場のメリークリスマス";
+	assert_eq!(display_width(title), 33);
+	for width in [24, 25, 46] {
+		let shown = fit(title, width);
+		assert_eq!(display_width(&shown), width, "{:?}", shown);
+		assert_eq!(shown.trim_end().ends_with('…'), width < 33, "{:?}", shown);
+		assert_eq!(display_width(&fit_center(title, width)), width);
+	}
+	// A double-width character that would straddle the edge is left out, not split
+	assert_eq!(truncate("東京", 3), "東…");
+	assert_eq!(truncate("Tokyo", 5), "Tokyo");
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// EXTRACT
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn extracts_tracks_byte_for_byte() {
+	let tracks = sample_tracks();
+	let cassette = Cassette::from_bytes(sample_cassette()).unwrap();
+	for (i, track) in tracks.iter().enumerate() {
+		assert_eq!(cassette.track(i), Some(track.audio.as_slice()), "track {}", i + 1);
+	}
+	assert_eq!(cassette.track(tracks.len()), None);
+}
+
+#[test]
+fn aligned_tracks_start_on_the_boundary() {
+	let tracks = sample_tracks();
+	let bytes = build_cassette(&pixel_png(), &tracks, CassetteHeader { align: Some(512), ..sample_header() });
+	assert!(Cassette::verify(&mut Cursor::new(&bytes), &mut |_, _| {}).unwrap().is_intact());
+	let cassette = Cassette::from_bytes(bytes.clone()).unwrap();
+	assert_eq!(cassette.layout.header.align, Some(512));
+
+	// Readers skip the padding: offsets land on the boundary and the audio comes back intact
+	let offsets = cassette.layout.track_offsets();
+	assert!(offsets.iter().all(|offset| offset % 512 == 0), "offsets {:?}", offsets);
+	assert!(offsets[0] > cassette.layout.audio_start);
+	for (i, track) in tracks.iter().enumerate() {
+		assert_eq!(cassette.track(i), Some(track.audio.as_slice()), "track {}", i + 1);
+	}
+	cassette.layout.check_strict(&mut Cursor::new(bytes)).expect("the padding is accounted for");
+}
+
+#[test]
+fn blank_cassette_reads_as_an_empty_toc() {
+	let bytes = build_cassette(&pixel_png(), &[], sample_header());
+	assert!(Cassette::verify(&mut Cursor::new(&bytes), &mut |_, _| {}).unwrap().is_intact());
+
+	// Reading succeeds with no entries; it's up to each command to say the cassette is blank
+	let cassette = Cassette::from_bytes(bytes.clone()).expect("a blank cassette is still a cassette");
+	assert!(cassette.layout.header.entries.is_empty());
+	assert!(cassette.layout.track_offsets().is_empty());
+	assert_eq!(cassette.track(0), None);
+	cassette.layout.check_strict(&mut Cursor::new(&bytes)).expect("no tracks, no audio bytes");
+	assert_eq!(cassette.layout.second_toc(&mut Cursor::new(&bytes)), None);
+}
+
+#[test]
+fn oversized_track_errors_instead_of_wrapping() {
+	assert_eq!(track_len(1024), Ok(1024));
+	// More than any platform can hold in one buffer; `as usize` would wrap on 32-bit targets
+	let error = track_buffer(u64::MAX).expect_err("u64::MAX bytes can't be allocated");
+	assert!(error.contains("too large for this platform"), "unexpected error: {}", error);
+
+	// A TOC claiming a huge track doesn't wrap into a short slice of the wrong bytes
+	let mut cassette = Cassette::from_bytes(sample_cassette()).unwrap();
+	cassette.layout.header.entries[0].size = u64::MAX;
+	assert_eq!(cassette.track(0), None);
+}
+
+#[test]
+fn extracts_tracks_from_disk() {
+	let tracks = sample_tracks();
+	let path = temp_cassette("extract", &sample_cassette());
+	let mut file = fs::File::open(&path).unwrap();
+	let layout = CassetteLayout::read(&mut file).expect("reading the TOC from disk");
+
+	for ((track, entry), offset) in tracks.iter().zip(&layout.header.entries).zip(layout.track_offsets()) {
+		let mut audio = Vec::new();
+		Region::new(fs::File::open(&path).unwrap(), offset, entry.size).unwrap()
+			.read_to_end(&mut audio).unwrap();
+		assert_eq!(audio, track.audio, "{}", entry.name);
+	}
+	let _ = fs::remove_file(path);
+}
+
+#[test]
+fn temp_files_are_removed_on_drop() {
+	assert_ne!(temp_path("track.wav"), temp_path("track.wav"));
+
+	let (temp, mut file) = TempFile::create("side a/track.wav").expect("creating a temp file");
+	let path = temp.path().to_path_buf();
+	assert!(path.starts_with(std::env::temp_dir()));
+	file.write_all(b"intermediate").unwrap();
+	assert_eq!(fs::read(&path).unwrap(), b"intermediate");
+
+	drop(file);
+	drop(temp);
+	assert!(!path.exists());
+}
+
+#[test]
+fn pending_files_appear_only_when_committed() {
+	let path = temp_path("tape.png");
+	let temp = PathBuf::from(format!("{}.tmp", path.display()));
+
+	// Dropped without a commit: neither file is left
+	let (pending, mut file) = PendingFile::create(&path).expect("creating a pending file");
+	file.write_all(b"half a tape").unwrap();
+	drop(file);
+	assert!(temp.exists());
+	drop(pending);
+	assert!(!temp.exists() && !path.exists());
+
+	// Committed: the temporary name is gone and the file is in place
+	let (pending, file) = PendingFile::create(&path).expect("creating a pending file");
+	let mut writer = std::io::BufWriter::new(file);
+	writer.write_all(b"whole tape").unwrap();
+	pending.commit(writer).expect("committing");
+	assert!(!temp.exists());
+	assert_eq!(fs::read(&path).unwrap(), b"whole tape");
+	fs::remove_file(&path).unwrap();
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// PLAY METADATA
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn reads_play_metadata() {
+	let cassette = Cassette::from_bytes(sample_cassette()).unwrap();
+	// (channels, frames) of each track in `sample_tracks`
+	let expected = [(1u16, SAMPLE_RATE / 2), (2, SAMPLE_RATE / 4)];
+
+	for (i, (channels, frames)) in expected.into_iter().enumerate() {
+		let audio = cassette.track(i).unwrap().to_vec();
+
+		// What `inspect` and the TUI show
+		let tagged = Probe::new(Cursor::new(&audio)).guess_file_type().unwrap().read().expect("probing the track");
+		let properties = tagged.properties();
+		assert_eq!(properties.duration().as_millis(), (frames * 1000 / SAMPLE_RATE) as u128, "track {} duration", i + 1);
+		assert_eq!(properties.sample_rate(), Some(SAMPLE_RATE));
+		assert_eq!(properties.channels(), Some(channels as u8));
+
+		// What the players decode
+		let len = audio.len() as u64;
+		let decoder = rodio::Decoder::builder()
+			.with_data(Cursor::new(audio))
+			.with_byte_len(len)
+			.with_seekable(true)
+			.build()
+			.expect("decoding the track");
+		assert_eq!(decoder.channels(), channels);
+		assert_eq!(decoder.sample_rate(), SAMPLE_RATE);
+		assert_eq!(decoder.count(), frames as usize * channels as usize);
+	}
+}
+
+#[test]
+fn bundled_selftest_cassette_plays() {
+	// Recorded by `record`, unlike the cassettes above; `rewind selftest` runs the same steps
+	let bytes = include_bytes!("../assets/cassettes/selftest.png");
+	let cassette = Cassette::from_bytes(bytes.to_vec()).expect("the seal is intact");
+	cassette.layout.check_strict(&mut Cursor::new(bytes)).expect("a well-formed layout");
+	assert_eq!(cassette.layout.header.entries.len(), 1);
+
+	let entry = &cassette.layout.header.entries[0];
+	let audio = cassette.track(0).unwrap().to_vec();
+	assert_eq!(entry.crc, Some(crc32fast::hash(&audio)));
+	let len = audio.len() as u64;
+	let decoder = rodio::Decoder::builder()
+		.with_data(Cursor::new(audio))
+		.with_byte_len(len)
+		.with_hint("wav")
+		.build()
+		.expect("decoding the beep");
+	assert_eq!(decoder.count(), 1600, "a fifth of a second at 8 kHz, mono");
+}
+
+// ══════════════════════════════════════════════════════════════════════════════
+// LYRICS
+// ══════════════════════════════════════════════════════════════════════════════
+
+#[test]
+fn parses_synced_lyrics() {
+	let lines = parse_lrc(SAMPLE_LYRICS);
+	assert_eq!(lines, vec![
+		LyricLine { at: Duration::ZERO, text: "Press play".to_string() },
+		LyricLine { at: Duration::from_millis(250), text: "Hear the hiss".to_string() },
+	]);
+	assert_eq!(current_line(&lines, Duration::from_millis(100)), Some(0));
+	assert_eq!(current_line(&lines, Duration::from_secs(5)), Some(1));
+
+	// Repeated choruses, an offset, word timestamps and untimed lines
+	let lines = parse_lrc("[offset:+500]\nNot timed\n[00:03.00][00:01]La <00:01.20>la\n[bad:stamp]x\n");
+	let timed: Vec<_> = lines.iter().map(|line| (line.at.as_millis(), line.text.as_str())).collect();
+	assert_eq!(timed, vec![(500, "La la"), (2500, "La la")]);
+	assert_eq!(current_line(&lines, Duration::from_millis(100)), None);
+}
@@ -0,0 +1,56 @@
+// ══════════════════════════════════════════════════════════════════════════════
+// RECORD TESTS
+// ══════════════════════════════════════════════════════════════════════════════
+//
+// Runs the `rewind` binary on real files in a scratch directory, for the checks
+// `record` makes before it writes anything.
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A fresh scratch directory for one test.
+fn scratch_dir(name: &str) -> PathBuf {
+	let dir = std::env::temp_dir().join(format!("rewind-record-{}-{}", std::process::id(), name));
+	let _ = fs::remove_dir_all(&dir);
+	fs::create_dir_all(&dir).expect("creating a scratch directory");
+	dir
+}
+
+/// Writes a short silent 8 kHz mono WAV to `path`.
+fn write_wav(path: &PathBuf) {
+	let spec = hound::WavSpec { channels: 1, sample_rate: 8000, bits_per_sample: 16, sample_format: hound::SampleFormat::Int };
+	let mut writer = hound::WavWriter::create(path, spec).expect("creating a WAV");
+	for _ in 0..800 {
+		writer.write_sample(0i16).unwrap();
+	}
+	writer.finalize().unwrap();
+}
+
+/// Runs `rewind` with `args` inside `dir`, keeping its config and history there too.
+fn rewind(dir: &PathBuf, args: &[&str]) -> Output {
+	Command::new(env!("CARGO_BIN_EXE_rewind"))
+		.args(args)
+		.current_dir(dir)
+		.env("HOME", dir)
+		.env("XDG_CONFIG_HOME", dir)
+		.env("XDG_DATA_HOME", dir)
+		.output()
+		.expect("running rewind")
+}
+
+#[test]
+fn empty_input_is_rejected_before_anything_is_written() {
+	let dir = scratch_dir("empty-input");
+	write_wav(&dir.join("good.wav"));
+	fs::write(dir.join("empty.wav"), b"").unwrap();
+
+	let output = rewind(&dir, &["record", "--no-cover", "good.wav", "empty.wav", "--output", "tape.png"]);
+	let stdout = String::from_utf8_lossy(&output.stdout);
+	assert!(stdout.contains("'empty.wav' rejected: This audio file is empty."), "unexpected output:\n{}", stdout);
+	assert!(!output.status.success(), "a failed recording exits non-zero");
+	assert!(!dir.join("tape.png").exists(), "no cassette is written");
+	assert!(!dir.join("tape.png.tmp").exists(), "no partial cassette is left behind");
+
+	fs::remove_dir_all(&dir).unwrap();
+}